@@ -1,64 +1,253 @@
 use std::time::{Duration, Instant};
 
-/// An empty struct whose methods permit to retrieve the duration execution of a given function.
-pub struct Benchmark {}
+/// Two-sided 95% quantiles of the Student's t-distribution for 1 to 30 degrees of freedom, the
+/// normal one being used above.
+const T_95: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228,
+    2.201, 2.179, 2.160, 2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086,
+    2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042,
+];
+const Z_95: f64 = 1.960;
+
+/// Settings of the benchmark of a function: the function is executed a few times to warm up the
+/// caches and the allocator, then once for each sample.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Benchmark {
+    iterations: u32,
+    warmup: u32,
+}
 
 impl Benchmark {
 
-    /// Benchmarks a function by executing it several times and averaging the results
+    /// Creates a benchmark without warm-up.
+    ///
+    /// # Arguments
+    /// * `iterations` - Amount of samples to be taken.
+    pub fn new(iterations: u32) -> Self {
+        Benchmark { iterations, warmup: 0 }
+    }
+
+    /// Sets the amount of times the function is executed before taking samples.
+    pub fn set_warmup(&mut self, warmup: u32) {
+        self.warmup = warmup;
+    }
+
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    pub fn warmup(&self) -> u32 {
+        self.warmup
+    }
+
+    /// Benchmarks a function by executing it several times and recording the duration of each execution.
     ///
     /// # Arguments
     /// * `func` - Function to be executed.
-    /// * `iterations` - Amount of times that the function needs to be executed for average.
     ///
     /// # Returns
-    /// A result containing either the averaged duration or a string containing an error.
+    /// A result containing either the samples and the result of the last execution, or a string
+    /// containing an error if there are no iterations or any execution fails.
     ///
     /// # Example
     /// ```
     /// use delegation::benchmark::Benchmark;
     /// fn print_example() -> Result<(), String> { println!("Example"); Ok(())}
-    /// let result = Benchmark::benchmark_function(print_example, 100);
+    /// let mut benchmark = Benchmark::new(100);
+    /// benchmark.set_warmup(10);
+    /// let (samples, _) = benchmark.run(print_example).unwrap();
+    /// assert_eq!(samples.durations().len(), 100);
     /// ```
-    pub fn benchmark_function<F, T>(func: F, iterations: i8) -> Result<(Duration, T), String>
+    pub fn run<F, T, Err>(&self, func: F) -> Result<(Samples, T), String>
     where
-        F: Fn() -> Result<T, String>
+        F: Fn() -> Result<T, Err>,
+        Err: std::fmt::Display,
     {
-        let mut start: Instant;
-        let mut result = None;
-        let mut total: f64 = 0f64;
+        if self.iterations == 0 {
+            return Err("Functions must be benchmarked at least once".to_string())
+        }
 
-        for _ in 0..iterations {
-            start = Instant::now();
+        for i in 0..self.warmup {
+            if let Err(err) = func() {
+                return Err(format!("Benchmarked function returned error at warm-up round {i} [{err}]"))
+            }
+        }
+
+        let mut durations: Vec<Duration> = Vec::with_capacity(self.iterations as usize);
+        let mut result = None;
+        for i in 0..self.iterations {
+            let start = Instant::now();
             match func() {
                 Ok(inner) => { result = Some(inner) }
-                Err(err) => { println!("Benchmarked function returned error [{err}]") }
+                Err(err) => { return Err(format!("Benchmarked function returned error at iteration {i} [{err}]")) }
             }
-
-            total = total + start.elapsed().as_secs_f64();
+            durations.push(start.elapsed());
         }
 
-        let average_duration: Duration = Duration::from_secs_f64(total / (iterations as f64));
         match result {
-            Some(result) => { Ok((average_duration, result)) },
+            Some(result) => { Ok((Samples::new(durations), result)) },
             None => { Err("Function did not return a result".to_string()) }
         }
     }
 
+    /// Benchmarks a function without warm-up, see `Benchmark::run`.
+    ///
+    /// # Arguments
+    /// * `func` - Function to be executed.
+    /// * `iterations` - Amount of samples to be taken.
+    ///
+    /// # Returns
+    /// A result containing either the samples and the result of the last execution, or a string
+    /// containing an error.
+    pub fn benchmark_function<F, T, Err>(func: F, iterations: u32) -> Result<(Samples, T), String>
+    where
+        F: Fn() -> Result<T, Err>,
+        Err: std::fmt::Display,
+    {
+        Benchmark::new(iterations).run(func)
+    }
 
     /// Benchmarks an adapter initialization function. This is needed because when creating instances nested inside adapters, they're of type "dyn Adapter".
     ///
     /// # Arguments
     /// * `func` - Function to be executed.
-    /// * `iterations` - Amount of times that the function needs to be executed for average.
+    /// * `iterations` - Amount of samples to be taken.
     ///
     /// # Returns
-    /// A result containing either the averaged duration or a string containing an error.
-    pub fn benchmark_initialization<F, T>(func: F, iterations: i8) -> Result<(Duration, Box<T>), String>
+    /// A result containing either the samples and the boxed result, or a string containing an error.
+    pub fn benchmark_initialization<F, T, Err>(func: F, iterations: u32) -> Result<(Samples, Box<T>), String>
     where
-        F: Fn() -> Result<T, String>,
+        F: Fn() -> Result<T, Err>,
+        Err: std::fmt::Display,
     {
-        let (duration, result) = Benchmark::benchmark_function(func, iterations)?;
-        Ok((duration, Box::new(result)))
+        let (samples, result) = Benchmark::benchmark_function(func, iterations)?;
+        Ok((samples, Box::new(result)))
+    }
+}
+
+/// Durations of the executions of a benchmarked function, in order of execution.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Samples {
+    durations: Vec<Duration>,
+}
+
+/// Summary of the distribution of some samples.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Statistics {
+    pub mean: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+    pub min: Duration,
+    pub max: Duration,
+    /// Sample standard deviation.
+    pub std_dev: Duration,
+    /// Bounds of the 95% confidence interval of the mean.
+    pub ci95: (Duration, Duration),
+}
+
+impl Samples {
+    pub fn new(durations: Vec<Duration>) -> Self {
+        Samples { durations }
+    }
+
+    pub fn durations(&self) -> &Vec<Duration> {
+        &self.durations
+    }
+
+    pub fn mean(&self) -> Duration {
+        if self.durations.is_empty() {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64(self.durations.iter().map(Duration::as_secs_f64).sum::<f64>() / self.durations.len() as f64)
+    }
+
+    /// Returns the smallest sample such that at least `percentile`% of the samples are not greater,
+    /// zero if there are no samples.
+    pub fn percentile(&self, percentile: f64) -> Duration {
+        let mut sorted = self.durations.clone();
+        sorted.sort();
+        let rank = (percentile / 100.0 * sorted.len() as f64).ceil() as usize;
+        sorted.get(rank.saturating_sub(1)).copied().unwrap_or(Duration::ZERO)
+    }
+
+    pub fn statistics(&self) -> Statistics {
+        let mean = self.mean();
+        let n = self.durations.len();
+
+        let std_dev = if n > 1 {
+            let variance = self.durations.iter()
+                .map(|duration| (duration.as_secs_f64() - mean.as_secs_f64()).powi(2))
+                .sum::<f64>() / (n - 1) as f64;
+            variance.sqrt()
+        } else {
+            0.0
+        };
+        let t = T_95.get(n.saturating_sub(2)).copied().unwrap_or(Z_95);
+        let margin = if n > 1 { t * std_dev / (n as f64).sqrt() } else { 0.0 };
+
+        Statistics {
+            mean,
+            median: self.percentile(50.0),
+            p95: self.percentile(95.0),
+            p99: self.percentile(99.0),
+            min: self.durations.iter().min().copied().unwrap_or(Duration::ZERO),
+            max: self.durations.iter().max().copied().unwrap_or(Duration::ZERO),
+            std_dev: Duration::from_secs_f64(std_dev),
+            ci95: (Duration::from_secs_f64((mean.as_secs_f64() - margin).max(0.0)), Duration::from_secs_f64(mean.as_secs_f64() + margin)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn summarize_samples() {
+        let samples = Samples::new((1..=100).rev().map(Duration::from_micros).collect());
+        let statistics = samples.statistics();
+        assert_eq!(statistics.median, Duration::from_micros(50));
+        assert_eq!(statistics.p95, Duration::from_micros(95));
+        assert_eq!(statistics.p99, Duration::from_micros(99));
+        assert_eq!(statistics.min, Duration::from_micros(1));
+        assert_eq!(statistics.max, Duration::from_micros(100));
+        assert_eq!(statistics.mean.as_nanos(), 50_500);
+        // Standard deviation of 1..=100 is sqrt(841.67) = 29.01
+        assert_eq!(statistics.std_dev.as_nanos() / 10, 2_901);
+        // With 100 samples the normal quantile gives a margin of 1.96 * 29.01 / 10 = 5.69
+        assert!(statistics.ci95.0 < statistics.mean && statistics.mean < statistics.ci95.1);
+        assert_eq!((statistics.ci95.1 - statistics.mean).as_nanos() / 10, 568);
+
+        let statistics = Samples::new(vec![Duration::from_micros(7)]).statistics();
+        assert_eq!(statistics.ci95, (Duration::from_micros(7), Duration::from_micros(7)));
+    }
+
+    #[test]
+    fn fail_on_any_error() {
+        let calls = Cell::new(0);
+        let mut benchmark = Benchmark::new(5);
+        benchmark.set_warmup(2);
+
+        let result = benchmark.run(|| {
+            calls.set(calls.get() + 1);
+            if calls.get() == 4 { Err("failed") } else { Ok(calls.get()) }
+        });
+        assert_eq!(result, Err(String::from("Benchmarked function returned error at iteration 1 [failed]")));
+
+        calls.set(0);
+        let result = benchmark.run(|| {
+            calls.set(calls.get() + 1);
+            Ok::<u32, String>(calls.get())
+        });
+        match result {
+            Ok((samples, last)) => {
+                assert_eq!(samples.durations().len(), 5);
+                assert_eq!(last, 7);
+            }
+            Err(err) => panic!("{err}"),
+        }
+        assert!(Benchmark::new(0).run(|| Ok::<(), String>(())).is_err());
     }
-}
\ No newline at end of file
+}
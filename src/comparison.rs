@@ -0,0 +1,250 @@
+use crate::experiment::{Metric, PARAMETER_COLUMNS};
+use csv::Reader;
+use std::fmt::{Display, Formatter};
+use std::fs::read_dir;
+use std::path::Path;
+
+/// Relative increases of the metrics, in percent, beyond which a new run is a regression.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Thresholds {
+    /// Threshold of the durations, which vary from one run to another.
+    pub duration: f64,
+    /// Threshold of the lengths of the VP JWTs.
+    pub length: f64,
+}
+
+impl Thresholds {
+    pub fn of(&self, metric: Metric) -> f64 {
+        if metric.is_duration() { self.duration } else { self.length }
+    }
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Thresholds { duration: 10.0, length: 1.0 }
+    }
+}
+
+/// Value of a metric of a scheme at the same parameters in the baseline and in the new run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Difference {
+    /// Name of the csv file, without extension.
+    pub file: String,
+    pub metric: Metric,
+    pub scheme: String,
+    /// Parameters of the row, e.g. `delegators=3, permissions=10, disclosed=1`, or its number in
+    /// files without parameters.
+    pub parameters: String,
+    pub baseline: f64,
+    pub current: f64,
+}
+
+impl Difference {
+    /// Returns the relative change from the baseline to the new run, in percent.
+    pub fn change(&self) -> f64 {
+        if self.baseline == 0.0 {
+            return if self.current == 0.0 { 0.0 } else { f64::INFINITY }
+        }
+        (self.current - self.baseline) / self.baseline * 100.0
+    }
+
+    pub fn is_regression(&self, thresholds: &Thresholds) -> bool {
+        self.change() > thresholds.of(self.metric)
+    }
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} [{}]: {} -> {} {} ({:+.1}%)", self.file, self.scheme, self.parameters,
+               self.baseline, self.current, self.metric.unit(), self.change())
+    }
+}
+
+/// Comparison of the csv files written by two runs of the experiments, whose rows are aligned by
+/// their parameters and scheme.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Comparison {
+    /// Values found in both runs.
+    pub differences: Vec<Difference>,
+    /// Files and values of the baseline that the new run did not write.
+    pub missing: Vec<String>,
+    /// Files and values of the new run that are not in the baseline.
+    pub added: Vec<String>,
+}
+
+// Metric of a file and the values of each scheme at each row, keyed by the parameters of the row
+type Values = (Metric, Vec<(String, String, f64)>);
+
+impl Comparison {
+
+    /// Compares the metrics of the experiments of a baseline run with the ones of a new run.
+    ///
+    /// # Arguments
+    /// * `baseline_dir` - Directory of the csv files of the baseline run.
+    /// * `current_dir` - Directory of the csv files of the new run.
+    ///
+    /// # Returns
+    /// The comparison or a string containing an error, if a file cannot be read.
+    pub fn new(baseline_dir: &Path, current_dir: &Path) -> Result<Self, String> {
+        let baseline_files = Self::files(baseline_dir)?;
+        let current_files = Self::files(current_dir)?;
+        let mut comparison = Comparison::default();
+
+        for file in baseline_files.iter() {
+            if !current_files.contains(file) {
+                comparison.missing.push(format!("{file} is missing from the new run"));
+                continue;
+            }
+            let (metric, baseline) = Self::read_values(baseline_dir, file)?;
+            let (_, current) = Self::read_values(current_dir, file)?;
+
+            for (parameters, scheme, value) in baseline.iter() {
+                match current.iter().find(|(other_parameters, other_scheme, _)| other_parameters == parameters && other_scheme == scheme) {
+                    Some((_, _, current)) => comparison.differences.push(Difference {
+                        file: file.clone(),
+                        metric,
+                        scheme: scheme.clone(),
+                        parameters: parameters.clone(),
+                        baseline: *value,
+                        current: *current,
+                    }),
+                    None => comparison.missing.push(format!("{file} {scheme} [{parameters}] is missing from the new run")),
+                }
+            }
+            for (parameters, scheme, _) in current.iter() {
+                if !baseline.iter().any(|(other_parameters, other_scheme, _)| other_parameters == parameters && other_scheme == scheme) {
+                    comparison.added.push(format!("{file} {scheme} [{parameters}] is not in the baseline"));
+                }
+            }
+        }
+        for file in current_files.iter().filter(|file| !baseline_files.contains(file)) {
+            comparison.added.push(format!("{file} is not in the baseline"));
+        }
+        Ok(comparison)
+    }
+
+    /// Returns the values that increased beyond the threshold of their metric.
+    pub fn regressions(&self, thresholds: &Thresholds) -> Vec<&Difference> {
+        self.differences.iter().filter(|difference| difference.is_regression(thresholds)).collect()
+    }
+
+    // Sorted names of the csv files of a directory named after a metric of an experiment
+    fn files(dir: &Path) -> Result<Vec<String>, String> {
+        let entries = match read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) => { return Err(format!("Could not read directory {}: [{err}]", dir.display())) }
+        };
+
+        let mut files: Vec<String> = entries.flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "csv"))
+            .filter_map(|path| path.file_stem().and_then(|stem| stem.to_str()).map(str::to_string))
+            .filter(|file| Metric::parse_file_name(file).is_some())
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+
+    fn read_values(dir: &Path, file: &str) -> Result<Values, String> {
+        let metric = match Metric::parse_file_name(file) {
+            Some((_, metric)) => metric,
+            None => { return Err(format!("{file} is not the file of a metric")) }
+        };
+        let path = dir.join(format!("{file}.csv"));
+        let mut reader = match Reader::from_path(&path) {
+            Ok(reader) => reader,
+            Err(err) => { return Err(format!("Could not open {}: [{err}]", path.display())) }
+        };
+        let headers = match reader.headers() {
+            Ok(headers) => headers.clone(),
+            Err(err) => { return Err(format!("Could not read the columns of {}: [{err}]", path.display())) }
+        };
+        let columns: Vec<(usize, &str)> = headers.iter().enumerate().collect();
+        let (parameters, schemes): (Vec<_>, Vec<_>) = columns.into_iter()
+            .partition(|(_, column)| PARAMETER_COLUMNS.contains(column));
+
+        let mut values = vec![];
+        for (row, record) in reader.records().enumerate() {
+            let record = match record {
+                Ok(record) => record,
+                Err(err) => { return Err(format!("Could not read row {row} of {}: [{err}]", path.display())) }
+            };
+            let key = if parameters.is_empty() {
+                format!("row={}", row + 1)
+            } else {
+                parameters.iter()
+                    .map(|(i, column)| format!("{column}={}", record.get(*i).unwrap_or_default()))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            };
+            for (i, scheme) in schemes.iter() {
+                let value = match record.get(*i).map(str::parse::<f64>) {
+                    Some(Ok(value)) => value,
+                    _ => { return Err(format!("Invalid value of {scheme} at row {row} of {}", path.display())) }
+                };
+                values.push((key.clone(), scheme.to_string(), value));
+            }
+        }
+        Ok((metric, values))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, remove_dir_all, write};
+
+    #[test]
+    fn align_runs_by_parameters() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = std::env::temp_dir().join(format!("delegation-comparison-{}", std::process::id()));
+        let (baseline, current) = (dir.join("baseline"), dir.join("current"));
+        create_dir_all(&baseline)?;
+        create_dir_all(&current)?;
+
+        write(baseline.join("iop_vp_verification.csv"), "delegators,permissions,disclosed,Ours,PJVs\n2,1,1,1000,10\n2,2,2,2000,20\n")?;
+        // Rows in another order, a new scheme, a faster row, a slower one and one within the threshold
+        write(current.join("iop_vp_verification.csv"), "delegators,permissions,disclosed,Ours,Ours (batched),PJVs\n2,2,2,1500,900,21\n2,1,1,1200,500,10\n")?;
+        write(baseline.join("iop_vp_jwt_length.csv"), "Ours,PJVs\n1822,2233\n")?;
+        write(current.join("iop_vp_jwt_length.csv"), "Ours,PJVs\n1900,2233\n")?;
+        write(baseline.join("iop_vp_verification_stats.csv"), "delegators,scheme,mean\n2,Ours,1000\n")?;
+        write(baseline.join("rp_vc_issuance.csv"), "Ours,PJVs\n1,1\n")?;
+
+        let comparison = Comparison::new(&baseline, &current)?;
+        assert_eq!(comparison.differences.len(), 6);
+        assert_eq!(comparison.added, vec![
+            String::from("iop_vp_verification Ours (batched) [delegators=2, permissions=2, disclosed=2] is not in the baseline"),
+            String::from("iop_vp_verification Ours (batched) [delegators=2, permissions=1, disclosed=1] is not in the baseline"),
+        ]);
+        assert_eq!(comparison.missing, vec![String::from("rp_vc_issuance is missing from the new run")]);
+
+        let regressions = comparison.regressions(&Thresholds::default());
+        assert_eq!(regressions.len(), 2);
+        assert_eq!(regressions[0].to_string(), "iop_vp_jwt_length Ours [row=1]: 1822 -> 1900 bytes (+4.3%)");
+        assert_eq!(regressions[1].to_string(), "iop_vp_verification Ours [delegators=2, permissions=1, disclosed=1]: 1000 -> 1200 us (+20.0%)");
+        assert!(comparison.regressions(&Thresholds { duration: 25.0, length: 5.0 }).is_empty());
+
+        remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn report_dropped_scheme() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = std::env::temp_dir().join(format!("delegation-comparison-dropped-{}", std::process::id()));
+        let (baseline, current) = (dir.join("baseline"), dir.join("current"));
+        create_dir_all(&baseline)?;
+        create_dir_all(&current)?;
+
+        write(baseline.join("iop_vp_verification.csv"), "delegators,permissions,disclosed,Ours,PJVs\n2,1,1,1000,10\n")?;
+        write(current.join("iop_vp_verification.csv"), "delegators,permissions,disclosed,Ours\n2,1,1,1000\n")?;
+
+        let comparison = Comparison::new(&baseline, &current)?;
+        assert!(comparison.regressions(&Thresholds::default()).is_empty());
+        assert!(comparison.added.is_empty());
+        assert_eq!(comparison.missing, vec![
+            String::from("iop_vp_verification PJVs [delegators=2, permissions=1, disclosed=1] is missing from the new run"),
+        ]);
+
+        remove_dir_all(dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,370 @@
+use crate::benchmark::Benchmark;
+use crate::csv_writer::CSV_DIR;
+use crate::experiment::Sweep;
+use clap::{Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Relative path of the default directory where the charts are rendered.
+pub const PLOTS_DIR: &str = "./plots";
+
+/// Values taken by a parameter of the benchmark, written as a comma-separated list of values and
+/// inclusive ranges with an optional step, e.g. `1,4,7,10`, `1-10` or `1-10:3`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RangeValue", into = "Vec<usize>")]
+pub struct ParameterRange(Vec<usize>);
+
+impl ParameterRange {
+    pub fn values(&self) -> &Vec<usize> {
+        &self.0
+    }
+
+    fn parse_value(value: &str) -> Result<usize, String> {
+        match usize::from_str(value.trim()) {
+            Ok(value) => Ok(value),
+            Err(err) => { Err(format!("Invalid parameter value {value} [{err}]")) }
+        }
+    }
+}
+
+impl FromStr for ParameterRange {
+    type Err = String;
+
+    fn from_str(range: &str) -> Result<Self, Self::Err> {
+        let mut values: Vec<usize> = vec![];
+        for part in range.split(',') {
+            let (bounds, step) = match part.split_once(':') {
+                Some((bounds, step)) => (bounds, Self::parse_value(step)?),
+                None => (part, 1),
+            };
+            let (start, end) = match bounds.split_once('-') {
+                Some((start, end)) => (Self::parse_value(start)?, Self::parse_value(end)?),
+                None => {
+                    let value = Self::parse_value(bounds)?;
+                    (value, value)
+                }
+            };
+
+            if step == 0 {
+                return Err(format!("The step of range {part} must be at least 1"))
+            } else if start > end {
+                return Err(format!("Range {part} is empty"))
+            }
+            values.extend((start..=end).step_by(step));
+        }
+        Ok(ParameterRange(values))
+    }
+}
+
+impl From<usize> for ParameterRange {
+    fn from(value: usize) -> Self {
+        ParameterRange(vec![value])
+    }
+}
+
+impl From<ParameterRange> for Vec<usize> {
+    fn from(range: ParameterRange) -> Self {
+        range.0
+    }
+}
+
+/// Ways a range can be written in a configuration file.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RangeValue {
+    Value(usize),
+    Values(Vec<usize>),
+    Text(String),
+}
+
+impl TryFrom<RangeValue> for ParameterRange {
+    type Error = String;
+
+    fn try_from(value: RangeValue) -> Result<Self, Self::Error> {
+        match value {
+            RangeValue::Value(value) => Ok(ParameterRange::from(value)),
+            RangeValue::Values(values) if values.is_empty() => Err(String::from("Ranges must have at least one value")),
+            RangeValue::Values(values) => Ok(ParameterRange(values)),
+            RangeValue::Text(text) => ParameterRange::from_str(&text),
+        }
+    }
+}
+
+/// Pairing-friendly curve of the accumulators of our scheme.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+pub enum CurveName {
+    #[default]
+    #[serde(rename = "bn254")]
+    #[value(name = "bn254")]
+    Bn254,
+    #[serde(rename = "bls12-381")]
+    #[value(name = "bls12-381")]
+    Bls12_381,
+}
+
+impl CurveName {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CurveName::Bn254 => "bn254",
+            CurveName::Bls12_381 => "bls12-381",
+        }
+    }
+}
+
+/// Experiment of the benchmark. Each combination of the parameters that are not swept along the
+/// rows is written to its own files, whose name ends with the parameters having several values.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Subcommand)]
+#[serde(tag = "experiment", rename_all = "kebab-case")]
+pub enum ExperimentConfig {
+    /// Iterates over the length of a chain, with a row for each delegator.
+    Delegators {
+        /// Delegators whose credential is measured, the chain being as long as the largest one.
+        #[arg(long, default_value = "1-10")]
+        delegators: ParameterRange,
+        /// Permissions granted at each level.
+        #[arg(long, default_value = "10")]
+        permissions: ParameterRange,
+        /// Permissions disclosed at each level.
+        #[arg(long, default_value = "1,4,7,10")]
+        disclosed: ParameterRange,
+    },
+    /// Iterates over the permissions granted and disclosed along a chain, with a row for each
+    /// amount of permissions.
+    Permissions {
+        /// Length of the chain.
+        #[arg(long, default_value = "10")]
+        delegators: ParameterRange,
+        /// Permissions granted at each level.
+        #[arg(long, default_value = "1-10")]
+        permissions: ParameterRange,
+    },
+    /// Retains permissions at each level of a chain, with a row for each delegator.
+    Retain {
+        /// Length of the chain.
+        #[arg(long, default_value = "10")]
+        delegators: ParameterRange,
+        /// Permissions granted by the root credential.
+        #[arg(long, default_value = "10")]
+        permissions: ParameterRange,
+        /// Permissions retained at each level, by default the permissions divided by the delegators.
+        #[arg(long)]
+        #[serde(default)]
+        retained: Option<usize>,
+    },
+}
+
+impl ExperimentConfig {
+
+    /// Returns the sweeps of the experiment and the names of their files.
+    ///
+    /// # Returns
+    /// A vector of the named sweeps or a string containing an error, if a sweep is not valid.
+    pub fn sweeps(&self) -> Result<Vec<(String, Sweep)>, String> {
+        let mut sweeps: Vec<(String, Sweep)> = vec![];
+        match self {
+            ExperimentConfig::Delegators { delegators, permissions, disclosed } => {
+                for permission in permissions.values() {
+                    for disclose in disclosed.values() {
+                        let name = format!("{disclose}_iod{}", Self::suffix("p", permissions, *permission));
+                        sweeps.push((name, Sweep::delegators(delegators.values(), *permission, *disclose)?));
+                    }
+                }
+            }
+            ExperimentConfig::Permissions { delegators, permissions } => {
+                for delegator in delegators.values() {
+                    let name = format!("iop{}", Self::suffix("d", delegators, *delegator));
+                    sweeps.push((name, Sweep::permissions(*delegator, permissions.values())?));
+                }
+            }
+            ExperimentConfig::Retain { delegators, permissions, retained } => {
+                for delegator in delegators.values() {
+                    for permission in permissions.values() {
+                        let retain = match retained {
+                            Some(retained) => *retained,
+                            None => permission.checked_div(*delegator).unwrap_or(0),
+                        };
+                        let name = format!("rp{}{}", Self::suffix("d", delegators, *delegator), Self::suffix("p", permissions, *permission));
+                        sweeps.push((name, Sweep::retained_permissions(*delegator, *permission, retain)?));
+                    }
+                }
+            }
+        }
+        Ok(sweeps)
+    }
+
+    // Parameters with a single value are left out of the names, as in the files of the paper
+    fn suffix(prefix: &str, range: &ParameterRange, value: usize) -> String {
+        if range.values().len() > 1 {
+            format!("_{prefix}{value}")
+        } else {
+            String::new()
+        }
+    }
+}
+
+fn default_iterations() -> u32 {
+    100
+}
+
+fn default_warmup() -> u32 {
+    3
+}
+
+fn default_output_dir() -> PathBuf {
+    PathBuf::from(CSV_DIR)
+}
+
+fn default_plots() -> bool {
+    true
+}
+
+fn default_plots_dir() -> PathBuf {
+    PathBuf::from(PLOTS_DIR)
+}
+
+/// Benchmark matrix, as read from a TOML or JSON configuration file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BenchmarkConfig {
+    /// Amount of samples taken of each operation.
+    #[serde(default = "default_iterations")]
+    pub iterations: u32,
+    /// Amount of times each operation is executed before taking samples.
+    #[serde(default = "default_warmup")]
+    pub warmup: u32,
+    #[serde(default)]
+    pub curve: CurveName,
+    /// Directory where the csv files are written.
+    #[serde(default = "default_output_dir")]
+    pub output_dir: PathBuf,
+    /// Whether every csv file is also written as JSON lines.
+    #[serde(default)]
+    pub json_lines: bool,
+    /// Whether the charts of the csv files are rendered after running the experiments.
+    #[serde(default = "default_plots")]
+    pub plots: bool,
+    /// Directory where the charts are rendered.
+    #[serde(default = "default_plots_dir")]
+    pub plots_dir: PathBuf,
+    pub experiments: Vec<ExperimentConfig>,
+}
+
+impl BenchmarkConfig {
+
+    /// Returns the settings of the benchmark of each operation.
+    pub fn benchmark(&self) -> Benchmark {
+        let mut benchmark = Benchmark::new(self.iterations);
+        benchmark.set_warmup(self.warmup);
+        benchmark
+    }
+
+    /// Reads a configuration file, parsed as JSON if its extension is `.json` and as TOML otherwise.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the configuration file.
+    ///
+    /// # Returns
+    /// The configuration or a string containing an error.
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let content = match read_to_string(path) {
+            Ok(content) => content,
+            Err(err) => { return Err(format!("Could not read configuration file {}: [{err}]", path.display())) }
+        };
+
+        let is_json = path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+        let config = if is_json {
+            serde_json::from_str(&content).map_err(|err| err.to_string())
+        } else {
+            toml::from_str(&content).map_err(|err| err.to_string())
+        };
+        match config {
+            Ok(config) => Ok(config),
+            Err(err) => { Err(format!("Invalid configuration file {}: [{err}]", path.display())) }
+        }
+    }
+}
+
+impl Default for BenchmarkConfig {
+    /// The experiments of the paper.
+    fn default() -> Self {
+        BenchmarkConfig {
+            iterations: default_iterations(),
+            warmup: default_warmup(),
+            curve: CurveName::default(),
+            output_dir: default_output_dir(),
+            json_lines: false,
+            plots: default_plots(),
+            plots_dir: default_plots_dir(),
+            experiments: vec![
+                ExperimentConfig::Delegators {
+                    delegators: ParameterRange(vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10]),
+                    permissions: ParameterRange::from(10),
+                    disclosed: ParameterRange(vec![1, 4, 7, 10]),
+                },
+                ExperimentConfig::Permissions {
+                    delegators: ParameterRange::from(10),
+                    permissions: ParameterRange(vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10]),
+                },
+                ExperimentConfig::Retain {
+                    delegators: ParameterRange::from(10),
+                    permissions: ParameterRange::from(10),
+                    retained: None,
+                },
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ranges() -> Result<(), Box<dyn std::error::Error>> {
+        assert_eq!(ParameterRange::from_str("1,4,7,10")?.values(), &vec![1, 4, 7, 10]);
+        assert_eq!(ParameterRange::from_str("1-10:3")?.values(), &vec![1, 4, 7, 10]);
+        assert_eq!(ParameterRange::from_str("2-4, 8")?.values(), &vec![2, 3, 4, 8]);
+        assert!(ParameterRange::from_str("4-2").is_err());
+        assert!(ParameterRange::from_str("1-4:0").is_err());
+        assert!(ParameterRange::from_str("").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_config_files() -> Result<(), Box<dyn std::error::Error>> {
+        let config: BenchmarkConfig = toml::from_str(r#"
+            iterations = 1000
+            curve = "bls12-381"
+
+            [[experiments]]
+            experiment = "delegators"
+            delegators = "1-10"
+            permissions = 10
+            disclosed = [1, 4, 7, 10]
+
+            [[experiments]]
+            experiment = "retain"
+            delegators = 10
+            permissions = "10,20"
+        "#)?;
+        assert_eq!(config.iterations, 1000);
+        assert_eq!(config.warmup, 3);
+        assert_eq!(config.curve, CurveName::Bls12_381);
+        assert_eq!(config.output_dir, PathBuf::from(CSV_DIR));
+        assert!(!config.json_lines);
+        assert!(config.plots);
+
+        let names: Vec<String> = config.experiments.iter()
+            .map(|experiment| experiment.sweeps())
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter().flatten().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["1_iod", "4_iod", "7_iod", "10_iod", "rp_p10", "rp_p20"]);
+
+        let json = serde_json::to_string(&BenchmarkConfig::default())?;
+        assert_eq!(serde_json::from_str::<BenchmarkConfig>(&json)?, BenchmarkConfig::default());
+
+        assert!(toml::from_str::<BenchmarkConfig>("[[experiments]]\nexperiment = \"permissions\"\ndelegators = []\npermissions = 1").is_err());
+        Ok(())
+    }
+}
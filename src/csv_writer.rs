@@ -1,9 +1,11 @@
 use std::collections::HashMap;
 use std::fs::{File, metadata};
-use std::fs::create_dir;
-use std::path::Path;
+use std::fs::create_dir_all;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
 use csv::Writer;
 use serde::Serialize;
+use serde_json::{Map, Value};
 
 /// An object used to easily write CSV files as it's necessary to keep track of several indicators (one for each csv file) for many different sd_algorithm instances (one for each column).
 pub struct CSVWriter {
@@ -11,11 +13,21 @@ pub struct CSVWriter {
     columns: Vec<String>,
     /// A Map containing the writers for all the possible files to be written.
     writers: HashMap<String, Writer<File>>,
+    /// Directory where the csv files will be saved in.
+    dir: PathBuf,
+    /// Whether every record is also written as a JSON object, keyed by the column names, to a JSON-lines file.
+    json_lines: bool,
+    /// A Map containing the writers of the JSON-lines files.
+    json_writers: HashMap<String, BufWriter<File>>,
 }
-/// Relative path of the directory where the csv files will be saved in.
-const CSV_DIR: &str = "./csv_dir";
+/// Relative path of the default directory where the csv files will be saved in.
+pub const CSV_DIR: &str = "./csv_dir";
 /// Extension of csv files.
 const CSV_EXT: &str = ".csv";
+/// Extension of JSON-lines files.
+const JSON_LINES_EXT: &str = ".jsonl";
+/// Extension of metadata files.
+const METADATA_EXT: &str = ".meta.json";
 
 impl CSVWriter {
 
@@ -34,17 +46,33 @@ impl CSVWriter {
     /// let csv_writer: CSVWriter = CSVWriter::new(vec!["first name".to_string(), "last name".to_string()]).unwrap();
     /// ```
     pub fn new(columns: Vec<String>) -> Result<Self, String> {
+        Self::with_dir(columns, Path::new(CSV_DIR))
+    }
+
+    /// Constructor for a CSVWriter saving the csv files in the given directory, created if needed.
+    ///
+    /// # Arguments
+    /// * `columns` - Vector of strings containing the column names.
+    /// * `dir` - Directory where the csv files will be saved in.
+    ///
+    /// # Returns
+    /// An instance of CSVWriter initialized with column names.
+    pub fn with_dir(columns: Vec<String>, dir: &Path) -> Result<Self, String> {
+        Self::check_dir_existence_or_create(dir)?;
 
-        let csv_dir: &Path = Path::new(CSV_DIR);
-        Self::check_dir_existence_or_create(csv_dir)?;
+        Ok(CSVWriter { columns, writers: HashMap::new(), dir: dir.to_path_buf(), json_lines: false, json_writers: HashMap::new() })
+    }
 
-        Ok(CSVWriter { columns, writers: HashMap::new() })
+    /// Sets whether the files added from now on are also written as JSON-lines files, with the
+    /// same name and a `.jsonl` extension.
+    pub fn set_json_lines(&mut self, json_lines: bool) {
+        self.json_lines = json_lines;
     }
 
     /// A utility function to check whether the csv directory exists or not
     fn check_dir_existence_or_create(csv_dir: &Path) -> Result<(), String> {
-        if !metadata(csv_dir).is_ok() {            // directory does not exist
-            match create_dir(csv_dir) {
+        if metadata(csv_dir).is_err() {            // directory does not exist
+            match create_dir_all(csv_dir) {
                 Ok(_) => {}
                 Err(err) => { return Err(format!("Error in creating CSV folder: [{err}]")) }
             };
@@ -72,22 +100,31 @@ impl CSVWriter {
         let mut filename_with_extension: String = filename.clone();
         filename_with_extension.push_str(CSV_EXT);
 
-        let csv_dir: &Path = Path::new(CSV_DIR);
-        Self::check_dir_existence_or_create(csv_dir)?;
-        let full_path = csv_dir.join(filename_with_extension);
+        Self::check_dir_existence_or_create(&self.dir)?;
+        let full_path = self.dir.join(filename_with_extension);
 
         let file = match File::create(full_path) {
             Ok(file) => { file }
             Err(err) => { return Err(format!("Error in creating file for CSV Writer: [{err}]")) }
         };
 
-        let writer = Writer::from_writer(file);
+        let mut writer = Writer::from_writer(file);
+        if let Err(err) = writer.serialize(&self.columns) {
+            return Err(format!("Error in writing record: [{err}]"))
+        }
         match self.writers.insert(filename.clone(), writer) {
             None => { }
             Some(_) => { return Err(format!("HashMap already has a writer for {filename} key"))}
         };
 
-        self.write_record_to_file(filename, self.columns.clone())?;
+        if self.json_lines {
+            let full_path = self.dir.join(format!("{filename}{JSON_LINES_EXT}"));
+            let file = match File::create(full_path) {
+                Ok(file) => { file }
+                Err(err) => { return Err(format!("Error in creating JSON-lines file: [{err}]")) }
+            };
+            self.json_writers.insert(filename.clone(), BufWriter::new(file));
+        }
 
         Ok(())
     }
@@ -111,18 +148,53 @@ impl CSVWriter {
     /// csv_writer.write_record_to_file(&String::from("Office"), vec!["0000", "Albert", "Einstein"]).unwrap();
     /// csv_writer.write_record_to_file(&String::from("Office"), vec!["0001", "Leonhard", "Euler"]).unwrap();
     /// ```
-    pub fn write_record_to_file<S: Serialize + std::fmt::Debug>(&mut self, filename: &String, record: S) -> Result<(), String>
+    pub fn write_record_to_file<S: Serialize>(&mut self, filename: &String, record: S) -> Result<(), String>
     {
         let writer: &mut Writer<File> = match self.writers.get_mut(filename) {
             None => { return Err(format!("Filename {filename} was not found in map"))}
             Some(writer) => { writer }
         };
 
-        match writer.serialize(record) {
-            Ok(_) => { Ok(()) }
-            Err(err) => { Err(format!("Error in writing record: [{err}]")) }
+        if let Err(err) = writer.serialize(&record) {
+            return Err(format!("Error in writing record: [{err}]"))
+        }
+
+        if let Some(json_writer) = self.json_writers.get_mut(filename) {
+            let object = match serde_json::to_value(&record) {
+                Ok(Value::Array(values)) => {
+                    Value::Object(self.columns.iter().cloned().zip(values).collect::<Map<String, Value>>())
+                }
+                Ok(object @ Value::Object(_)) => object,
+                Ok(other) => { return Err(format!("Record {other} has no columns")) }
+                Err(err) => { return Err(format!("Error in converting record to JSON: [{err}]")) }
+            };
+            if let Err(err) = writeln!(json_writer, "{object}") {
+                return Err(format!("Error in writing JSON line: [{err}]"))
+            }
         }
 
+        Ok(())
+    }
+
+    /// Writes the metadata describing the files of the CSVWriter to the JSON file `{name}.meta.json`.
+    ///
+    /// # Arguments
+    /// * `name` - String containing the name of the metadata file.
+    /// * `metadata` - Metadata to be serialized in the file.
+    ///
+    /// # Returns
+    /// The result of the operation or a string containing an error.
+    pub fn write_metadata<S: Serialize>(&self, name: &String, metadata: &S) -> Result<(), String> {
+        let full_path = self.dir.join(format!("{name}{METADATA_EXT}"));
+        let file = match File::create(full_path) {
+            Ok(file) => { file }
+            Err(err) => { return Err(format!("Error in creating metadata file: [{err}]")) }
+        };
+
+        match serde_json::to_writer_pretty(BufWriter::new(file), metadata) {
+            Ok(_) => { Ok(()) }
+            Err(err) => { Err(format!("Error in writing metadata: [{err}]")) }
+        }
     }
 
 }
@@ -134,5 +206,8 @@ impl Drop for CSVWriter {
         for (_, writer) in self.writers.iter_mut() {
             writer.flush().unwrap();
         }
+        for (_, writer) in self.json_writers.iter_mut() {
+            writer.flush().unwrap();
+        }
     }
 }
\ No newline at end of file
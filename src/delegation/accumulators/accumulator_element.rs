@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// Version of the encoding used to map the elements of a delegation to accumulator scalars.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ElementEncoding {
+    /// Elements are the plain SHA-256 hash of their value, without any tagging. Credentials issued
+    /// before the typed encoding do not carry a version and use this one.
+    #[default]
+    #[serde(rename = "legacy")]
+    Legacy,
+    /// Elements are hashed to the scalar field with a domain separation tag, together with their
+    /// role and the key of the delegation they belong to.
+    #[serde(rename = "v1")]
+    Typed,
+}
+
+/// Role of an element in the accumulator of a delegation. The role also fixes the position of the
+/// metadata elements, that are always accumulated as delegatee id, issuance and expiration time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ElementRole {
+    DelegateeId,
+    IssuedAt,
+    ExpiresAt,
+    Permission,
+}
+
+impl ElementRole {
+    fn tag(&self) -> u8 {
+        match self {
+            ElementRole::DelegateeId => 0,
+            ElementRole::IssuedAt => 1,
+            ElementRole::ExpiresAt => 2,
+            ElementRole::Permission => 3,
+        }
+    }
+}
+
+impl Display for ElementRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ElementRole::DelegateeId => write!(f, "sub"),
+            ElementRole::IssuedAt => write!(f, "iat"),
+            ElementRole::ExpiresAt => write!(f, "exp"),
+            ElementRole::Permission => write!(f, "per"),
+        }
+    }
+}
+
+/// Element accumulated by the issuer of a delegation, see `AccumulatorUtils::convert_element_to_scalar`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccumulatorElement {
+    encoding: ElementEncoding,
+    role: ElementRole,
+    delegation_key: String,
+    value: String,
+}
+
+impl AccumulatorElement {
+
+    /// Creates an element of a delegation.
+    ///
+    /// # Arguments
+    /// * `encoding` - Encoding of the delegation the element belongs to.
+    /// * `role` - Role of the element in the delegation.
+    /// * `delegation_key` - Key under which the accumulator of the delegation is published, binding
+    ///   the element to a single credential.
+    /// * `value` - Value of the element.
+    pub fn new(encoding: ElementEncoding, role: ElementRole, delegation_key: &str, value: &str) -> Self {
+        AccumulatorElement { encoding, role, delegation_key: delegation_key.to_string(), value: value.to_string() }
+    }
+
+    pub fn encoding(&self) -> ElementEncoding {
+        self.encoding
+    }
+
+    pub fn role(&self) -> ElementRole {
+        self.role
+    }
+
+    pub fn value(&self) -> &String {
+        &self.value
+    }
+
+    /// Returns the message hashed to the scalar field by the typed encoding: the role tag followed
+    /// by the length-prefixed delegation key and value.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::with_capacity(9 + self.delegation_key.len() + self.value.len());
+        bytes.push(self.role.tag());
+        for field in [&self.delegation_key, &self.value] {
+            bytes.extend_from_slice(&(field.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(field.as_bytes());
+        }
+        bytes
+    }
+}
+
+impl Display for AccumulatorElement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.value, self.role)
+    }
+}
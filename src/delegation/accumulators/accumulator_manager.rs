@@ -1,8 +1,11 @@
 use ark_ec::pairing::Pairing;
+use vb_accumulator::batch_utils::Omega;
 use vb_accumulator::positive::{Accumulator, PositiveAccumulator};
 use vb_accumulator::prelude::{ SecretKey, SetupParams};
+use crate::delegation::accumulators::accumulator_update::AccumulatorUpdate;
 use crate::delegation::accumulators::accumulator_utils::AccumulatorUtils;
 use crate::delegation::accumulators::in_memory_state::InMemoryState;
+use crate::delegation::error::DelegationError;
 
 pub struct AccumulatorManager<'sk, E: Pairing> {
     secret_key: &'sk SecretKey<E::ScalarField>,
@@ -20,57 +23,86 @@ impl <'keypair, E: Pairing>AccumulatorManager<'keypair, E> {
 
     }
 
-    pub fn clone_accumulator(&self) -> Result<String, String> {
+    /// Rebuilds a manager from an accumulator and state previously extracted with `into_parts`, so
+    /// that elements can be removed after issuance.
+    pub fn restore(secret_key: &'keypair SecretKey<E::ScalarField>, accumulator: PositiveAccumulator<E>, state: InMemoryState<E::ScalarField>) -> AccumulatorManager<'keypair, E> {
+        AccumulatorManager { secret_key, accumulator, state }
+    }
+
+    /// Releases the accumulator and its state so that they can outlive the borrowed secret key.
+    pub fn into_parts(self) -> (PositiveAccumulator<E>, InMemoryState<E::ScalarField>) {
+        (self.accumulator, self.state)
+    }
+
+    pub fn clone_accumulator(&self) -> Result<String, DelegationError> {
         AccumulatorUtils::<E>::serialize(&self.accumulator)
     }
 
-    pub fn add_element(&mut self, element: E::ScalarField) -> Result<(), String> {
-        match self.accumulator.add(element, &self.secret_key, &mut self.state) {
+    pub fn add_element(&mut self, element: E::ScalarField) -> Result<(), DelegationError> {
+        match self.accumulator.add(element, self.secret_key, &mut self.state) {
             Ok(accumulator) => {
                 self.accumulator = accumulator;
                 Ok(())
             }
-            Err(err) => { Err(format!("Error in adding single element: [{:?}]", err))}
+            Err(err) => { Err(DelegationError::Accumulator(err)) }
         }
     }
 
-    pub fn add_elements(&mut self, elements: Vec<E::ScalarField>) -> Result<(), String> {
-        match self.accumulator.add_batch(elements, &self.secret_key, &mut self.state) {
+    pub fn add_elements(&mut self, elements: Vec<E::ScalarField>) -> Result<(), DelegationError> {
+        match self.accumulator.add_batch(elements, self.secret_key, &mut self.state) {
             Ok(accumulator) => {
                 self.accumulator = accumulator;
                 Ok(())
             }
-            Err(err) => { Err(format!("Error in adding batch elements: [{:?}]", err)) }
+            Err(err) => { Err(DelegationError::Accumulator(err)) }
         }
     }
 
-    pub fn remove_element(&mut self, element: E::ScalarField) -> Result<(), String> {
-        match self.accumulator.remove(&element, &self.secret_key, &mut self.state) {
+    pub fn remove_element(&mut self, element: E::ScalarField) -> Result<(), DelegationError> {
+        match self.accumulator.remove(&element, self.secret_key, &mut self.state) {
             Ok(accumulator) => {
                 self.accumulator = accumulator;
                 Ok(())
             }
-            Err(err) => { Err(format!("Error in removing single element: [{:?}]", err))}
+            Err(err) => { Err(DelegationError::Accumulator(err)) }
         }
     }
 
-    pub fn remove_elements(&mut self, elements: &[E::ScalarField]) -> Result<(), String> {
-        match self.accumulator.remove_batch(elements, &self.secret_key, &mut self.state) {
+    pub fn remove_elements(&mut self, elements: &[E::ScalarField]) -> Result<(), DelegationError> {
+        match self.accumulator.remove_batch(elements, self.secret_key, &mut self.state) {
             Ok(accumulator) => {
                 self.accumulator = accumulator;
                 Ok(())
             }
-            Err(err) => { Err(format!("Error in removing batch elements: [{:?}]", err)) }
+            Err(err) => { Err(DelegationError::Accumulator(err)) }
         }
     }
 
-    pub fn compute_witness(&mut self, element: E::ScalarField) -> Result<String, String> {
-        let witness = self.accumulator.compute_membership_witness(&element, &self.secret_key);
+    /// Removes a batch of elements and computes the public information that holders need to update
+    /// the witnesses of the remaining elements.
+    pub fn remove_elements_with_update(&mut self, elements: &[E::ScalarField]) -> Result<AccumulatorUpdate, DelegationError> {
+        let previous_accumulator = self.accumulator.clone();
+        let previous_accumulator_value = self.clone_accumulator()?;
+
+        self.remove_elements(elements)?;
+
+        let omega = Omega::new(&[], elements, previous_accumulator.value(), self.secret_key);
+
+        let mut removals = vec![];
+        for element in elements {
+            removals.push(AccumulatorUtils::<E>::serialize(element)?);
+        }
+
+        Ok(AccumulatorUpdate::new(previous_accumulator_value, self.clone_accumulator()?, removals, AccumulatorUtils::<E>::serialize(&omega)?))
+    }
+
+    pub fn compute_witness(&mut self, element: E::ScalarField) -> Result<String, DelegationError> {
+        let witness = self.accumulator.compute_membership_witness(&element, self.secret_key);
         AccumulatorUtils::<E>::serialize(&witness)
     }
 
-    pub fn compute_witnesses(&mut self, elements: &[E::ScalarField]) -> Result<Vec<String>, String> {
-        let witnesses = self.accumulator.compute_membership_witnesses_for_batch(elements, &self.secret_key);
+    pub fn compute_witnesses(&mut self, elements: &[E::ScalarField]) -> Result<Vec<String>, DelegationError> {
+        let witnesses = self.accumulator.compute_membership_witnesses_for_batch(elements, self.secret_key);
         let mut result = vec![];
 
         for witness in witnesses {
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+/// Public information published by an issuer after removing a batch of elements from an
+/// accumulator. Holders use it to update their witnesses without contacting the issuer.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AccumulatorUpdate {
+    /// Accumulator value before the removal.
+    pub previous_accumulator_value: String,
+    /// Accumulator value after the removal.
+    pub accumulator_value: String,
+    /// Encoding of the removed scalars.
+    pub removals: Vec<String>,
+    /// Encoding of the `Omega` polynomial evaluated on the previous accumulator value.
+    pub omega: String,
+}
+
+impl AccumulatorUpdate {
+    pub fn new(previous_accumulator_value: String, accumulator_value: String, removals: Vec<String>, omega: String) -> Self {
+        AccumulatorUpdate { previous_accumulator_value, accumulator_value, removals, omega }
+    }
+}
@@ -1,9 +1,15 @@
 use std::marker::PhantomData;
 use ark_ec::pairing::Pairing;
 use ark_ff::PrimeField;
+use ark_ff::field_hashers::{DefaultFieldHasher, HashToField};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use digest::Digest;
 use sha2::Sha256;
+use crate::delegation::accumulators::accumulator_element::{AccumulatorElement, ElementEncoding};
+use crate::delegation::error::DelegationError;
+
+/// Domain separation tag of the typed encoding of accumulated elements.
+const ELEMENT_DST: &[u8] = b"DELEGATION-V1-ACCUMULATOR-ELEMENT_XMD:SHA-256";
 
 pub struct AccumulatorUtils<E: Pairing> {
     phantom: PhantomData<E>,
@@ -25,8 +31,28 @@ impl <E: Pairing> AccumulatorUtils<E> {
         hasher.update(value);
         let result = hasher.finalize();
 
-        E::ScalarField::from_be_bytes_mod_order(&result.as_slice())
+        E::ScalarField::from_be_bytes_mod_order(result.as_slice())
+
+    }
 
+    /// Maps an element of a delegation to a scalar value according to the encoding of the
+    /// delegation. With the typed encoding, elements with the same value but a different role or
+    /// delegation are mapped to unrelated scalars.
+    ///
+    /// # Arguments
+    /// * `element` - Element to be converted.
+    ///
+    /// # Returns
+    /// This function returns the converted scalar.
+    pub fn convert_element_to_scalar(element: &AccumulatorElement) -> E::ScalarField {
+        match element.encoding() {
+            ElementEncoding::Legacy => Self::convert_string_to_scalar(element.value()),
+            ElementEncoding::Typed => {
+                let hasher = <DefaultFieldHasher<Sha256> as HashToField<E::ScalarField>>::new(ELEMENT_DST);
+                let scalars: Vec<E::ScalarField> = hasher.hash_to_field(&element.to_bytes(), 1);
+                scalars[0]
+            }
+        }
     }
 
 
@@ -36,13 +62,13 @@ impl <E: Pairing> AccumulatorUtils<E> {
     /// * `element` - Element to be serialized.
     ///
     /// # Returns
-    /// This function returns a result wrapping the encoding of the element or a string illustrating the error, if it occurs.
-    pub fn serialize<S>(element: &S) -> Result<String, String>
+    /// This function returns a result wrapping the encoding of the element or the error, if it occurs.
+    pub fn serialize<S>(element: &S) -> Result<String, DelegationError>
     where S: CanonicalSerialize {
         let mut compressed_bytes: Vec<u8> = Vec::new();
         match element.serialize_compressed(&mut compressed_bytes) {
-            Ok(()) => { () }
-            Err(err) => { return Err(format!("Error in serialization of element: [{err}]")) }
+            Ok(()) => {}
+            Err(err) => { return Err(DelegationError::Serialization(err)) }
         };
 
         Ok(multibase::Base::Base64Url.encode(compressed_bytes))
@@ -56,18 +82,48 @@ impl <E: Pairing> AccumulatorUtils<E> {
     /// * `encoded_element` - String containing the element to be deserialized.
     ///
     /// # Returns
-    /// This function returns a result wrapping the deserialization of element or a string illustrating the error, if it occurs.
-    pub fn deserialize<D>(encoded_element: &String) -> Result<D, String>
+    /// This function returns a result wrapping the deserialization of element or the error, if it occurs.
+    pub fn deserialize<D>(encoded_element: &String) -> Result<D, DelegationError>
     where D: CanonicalDeserialize {
         let decoded = match multibase::Base::Base64Url.decode(encoded_element) {
             Ok(byte_array) => { byte_array }
-            Err(err) => { return Err(format!("Error in decoding element: [{err}]")) }
+            Err(err) => { return Err(DelegationError::Decoding(err)) }
         };
         let deserialized_element = match CanonicalDeserialize::deserialize_compressed(&*decoded) {
             Ok(element) => { element },
-            Err(err) => { return Err(format!("Error in deserializing element: [{err}]")) }
+            Err(err) => { return Err(DelegationError::Serialization(err)) }
         };
 
         Ok(deserialized_element)
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Bn254;
+    use crate::delegation::accumulators::accumulator_element::ElementRole;
+
+    #[test]
+    fn separate_element_domains() -> Result<(), Box<dyn std::error::Error>> {
+        type Utils = AccumulatorUtils<Bn254>;
+        let key = "https://vc.example/delegators/d0#https://vc.example/delegators/d1#1";
+        let value = "https://vc.example/delegators/d1";
+
+        // Legacy elements are the plain hash of their value, whatever their role
+        let legacy = AccumulatorElement::new(ElementEncoding::Legacy, ElementRole::Permission, key, value);
+        if Utils::convert_element_to_scalar(&legacy) != Utils::convert_string_to_scalar(&value.to_string()) {
+            return Err(String::from("Legacy element differs from the hash of its value").into());
+        }
+
+        let permission = Utils::convert_element_to_scalar(&AccumulatorElement::new(ElementEncoding::Typed, ElementRole::Permission, key, value));
+        let delegatee_id = Utils::convert_element_to_scalar(&AccumulatorElement::new(ElementEncoding::Typed, ElementRole::DelegateeId, key, value));
+        let other_delegation = Utils::convert_element_to_scalar(&AccumulatorElement::new(ElementEncoding::Typed, ElementRole::Permission, "https://vc.example/delegators/d0#https://vc.example/delegators/d1#2", value));
+        if permission == delegatee_id || permission == other_delegation || permission == Utils::convert_element_to_scalar(&legacy) {
+            return Err(String::from("Typed elements with different roles or delegations collide").into());
+        }
+
+        Ok(())
+    }
+}
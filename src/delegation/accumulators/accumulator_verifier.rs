@@ -1,8 +1,11 @@
-use std::thread;
-use std::thread::JoinHandle;
 use ark_ec::pairing::Pairing;
+use rayon::prelude::*;
 use vb_accumulator::prelude::{Accumulator, MembershipWitness, PositiveAccumulator, PublicKey, SetupParams};
+use crate::delegation::accumulators::accumulator_element::AccumulatorElement;
 use crate::delegation::accumulators::accumulator_utils::AccumulatorUtils;
+use crate::delegation::accumulators::batch_verifier::BatchVerifier;
+use crate::delegation::accumulators::verification_mode::VerificationMode;
+use crate::delegation::error::DelegationError;
 
 pub struct AccumulatorVerifier<E: Pairing> {
     accumulator_value: PositiveAccumulator<E>,
@@ -12,64 +15,75 @@ pub struct AccumulatorVerifier<E: Pairing> {
 
 impl <E:Pairing> AccumulatorVerifier<E> {
 
-    pub fn new(accumulator_value: String, public_key: PublicKey<E>, params: SetupParams<E>) -> Result<Self, String> {
+    pub fn new(accumulator_value: String, public_key: PublicKey<E>, params: SetupParams<E>) -> Result<Self, DelegationError> {
         let accumulator_value: PositiveAccumulator<E> = AccumulatorUtils::<E>::deserialize(&accumulator_value)?;
 
         Ok(AccumulatorVerifier { accumulator_value, public_key, params })
     }
 
-    fn verify_accumulator_witness(accumulator_value: &PositiveAccumulator<E>, witness: &String, element: &String, public_key: &PublicKey<E>, params: &SetupParams<E>) -> Result<(), String> {
-        let element_value: E::ScalarField = AccumulatorUtils::<E>::deserialize(&element)?;
-        let witness_value: MembershipWitness<E::G1Affine> = AccumulatorUtils::<E>::deserialize(&witness)?;
+    fn verify_accumulator_witness(accumulator_value: &PositiveAccumulator<E>, witness: &String, element: &AccumulatorElement, public_key: &PublicKey<E>, params: &SetupParams<E>) -> Result<(), DelegationError> {
+        // Elements are accumulated by the issuer as hashes of their typed encoding
+        let element_value: E::ScalarField = AccumulatorUtils::<E>::convert_element_to_scalar(element);
+        let witness_value: MembershipWitness<E::G1Affine> = AccumulatorUtils::<E>::deserialize(witness)?;
 
-        match accumulator_value.verify_membership(&element_value, &witness_value, &public_key, &params) {
+        match accumulator_value.verify_membership(&element_value, &witness_value, public_key, params) {
             true => Ok(()),
-            false => Err(format!("Could not verify membership for element {element}"))
+            false => Err(DelegationError::MembershipNotVerified { element: element.to_string() })
         }
     }
 
-    pub fn verify_accumulator_witnesses(&self, witnesses: Vec<String>, elements: Vec<String>, parallel: bool) -> Result<(), String> {
+    /// Adds the membership checks of the elements to a batch, possibly shared with other accumulators.
+    ///
+    /// # Arguments
+    /// * `batch` - Batch verifier collecting the checks.
+    /// * `witnesses` - Encoded witnesses, one for each element.
+    /// * `elements` - Elements whose membership must be verified.
+    ///
+    /// # Returns
+    /// The result of the operation or the error, if a witness cannot be decoded.
+    pub fn add_to_batch(&self, batch: &mut BatchVerifier<E>, witnesses: &[String], elements: &[AccumulatorElement]) -> Result<(), DelegationError> {
+        batch.add(self.accumulator_value.value(), witnesses, elements, &self.public_key, &self.params)
+    }
+
+    /// Verifies the membership of the elements in the accumulator.
+    ///
+    /// # Arguments
+    /// * `witnesses` - Encoded witnesses, one for each element.
+    /// * `elements` - Elements whose membership must be verified.
+    /// * `mode` - How the witnesses are verified. In parallel mode the witnesses are split among the
+    ///   workers of the rayon pool the method is called from.
+    ///
+    /// # Returns
+    /// The result of the verification or the first error encountered.
+    pub fn verify_accumulator_witnesses(&self, witnesses: &[String], elements: &[AccumulatorElement], mode: VerificationMode) -> Result<(), DelegationError> {
 
         if elements.len() != witnesses.len() {
-            return Err(format!("Witnesses length does not match elements [{} - {}]", elements.len(), witnesses.len()));
+            return Err(DelegationError::WitnessCountMismatch { witnesses: witnesses.len(), elements: elements.len() });
         }
 
-        if !parallel {
-            for (witness, element) in witnesses.iter().zip(elements.iter()) {
-                AccumulatorVerifier::verify_accumulator_witness(&self.accumulator_value, witness, element, &self.public_key, &self.params)?;
+        if mode == VerificationMode::Batched {
+            let mut batch = BatchVerifier::new();
+            self.add_to_batch(&mut batch, witnesses, elements)?;
+            if batch.verify().is_ok() {
+                return Ok(());
             }
-        } else {
-
-            let mut threads: Vec<JoinHandle<Result<(), String>>> = vec![];
+            // Check every witness on its own to report the element that is not a member
+            self.verify_accumulator_witnesses(witnesses, elements, VerificationMode::Sequential)?;
+            return Err(DelegationError::BatchNotVerified);
+        }
 
+        if mode == VerificationMode::Sequential {
             for (witness, element) in witnesses.iter().zip(elements.iter()) {
-
-                let accumulator_value = self.accumulator_value.clone();
-                let witness = witness.clone();
-                let element = element.clone();
-                let public_key = self.public_key.clone();
-                let params = self.params.clone();
-
-                let thread = thread::spawn(move || {
-                    match AccumulatorVerifier::verify_accumulator_witness(&accumulator_value, &witness, &element, &public_key, &params) {
-                        Ok(_) => Ok(()),
-                        Err(e) => Err(e)
-                    }
-                });
-                threads.push(thread);
-            }
-
-            for thread in threads {
-                match thread.join() {
-                    Ok(_) => {}
-                    Err(_) => {
-                        return Err(String::from("Thread verifying witness panicked"));
-                    }
-                }
+                AccumulatorVerifier::verify_accumulator_witness(&self.accumulator_value, witness, element, &self.public_key, &self.params)?;
             }
+        } else {
+            // Witnesses are verified by the workers of the current pool, which stop at the first failure
+            witnesses.par_iter().zip(elements.par_iter()).try_for_each(|(witness, element)| {
+                AccumulatorVerifier::verify_accumulator_witness(&self.accumulator_value, witness, element, &self.public_key, &self.params)
+            })?;
         }
 
         Ok(())
     }
 
-}
\ No newline at end of file
+}
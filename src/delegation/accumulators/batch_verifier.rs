@@ -0,0 +1,110 @@
+use ark_ec::pairing::Pairing;
+use ark_ec::{CurveGroup, VariableBaseMSM};
+use ark_ff::{One, UniformRand, Zero};
+use ark_std::rand::prelude::StdRng;
+use ark_std::rand::SeedableRng;
+use vb_accumulator::prelude::{MembershipWitness, PublicKey, SetupParams};
+use crate::delegation::accumulators::accumulator_element::AccumulatorElement;
+use crate::delegation::accumulators::accumulator_utils::AccumulatorUtils;
+use crate::delegation::error::DelegationError;
+
+/// Randomized batch verifier of membership witnesses, possibly issued for different accumulators
+/// under different keys.
+///
+/// A witness `w` of the element `y` is valid for the accumulator `V` if
+/// `e(w, y * P_tilde + Q_tilde) == e(V, P_tilde)`. Each equation is raised to a random scalar `r`
+/// and the equations sharing the same setup are summed up, so that every accumulator only
+/// contributes two pairings:
+/// `e(sum(r * (y * w - V)), P_tilde) * e(sum(r * w), Q_tilde) == 1`.
+/// The pairings of all the accumulators are then computed with a single multi-pairing.
+pub struct BatchVerifier<E: Pairing> {
+    rng: StdRng,
+    g1_elements: Vec<E::G1Affine>,
+    g2_elements: Vec<E::G2Affine>,
+}
+
+impl <E: Pairing> Default for BatchVerifier<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl <E: Pairing> BatchVerifier<E> {
+
+    pub fn new() -> Self {
+        BatchVerifier { rng: StdRng::from_entropy(), g1_elements: vec![], g2_elements: vec![] }
+    }
+
+    /// Adds the membership checks of several elements of the same accumulator to the batch.
+    ///
+    /// # Arguments
+    /// * `accumulator_value` - Value of the accumulator.
+    /// * `witnesses` - Encoded witnesses, one for each element.
+    /// * `elements` - Elements whose membership must be verified.
+    /// * `public_key` - Public key of the accumulator manager.
+    /// * `params` - Setup parameters of the accumulator.
+    ///
+    /// # Returns
+    /// The result of the operation or the error, if a witness cannot be decoded.
+    pub fn add(&mut self, accumulator_value: &E::G1Affine, witnesses: &[String], elements: &[AccumulatorElement],
+               public_key: &PublicKey<E>, params: &SetupParams<E>) -> Result<(), DelegationError> {
+
+        if elements.len() != witnesses.len() {
+            return Err(DelegationError::WitnessCountMismatch { witnesses: witnesses.len(), elements: elements.len() });
+        }
+        if elements.is_empty() {
+            return Ok(());
+        }
+
+        let mut bases: Vec<E::G1Affine> = Vec::with_capacity(elements.len() + 1);
+        let mut scaled_randomizers: Vec<E::ScalarField> = Vec::with_capacity(elements.len() + 1);
+        let mut randomizers: Vec<E::ScalarField> = Vec::with_capacity(elements.len());
+        let mut randomizers_sum = E::ScalarField::zero();
+
+        for (witness, element) in witnesses.iter().zip(elements.iter()) {
+            let witness_value: MembershipWitness<E::G1Affine> = AccumulatorUtils::<E>::deserialize(witness)?;
+            let element_value: E::ScalarField = AccumulatorUtils::<E>::convert_element_to_scalar(element);
+
+            // A zero coefficient would drop the equation from the batch
+            let mut randomizer = E::ScalarField::rand(&mut self.rng);
+            if randomizer.is_zero() {
+                randomizer = E::ScalarField::one();
+            }
+
+            bases.push(witness_value.0);
+            scaled_randomizers.push(randomizer * element_value);
+            randomizers.push(randomizer);
+            randomizers_sum += randomizer;
+        }
+
+        // sum(r * w) is computed before appending V to the bases
+        let randomized_witnesses = E::G1::msm_unchecked(&bases, &randomizers);
+
+        // sum(r * y * w) - sum(r) * V
+        bases.push(*accumulator_value);
+        scaled_randomizers.push(-randomizers_sum);
+        let randomized_equations = E::G1::msm_unchecked(&bases, &scaled_randomizers);
+
+        self.g1_elements.push(randomized_equations.into_affine());
+        self.g2_elements.push(params.P_tilde);
+        self.g1_elements.push(randomized_witnesses.into_affine());
+        self.g2_elements.push(public_key.0);
+
+        Ok(())
+    }
+
+    /// Checks every membership added to the batch with a single multi-pairing.
+    ///
+    /// # Returns
+    /// The result of the verification or the error, if any of the memberships does not hold.
+    pub fn verify(self) -> Result<(), DelegationError> {
+        if self.g1_elements.is_empty() {
+            return Ok(());
+        }
+
+        match E::multi_pairing(self.g1_elements, self.g2_elements).is_zero() {
+            true => Ok(()),
+            false => Err(DelegationError::BatchNotVerified),
+        }
+    }
+}
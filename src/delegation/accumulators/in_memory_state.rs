@@ -14,6 +14,12 @@ impl<T: Clone> InMemoryState<T> {
     }
 }
 
+impl<T: Clone> Default for InMemoryState<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T: Clone + Hash + Eq + Sized> State<T> for InMemoryState<T> {
     fn add(&mut self, element: T) {
         self.db.insert(element);
@@ -24,7 +30,7 @@ impl<T: Clone + Hash + Eq + Sized> State<T> for InMemoryState<T> {
     }
 
     fn has(&self, element: &T) -> bool {
-        self.db.get(element).is_some()
+        self.db.contains(element)
     }
 
     fn size(&self) -> u64 {
@@ -1,4 +1,9 @@
+pub mod accumulator_element;
 pub mod accumulator_manager;
 pub mod accumulator_verifier;
+pub mod batch_verifier;
 pub mod in_memory_state;
-pub mod accumulator_utils;
\ No newline at end of file
+pub mod accumulator_utils;
+pub mod accumulator_update;
+pub mod verification_mode;
+pub mod witness_updater;
\ No newline at end of file
@@ -0,0 +1,13 @@
+/// Strategy used to check the membership witnesses of a presentation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerificationMode {
+    /// Every witness is checked with its own pairing equation, one after the other.
+    Sequential,
+    /// Every witness is checked with its own pairing equation, the witnesses being split among the
+    /// workers of the bounded rayon pool of the verifier.
+    Parallel,
+    /// All the witnesses are combined with random coefficients into a single multi-pairing with
+    /// one final exponentiation. If the batch fails, the witnesses are checked sequentially to
+    /// locate the offending one.
+    Batched,
+}
@@ -0,0 +1,86 @@
+use ark_ec::pairing::Pairing;
+use vb_accumulator::batch_utils::Omega;
+use vb_accumulator::prelude::MembershipWitness;
+use crate::delegation::accumulators::accumulator_element::AccumulatorElement;
+use crate::delegation::accumulators::accumulator_update::AccumulatorUpdate;
+use crate::delegation::accumulators::accumulator_utils::AccumulatorUtils;
+use crate::delegation::error::DelegationError;
+
+/// Updates membership witnesses using the public information published after a sequence of
+/// removals, following the batch update protocol of the VB accumulator.
+pub struct WitnessUpdater<E: Pairing> {
+    removals: Vec<Vec<E::ScalarField>>,
+    omegas: Vec<Omega<E::G1Affine>>,
+}
+
+impl <E: Pairing> WitnessUpdater<E> {
+
+    /// Builds an updater from a sequence of consecutive accumulator updates.
+    ///
+    /// # Arguments
+    /// * `updates` - Updates in the order in which they were published.
+    ///
+    /// # Returns
+    /// A result wrapping the updater or the error, if it occurs.
+    pub fn new(updates: &[AccumulatorUpdate]) -> Result<Self, DelegationError> {
+        let mut removals: Vec<Vec<E::ScalarField>> = vec![];
+        let mut omegas: Vec<Omega<E::G1Affine>> = vec![];
+
+        for update in updates {
+            let mut batch: Vec<E::ScalarField> = vec![];
+            for removal in &update.removals {
+                batch.push(AccumulatorUtils::<E>::deserialize(removal)?);
+            }
+            removals.push(batch);
+            omegas.push(AccumulatorUtils::<E>::deserialize(&update.omega)?);
+        }
+
+        Ok(WitnessUpdater { removals, omegas })
+    }
+
+    /// Checks whether an element has been removed by any of the updates.
+    pub fn is_removed(&self, element: &AccumulatorElement) -> bool {
+        let element_value = AccumulatorUtils::<E>::convert_element_to_scalar(element);
+        self.removals.iter().any(|batch| batch.contains(&element_value))
+    }
+
+    /// Updates the witness of an element that is still a member of the accumulator.
+    ///
+    /// # Arguments
+    /// * `witness` - Encoding of the witness before the updates.
+    /// * `element` - Element whose membership is proven by the witness.
+    ///
+    /// # Returns
+    /// A result wrapping the encoding of the updated witness or the error, if it occurs.
+    pub fn update_witness(&self, witness: &String, element: &AccumulatorElement) -> Result<String, DelegationError> {
+        if self.omegas.is_empty() {
+            return Ok(witness.clone());
+        }
+
+        let element_value: E::ScalarField = AccumulatorUtils::<E>::convert_element_to_scalar(element);
+        let witness_value: MembershipWitness<E::G1Affine> = AccumulatorUtils::<E>::deserialize(witness)?;
+
+        let additions: Vec<E::ScalarField> = vec![];
+        let updates_and_omegas = self.removals.iter().zip(self.omegas.iter())
+            .map(|(removals, omega)| (additions.as_slice(), removals.as_slice(), omega))
+            .collect::<Vec<_>>();
+
+        match witness_value.update_using_public_info_after_multiple_batch_updates(updates_and_omegas, &element_value) {
+            Ok(updated_witness) => AccumulatorUtils::<E>::serialize(&updated_witness),
+            Err(err) => Err(DelegationError::Accumulator(err)),
+        }
+    }
+
+    /// Updates the witnesses of several elements, see `update_witness`.
+    pub fn update_witnesses(&self, witnesses: &[String], elements: &[AccumulatorElement]) -> Result<Vec<String>, DelegationError> {
+        if elements.len() != witnesses.len() {
+            return Err(DelegationError::WitnessCountMismatch { witnesses: witnesses.len(), elements: elements.len() });
+        }
+
+        let mut result = vec![];
+        for (witness, element) in witnesses.iter().zip(elements.iter()) {
+            result.push(self.update_witness(witness, element)?);
+        }
+        Ok(result)
+    }
+}
@@ -0,0 +1,9 @@
+use crate::delegation::error::DelegationError;
+
+/// Source of the current time used by issuers and verifiers. Injecting a clock makes issuance and
+/// verification reproducible at any given time.
+pub trait Clock: Send + Sync {
+
+    /// Returns the current time in nanoseconds since the Unix epoch, or the error if it cannot be read.
+    fn now(&self) -> Result<u128, DelegationError>;
+}
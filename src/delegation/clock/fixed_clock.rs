@@ -0,0 +1,43 @@
+use crate::delegation::clock::clock::Clock;
+use crate::delegation::error::DelegationError;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Clock that stands still until it is explicitly moved, to replay a verification at a given time.
+pub struct FixedClock {
+    now: Mutex<u128>,
+}
+
+impl FixedClock {
+    /// Creates a clock stopped at `now` nanoseconds since the Unix epoch.
+    pub fn new(now: u128) -> Self {
+        FixedClock { now: Mutex::new(now) }
+    }
+
+    /// Sets the time of the clock in nanoseconds since the Unix epoch.
+    pub fn set(&self, now: u128) -> Result<(), DelegationError> {
+        match self.now.lock() {
+            Ok(mut current) => { *current = now; }
+            Err(_) => { return Err(DelegationError::LockPoisoned) }
+        };
+        Ok(())
+    }
+
+    /// Moves the clock forward.
+    pub fn advance(&self, duration: Duration) -> Result<(), DelegationError> {
+        match self.now.lock() {
+            Ok(mut current) => { *current += duration.as_nanos(); }
+            Err(_) => { return Err(DelegationError::LockPoisoned) }
+        };
+        Ok(())
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> Result<u128, DelegationError> {
+        match self.now.lock() {
+            Ok(now) => Ok(*now),
+            Err(_) => Err(DelegationError::LockPoisoned),
+        }
+    }
+}
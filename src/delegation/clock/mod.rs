@@ -0,0 +1,5 @@
+#[allow(clippy::module_inception)]
+pub mod clock;
+pub mod fixed_clock;
+pub mod offset_clock;
+pub mod system_clock;
\ No newline at end of file
@@ -0,0 +1,53 @@
+use crate::delegation::clock::clock::Clock;
+use crate::delegation::error::DelegationError;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Clock running ahead of or behind another one by a constant offset, like the clock of a host
+/// that is not perfectly synchronized.
+pub struct OffsetClock {
+    clock: Arc<dyn Clock>,
+    offset: Duration,
+    ahead: bool,
+}
+
+impl OffsetClock {
+    /// Creates a clock running `offset` ahead of `clock`.
+    pub fn ahead(clock: Arc<dyn Clock>, offset: Duration) -> Self {
+        OffsetClock { clock, offset, ahead: true }
+    }
+
+    /// Creates a clock running `offset` behind `clock`. The time never goes before the Unix epoch.
+    pub fn behind(clock: Arc<dyn Clock>, offset: Duration) -> Self {
+        OffsetClock { clock, offset, ahead: false }
+    }
+}
+
+impl Clock for OffsetClock {
+    fn now(&self) -> Result<u128, DelegationError> {
+        let now = self.clock.now()?;
+        match self.ahead {
+            true => Ok(now + self.offset.as_nanos()),
+            false => Ok(now.saturating_sub(self.offset.as_nanos())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::delegation::clock::fixed_clock::FixedClock;
+
+    #[test]
+    fn offset_clocks() -> Result<(), Box<dyn std::error::Error>> {
+        let clock = Arc::new(FixedClock::new(10_000_000_000));
+        let ahead = OffsetClock::ahead(clock.clone(), Duration::new(2, 0));
+        let behind = OffsetClock::behind(clock.clone(), Duration::new(20, 0));
+
+        clock.advance(Duration::new(1, 0))?;
+        if ahead.now()? != 13_000_000_000 || behind.now()? != 0 {
+            return Err(format!("Unexpected offset times [{} - {}]", ahead.now()?, behind.now()?).into());
+        }
+        Ok(())
+    }
+}
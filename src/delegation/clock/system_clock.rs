@@ -0,0 +1,24 @@
+use crate::delegation::clock::clock::Clock;
+use crate::delegation::error::DelegationError;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Clock reading the time of the system.
+#[derive(Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl SystemClock {
+    pub fn shared() -> Arc<dyn Clock> {
+        Arc::new(SystemClock)
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Result<u128, DelegationError> {
+        let since_epoch: Duration = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration,
+            Err(e) => return Err(DelegationError::Clock(e)),
+        };
+        Ok(since_epoch.as_nanos())
+    }
+}
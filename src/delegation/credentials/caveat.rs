@@ -0,0 +1,247 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+use crate::delegation::credentials::request_context::RequestContext;
+use crate::delegation::error::DelegationError;
+
+/// Minutes in a day, the times of the day being counted from midnight UTC.
+const MINUTES_PER_DAY: u16 = 24 * 60;
+/// Nanoseconds in a minute.
+const NANOS_PER_MINUTE: u128 = 60_000_000_000;
+
+/// Condition restricting the requests a delegation may be used for, beyond its permissions. The
+/// caveats of a delegation are carried by every delegation following it, possibly replaced by
+/// stricter ones, and each of them is evaluated against the context of the request.
+///
+/// Unlike permissions, caveats are not accumulated as elements of the accumulator of the
+/// delegation. They are signed with the credential and bound to its accumulator through the key
+/// under which it is published, see `AccumulatorValueEntry::key`. A caveat must always be
+/// disclosed, so it has no use for selective disclosure, and revoking one would revoke the whole
+/// delegation, which `OurIssuer::revoke_delegatee` already does.
+///
+/// Caveats are serialized in their canonical form `{name}={value}`:
+/// - `time=09:00-17:00`: time of the day, UTC, from which and until which requests are allowed. A
+///   window ending before it starts spans midnight, e.g. `time=22:00-06:00`.
+/// - `ip=10.0.0.0/8`: network of the clients, IPv4 or IPv6.
+/// - `amount=100`: maximum amount of the requests.
+/// - `aud=https://vc.example/verifiers/v1`: verifier the requests are meant for.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Caveat {
+    /// Window of the day, in minutes since midnight UTC, including `from` and excluding `until`.
+    TimeOfDay { from: u16, until: u16 },
+    /// Network of the clients, whose address has no bits set after the prefix.
+    IpRange { network: IpAddr, prefix: u8 },
+    MaxAmount(u64),
+    Audience(String),
+}
+
+impl Caveat {
+
+    /// Creates a caveat allowing the clients of a network.
+    ///
+    /// # Arguments
+    /// * `address` - Any address of the network, whose bits after the prefix are ignored.
+    /// * `prefix` - Length of the prefix of the network, in bits.
+    ///
+    /// # Returns
+    /// A result wrapping the caveat or the error, if the prefix is longer than the address.
+    pub fn ip_range(address: IpAddr, prefix: u8) -> Result<Self, DelegationError> {
+        let (bits, width) = Self::address_bits(&address);
+        if prefix > width {
+            return Err(DelegationError::InvalidCaveat { caveat: format!("ip={address}/{prefix}"), reason: "the prefix is longer than the address" });
+        }
+        let network = match address {
+            IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::from((bits & Self::mask(prefix, width)) as u32)),
+            IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::from(bits & Self::mask(prefix, width))),
+        };
+        Ok(Caveat::IpRange { network, prefix })
+    }
+
+    /// Returns whether a request satisfies the caveat. A caveat on a value that is missing from
+    /// the context is not satisfied.
+    ///
+    /// # Arguments
+    /// * `now_ns` - Time of the request, in nanoseconds since the Unix epoch.
+    /// * `context` - Context of the request.
+    pub fn is_satisfied(&self, now_ns: u128, context: &RequestContext) -> bool {
+        match self {
+            Caveat::TimeOfDay { .. } => self.covers(((now_ns / NANOS_PER_MINUTE) % MINUTES_PER_DAY as u128) as u16),
+            Caveat::IpRange { network, prefix } => context.client_ip().is_some_and(|address| Self::contains(network, *prefix, address)),
+            Caveat::MaxAmount(max_amount) => context.amount().is_some_and(|amount| amount <= *max_amount),
+            Caveat::Audience(audience) => context.audience().is_some_and(|other| other == audience),
+        }
+    }
+
+    /// Returns whether every request satisfying the caveat also satisfies `other`, so that it may
+    /// replace `other` further down the chain.
+    ///
+    /// # Example
+    /// ```
+    /// use delegation::delegation::credentials::caveat::Caveat;
+    /// use std::str::FromStr;
+    ///
+    /// let parent = Caveat::from_str("time=08:00-18:00").unwrap();
+    /// assert!(Caveat::from_str("time=09:00-17:00").unwrap().is_within(&parent));
+    /// assert!(!Caveat::from_str("time=22:00-06:00").unwrap().is_within(&parent));
+    /// ```
+    pub fn is_within(&self, other: &Caveat) -> bool {
+        match (self, other) {
+            (Caveat::TimeOfDay { .. }, Caveat::TimeOfDay { .. }) => (0..MINUTES_PER_DAY).all(|minute| !self.covers(minute) || other.covers(minute)),
+            (Caveat::IpRange { network, prefix }, Caveat::IpRange { network: other_network, prefix: other_prefix }) =>
+                prefix >= other_prefix && Self::contains(other_network, *other_prefix, network),
+            (Caveat::MaxAmount(max_amount), Caveat::MaxAmount(other_max_amount)) => max_amount <= other_max_amount,
+            (Caveat::Audience(audience), Caveat::Audience(other_audience)) => audience == other_audience,
+            _ => false,
+        }
+    }
+
+    // Whether a time of the day falls in the window of a time caveat
+    fn covers(&self, minute: u16) -> bool {
+        match self {
+            Caveat::TimeOfDay { from, until } if from < until => *from <= minute && minute < *until,
+            Caveat::TimeOfDay { from, until } => *from <= minute || minute < *until,
+            _ => false,
+        }
+    }
+
+    fn contains(network: &IpAddr, prefix: u8, address: &IpAddr) -> bool {
+        let (network_bits, width) = Self::address_bits(network);
+        let (address_bits, address_width) = Self::address_bits(address);
+        width == address_width && address_bits & Self::mask(prefix, width) == network_bits
+    }
+
+    // Bits of an address and their number
+    fn address_bits(address: &IpAddr) -> (u128, u8) {
+        match address {
+            IpAddr::V4(address) => (u32::from(*address) as u128, 32),
+            IpAddr::V6(address) => (u128::from(*address), 128),
+        }
+    }
+
+    fn mask(prefix: u8, width: u8) -> u128 {
+        match prefix {
+            0 => 0,
+            prefix => (u128::MAX << (128 - prefix as u32)) >> (128 - width as u32),
+        }
+    }
+
+    fn parse_time(time: &str) -> Option<u16> {
+        let (hours, minutes) = time.split_once(':')?;
+        let (hours, minutes) = (hours.parse::<u16>().ok()?, minutes.parse::<u16>().ok()?);
+        if hours >= 24 || minutes >= 60 || time.len() != 5 {
+            return None;
+        }
+        Some(hours * 60 + minutes)
+    }
+}
+
+impl Display for Caveat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Caveat::TimeOfDay { from, until } => write!(f, "time={:02}:{:02}-{:02}:{:02}", from / 60, from % 60, until / 60, until % 60),
+            Caveat::IpRange { network, prefix } => write!(f, "ip={network}/{prefix}"),
+            Caveat::MaxAmount(max_amount) => write!(f, "amount={max_amount}"),
+            Caveat::Audience(audience) => write!(f, "aud={audience}"),
+        }
+    }
+}
+
+impl FromStr for Caveat {
+    type Err = DelegationError;
+
+    fn from_str(caveat: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason| DelegationError::InvalidCaveat { caveat: caveat.to_string(), reason };
+
+        let (name, value) = match caveat.split_once('=') {
+            Some((name, value)) => (name, value),
+            None => { return Err(invalid("caveats must be written as name=value")) }
+        };
+        match name {
+            "time" => {
+                let window = value.split_once('-').and_then(|(from, until)| Some((Self::parse_time(from)?, Self::parse_time(until)?)));
+                match window {
+                    Some((from, until)) if from != until => Ok(Caveat::TimeOfDay { from, until }),
+                    Some(_) => Err(invalid("the time window cannot be empty")),
+                    None => Err(invalid("the time window must be written as HH:MM-HH:MM")),
+                }
+            }
+            "ip" => {
+                let range = value.split_once('/').and_then(|(address, prefix)| Some((address.parse::<IpAddr>().ok()?, prefix.parse::<u8>().ok()?)));
+                match range {
+                    Some((address, prefix)) => Caveat::ip_range(address, prefix),
+                    None => Err(invalid("the network must be written as address/prefix")),
+                }
+            }
+            "amount" => match value.parse::<u64>() {
+                Ok(max_amount) => Ok(Caveat::MaxAmount(max_amount)),
+                Err(_) => Err(invalid("the maximum amount must be a non-negative integer")),
+            },
+            "aud" if !value.is_empty() => Ok(Caveat::Audience(value.to_string())),
+            "aud" => Err(invalid("the audience cannot be empty")),
+            _ => Err(invalid("unknown caveat, expected time, ip, amount or aud")),
+        }
+    }
+}
+
+impl TryFrom<String> for Caveat {
+    type Error = DelegationError;
+
+    fn try_from(caveat: String) -> Result<Self, Self::Error> {
+        Caveat::from_str(&caveat)
+    }
+}
+
+impl From<Caveat> for String {
+    fn from(caveat: Caveat) -> Self {
+        caveat.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluate_caveats() -> Result<(), Box<dyn std::error::Error>> {
+        for caveat in ["time=09:00-17:30", "time=22:00-06:00", "ip=10.0.0.0/8", "ip=2001:db8::/32", "amount=100", "aud=https://vc.example/verifiers/v1"] {
+            assert_eq!(Caveat::from_str(caveat)?.to_string(), caveat);
+        }
+        // Host bits of the networks are dropped from the canonical form
+        assert_eq!(Caveat::from_str("ip=10.1.2.3/8")?.to_string(), "ip=10.0.0.0/8");
+        for invalid in ["time=9:00-17:00", "time=09:00-09:00", "time=24:00-01:00", "ip=10.0.0.0/33", "ip=10.0.0.0", "amount=-1", "aud=", "until=18:00"] {
+            if Caveat::from_str(invalid).is_ok() {
+                return Err(format!("Caveat {invalid} should not be valid").into());
+            }
+        }
+
+        // 2026-01-01T10:00:00Z and 23:00:00Z
+        let (morning, night): (u128, u128) = (1_767_261_600_000_000_000, 1_767_308_400_000_000_000);
+        let context = RequestContext::new()
+            .with_client_ip("10.1.2.3".parse()?)
+            .with_amount(50)
+            .with_audience(String::from("https://vc.example/verifiers/v1"));
+        assert!(Caveat::from_str("time=09:00-17:00")?.is_satisfied(morning, &context));
+        assert!(!Caveat::from_str("time=09:00-17:00")?.is_satisfied(night, &context));
+        assert!(Caveat::from_str("time=22:00-06:00")?.is_satisfied(night, &context));
+        assert!(Caveat::from_str("ip=10.0.0.0/8")?.is_satisfied(morning, &context));
+        assert!(!Caveat::from_str("ip=10.2.0.0/16")?.is_satisfied(morning, &context));
+        assert!(!Caveat::from_str("ip=2001:db8::/32")?.is_satisfied(morning, &context));
+        assert!(!Caveat::from_str("amount=10")?.is_satisfied(morning, &context));
+        assert!(!Caveat::from_str("aud=https://vc.example/verifiers/v2")?.is_satisfied(morning, &context));
+        // Values missing from the context do not satisfy the caveats on them
+        assert!(!Caveat::from_str("amount=100")?.is_satisfied(morning, &RequestContext::new()));
+
+        let within = |caveat: &str, other: &str| -> Result<bool, DelegationError> {
+            Ok(Caveat::from_str(caveat)?.is_within(&Caveat::from_str(other)?))
+        };
+        assert!(within("time=23:00-01:00", "time=22:00-06:00")?);
+        assert!(!within("time=05:00-07:00", "time=22:00-06:00")?);
+        assert!(within("ip=10.1.0.0/16", "ip=10.0.0.0/8")?);
+        assert!(!within("ip=10.0.0.0/8", "ip=10.1.0.0/16")?);
+        assert!(within("amount=10", "amount=100")?);
+        assert!(!within("amount=100", "aud=https://vc.example/verifiers/v1")?);
+        Ok(())
+    }
+}
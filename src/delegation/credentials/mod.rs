@@ -1,3 +1,8 @@
+pub mod caveat;
+pub mod permission;
+pub mod presentation_request;
+pub mod request_context;
+pub mod signature_algorithm;
 pub mod verifiable_credential;
 pub mod verifiable_presentation;
 pub mod pjv;
@@ -1,8 +1,44 @@
+use crate::delegation::accumulators::accumulator_element::{AccumulatorElement, ElementEncoding, ElementRole};
+use crate::delegation::credentials::caveat::Caveat;
+use crate::delegation::credentials::permission::Permission;
+use crate::delegation::registry::accumulator_value_entry::AccumulatorValueEntry;
+
 pub trait OurDelegation {
      fn delegatee_id(&self) -> &String;
      fn accumulator_value(&self) -> &String;
      fn iat(&self) -> &String;
      fn exp(&self) -> &String;
+     fn element_encoding(&self) -> ElementEncoding;
+     /// Maximum number of delegations that may follow the delegation, none if unlimited.
+     fn max_depth(&self) -> Option<usize>;
+     /// Caveats restricting the requests the delegation may be used for.
+     fn caveats(&self) -> &Vec<Caveat>;
      fn metadata_witnesses(&self) -> &Vec<String>;
      fn permission_witnesses(&self) -> &Vec<String>;
-}
\ No newline at end of file
+     fn set_accumulator(&mut self, accumulator_value: String, metadata_witnesses: Vec<String>, permission_witnesses: Vec<String>);
+
+     /// Returns the key under which the accumulator of the delegation issued by `issuer` is published.
+     fn revocation_key(&self, issuer: &str) -> String {
+          AccumulatorValueEntry::key(issuer, self.delegatee_id(), self.iat(), self.max_depth(), self.caveats())
+     }
+
+     /// Returns the accumulated metadata of the delegation issued by `issuer`, in the order of the
+     /// metadata witnesses.
+     fn metadata_elements(&self, issuer: &str) -> Vec<AccumulatorElement> {
+          let key = self.revocation_key(issuer);
+          vec![
+               AccumulatorElement::new(self.element_encoding(), ElementRole::DelegateeId, &key, self.delegatee_id()),
+               AccumulatorElement::new(self.element_encoding(), ElementRole::IssuedAt, &key, self.iat()),
+               AccumulatorElement::new(self.element_encoding(), ElementRole::ExpiresAt, &key, self.exp()),
+          ]
+     }
+
+     /// Returns the accumulated elements of the given permissions of the delegation issued by
+     /// `issuer`, in their canonical form.
+     fn permission_elements(&self, issuer: &str, permissions: &[Permission]) -> Vec<AccumulatorElement> {
+          let key = self.revocation_key(issuer);
+          permissions.iter()
+               .map(|permission| AccumulatorElement::new(self.element_encoding(), ElementRole::Permission, &key, &permission.to_string()))
+               .collect()
+     }
+}
@@ -2,8 +2,12 @@ use crate::delegation::traits::credential::Credential;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use std::fmt::Display;
+use crate::delegation::error::DelegationError;
+use crate::delegation::accumulators::accumulator_element::ElementEncoding;
 use crate::delegation::credentials::ours::our_delegation::OurDelegation;
 use crate::delegation::credentials::ours::our_delegator::OurDelegator;
+use crate::delegation::credentials::caveat::Caveat;
+use crate::delegation::credentials::permission::Permission;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct OurDelegationCredential {
@@ -15,8 +19,15 @@ pub struct OurDelegationCredential {
     iat: String,
     #[serde(rename = "exp")]
     exp: String,
+    // Maximum number of delegations that may follow the delegation, none if unlimited
+    #[serde(rename = "md", default, skip_serializing_if = "Option::is_none")]
+    max_depth: Option<usize>,
+    #[serde(rename = "cav", default, skip_serializing_if = "Vec::is_empty")]
+    caveats: Vec<Caveat>,
+    #[serde(rename = "enc", default)]
+    element_encoding: ElementEncoding,
     #[serde(rename = "per")]
-    permissions: Vec<String>,
+    permissions: Vec<Permission>,
     #[serde(rename = "mw")]
     metadata_witnesses: Vec<String>,
     #[serde(rename = "pw")]
@@ -26,17 +37,82 @@ pub struct OurDelegationCredential {
 }
 
 impl OurDelegationCredential {
-    pub fn new(delegatee_id: String, accumulator_value: String, iat: String, exp: String, permissions: Vec<String>, metadata_witnesses: Vec<String>, permission_witnesses: Vec<String>, hierarchy: Vec<OurDelegator>) -> Result<OurDelegationCredential, String> {
-        Ok(OurDelegationCredential { delegatee_id, accumulator_value, iat, exp, permissions, metadata_witnesses, permission_witnesses, hierarchy})
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(delegatee_id: String, accumulator_value: String, iat: String, exp: String, element_encoding: ElementEncoding, permissions: Vec<Permission>, metadata_witnesses: Vec<String>, permission_witnesses: Vec<String>, hierarchy: Vec<OurDelegator>) -> Result<OurDelegationCredential, DelegationError> {
+        Ok(OurDelegationCredential { delegatee_id, accumulator_value, iat, exp, max_depth: None, caveats: vec![], element_encoding, permissions, metadata_witnesses, permission_witnesses, hierarchy})
     }
 
-    pub fn permissions(&self) -> &Vec<String> {
+    pub fn permissions(&self) -> &Vec<Permission> {
         &self.permissions
     }
 
     pub fn hierarchy(&self) -> &Vec<OurDelegator> {
         &self.hierarchy
     }
+
+    pub fn mut_hierarchy(&mut self) -> &mut Vec<OurDelegator> {
+        &mut self.hierarchy
+    }
+
+    /// Sets the maximum number of delegations that may follow the credential, which is part of the
+    /// key of its accumulator.
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.max_depth = max_depth;
+    }
+
+    /// Sets the caveats restricting the requests the credential may be used for, which are part of
+    /// the key of its accumulator.
+    pub fn set_caveats(&mut self, caveats: Vec<Caveat>) {
+        self.caveats = caveats;
+    }
+
+    /// Returns whether the delegatee may pass the permissions of the credential on.
+    pub fn may_delegate(&self) -> bool {
+        self.max_depth != Some(0)
+    }
+
+    /// Narrows the permissions proven by the delegators of a hierarchy, from the last one up to the
+    /// root, to the ones implying a permission of the level below. Delegators proving the
+    /// permissions of the credential are left untouched.
+    ///
+    /// # Arguments
+    /// * `hierarchy` - Delegators of a credential.
+    /// * `permissions` - Permissions granted by the level below the last delegator.
+    pub fn narrow_hierarchy(hierarchy: &mut [OurDelegator], permissions: &[Permission]) {
+        let mut granted: Vec<Permission> = permissions.to_vec();
+        for delegator in hierarchy.iter_mut().rev() {
+            delegator.retain_permissions(|permission| granted.iter().any(|granted| permission.implies(granted)));
+            if let Some(permissions) = delegator.permissions() {
+                granted = permissions.clone();
+            }
+        }
+    }
+
+    /// Selects the witnesses of some permissions, looked up by permission rather than by position,
+    /// so that the permissions may be selected in any order.
+    ///
+    /// # Arguments
+    /// * `permissions` - Permissions proven by the witnesses.
+    /// * `witnesses` - Witnesses of the permissions, in the same order.
+    /// * `selected` - Permissions whose witnesses are selected.
+    ///
+    /// # Returns
+    /// A result wrapping the witnesses of the selected permissions, in their order, or the error, if
+    /// a permission is not proven by the witnesses.
+    pub fn select_witnesses(permissions: &[Permission], witnesses: &[String], selected: &[Permission]) -> Result<Vec<String>, DelegationError> {
+        if permissions.len() != witnesses.len() {
+            return Err(DelegationError::MalformedCredential { reason: format!("witnesses and permissions have different cardinality [{} - {}]", permissions.len(), witnesses.len()) })
+        }
+
+        let mut selected_witnesses: Vec<String> = vec![];
+        for permission in selected {
+            match permissions.iter().position(|proven| proven == permission) {
+                Some(i) => selected_witnesses.push(witnesses[i].clone()),
+                None => { return Err(DelegationError::PermissionNotGranted { permission: permission.to_string() }) }
+            }
+        }
+        Ok(selected_witnesses)
+    }
 }
 
 impl OurDelegation for OurDelegationCredential {
@@ -53,76 +129,96 @@ impl OurDelegation for OurDelegationCredential {
     fn exp(&self) -> &String {
         &self.exp
     }
+    fn element_encoding(&self) -> ElementEncoding {
+        self.element_encoding
+    }
+    fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+    fn caveats(&self) -> &Vec<Caveat> {
+        &self.caveats
+    }
     fn metadata_witnesses(&self) -> &Vec<String> {
         &self.metadata_witnesses
     }
     fn permission_witnesses(&self) -> &Vec<String> {
         &self.permission_witnesses
     }
+    fn set_accumulator(&mut self, accumulator_value: String, metadata_witnesses: Vec<String>, permission_witnesses: Vec<String>) {
+        self.accumulator_value = accumulator_value;
+        self.metadata_witnesses = metadata_witnesses;
+        self.permission_witnesses = permission_witnesses;
+    }
 }
 
 impl Credential for OurDelegationCredential {
+    type Claim = Permission;
+
     fn credential_type(&self) -> &'static str {
         "OurDelegationCredential"
     }
 
-    fn from_map(map: Map<String, Value>) -> Result<Self, String> {
+    fn from_map(map: Map<String, Value>) -> Result<Self, DelegationError> {
         match serde_json::from_value::<OurDelegationCredential>(Value::Object(map.clone())) {
             Ok(credential) => Ok(credential),
-            Err(err) => Err(format!("Error in parsing OurDelegationCredential: {err}")),
+            Err(err) => Err(DelegationError::Json { context: "Error in parsing OurDelegationCredential", source: err }),
         }
     }
 
-    fn from_string(str: String) -> Result<Self, String>
+    fn from_string(str: String) -> Result<Self, DelegationError>
     {
         match serde_json::from_str::<OurDelegationCredential>(&str) {
             Ok(credential) => Ok(credential),
-            Err(err) => { Err(format!("Failed to deserialize OurDelegationCredential [{err}]")) }
+            Err(err) => { Err(DelegationError::Json { context: "Failed to deserialize OurDelegationCredential", source: err }) }
         }
     }
 
-    fn to_map(&self) -> Result<Map<String, Value>, String> {
-        let map_value = match::serde_json::to_value(&self) {
+    fn to_map(&self) -> Result<Map<String, Value>, DelegationError> {
+        let map_value = match::serde_json::to_value(self) {
             Ok(map_value) => map_value,
-            Err(err) => { return Err(format!("Failed to serialize OurDelegationCredential to map [{err}]")) }
+            Err(err) => { return Err(DelegationError::Json { context: "Failed to serialize OurDelegationCredential to map", source: err }) }
         };
 
         match map_value {
             Value::Object(map) => Ok(map),
-            _ => Err(format!("Serialized map is not an object [{map_value}]")),
+            _ => Err(DelegationError::NotAnObject),
         }
     }
 
-    fn to_string(&self) -> Result<String, String> {
+    fn to_string(&self) -> Result<String, DelegationError> {
         match serde_json::to_string(&self) {
             Ok(str) => Ok(str),
-            Err(err) => { Err(format!("Failed to serialize OurDelegationCredential to json string [{err}]")) }
+            Err(err) => { Err(DelegationError::Json { context: "Failed to serialize OurDelegationCredential to json string", source: err }) }
         }
     }
 
-    fn retain_only(&mut self, allowed: Vec<String>) -> Result<Vec<usize>, String> {
+    fn retain_only(&mut self, allowed: Vec<Permission>) -> Result<Vec<usize>, DelegationError> {
         let permissions_to_keep = allowed;
 
         let mut removable_indices: Vec<usize> = vec![];
+        let mut kept_permissions: Vec<Permission> = vec![];
 
         // For every permission check whether it is contained in the permissions to be kept.
-        // If not, add it to an array of indices to be removed
+        // If not, or if it was already kept, add it to an array of indices to be removed. The
+        // permissions to be kept may thus be given in any order and more than once
         for (i, permission) in self.permissions.iter().enumerate() {
-            if !permissions_to_keep.contains(&permission) {
+            if permissions_to_keep.contains(permission) && !kept_permissions.contains(permission) {
+                kept_permissions.push(permission.clone());
+            } else {
                 removable_indices.push(i);
             }
         }
 
-        // Remove indices from permissions, witnesses, and delegator witnesses contained in
-        // hierarchy
-        for i in removable_indices.iter().rev() {
-            self.permissions.remove(*i);
-            self.permission_witnesses.remove(*i);
-
-            for delegator in self.hierarchy.iter_mut() {
-                delegator.mut_permission_witnesses().remove(*i);
-            }
+        // Select the witnesses of the kept permissions, and of the delegators contained in
+        // hierarchy that prove the same permissions, by permission
+        self.permission_witnesses = Self::select_witnesses(&self.permissions, &self.permission_witnesses, &kept_permissions)?;
+        for delegator in self.hierarchy.iter_mut().filter(|delegator| delegator.permissions().is_none()) {
+            let witnesses = Self::select_witnesses(&self.permissions, delegator.permission_witnesses(), &kept_permissions)?;
+            *delegator.mut_permission_witnesses() = witnesses;
         }
+        self.permissions = kept_permissions;
+        // The broader permissions of the other delegators are only disclosed when still needed
+        Self::narrow_hierarchy(&mut self.hierarchy, &self.permissions);
 
         Ok(removable_indices)
     }
@@ -137,21 +233,23 @@ impl Display for OurDelegationCredential {
 
         match Credential::to_string(self) {
             Ok(result) => write!(f, "{}", result),
-            Err(e) => {
-                eprintln!("OurDelegationCredential serialization failed: {}", e);
-                Err(std::fmt::Error)
-            }
+            Err(_) => Err(std::fmt::Error),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::delegation::accumulators::accumulator_element::ElementEncoding;
     use crate::delegation::credentials::ours::our_delegation_credential::OurDelegationCredential;
+    use crate::delegation::credentials::ours::our_delegation::OurDelegation;
+    use crate::delegation::credentials::permission::Permission;
     use crate::delegation::traits::credential::Credential;
+    use serde_json::Value;
+    use std::str::FromStr;
 
     #[test]
-    fn parse_dc() -> Result<(), String> {
+    fn parse_dc() -> Result<(), Box<dyn std::error::Error>> {
 
         let dcs: Vec<&str> = vec![DC_D1, DC_D2, DC_D3, DC_D4];
         let names: Vec<&str> = vec!["D1", "D2", "D3", "D4"];
@@ -160,7 +258,7 @@ mod tests {
 
             let dc: OurDelegationCredential = match serde_json::from_str(dc) {
                 Ok(dc) => dc,
-                Err(err) => { return Err(format!("Failed to deserialize DelegationCredential [{err}]")) }
+                Err(err) => { return Err(format!("Failed to deserialize DelegationCredential [{err}]").into()) }
             };
 
             let dc_map = dc.to_map()?;
@@ -172,6 +270,72 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn retain_by_permission() -> Result<(), Box<dyn std::error::Error>> {
+        let mut dc: OurDelegationCredential = serde_json::from_str(DC_D3)?;
+        let p0 = Permission::from_str("https://vc.example/resources/r1:p0")?;
+        let p1 = Permission::from_str("https://vc.example/resources/r1:p1")?;
+
+        // Permissions are kept in the order of the credential, whatever their order and duplicates
+        let removed_indices = dc.retain_only(vec![p1.clone(), p0.clone(), p1.clone()])?;
+        assert!(removed_indices.is_empty());
+        assert_eq!(*dc.permissions(), vec![p0.clone(), p1.clone()]);
+        assert_eq!(*dc.permission_witnesses(), vec![String::from("w0d3"), String::from("w1d3")]);
+
+        assert_eq!(dc.retain_only(vec![p1.clone()])?, vec![0]);
+        assert_eq!(*dc.permissions(), vec![p1]);
+        assert_eq!(*dc.permission_witnesses(), vec![String::from("w1d3")]);
+        for (delegator, witness) in dc.hierarchy().iter().zip(["w1d1", "w1d2"]) {
+            assert_eq!(*delegator.permission_witnesses(), vec![String::from(witness)]);
+        }
+
+        let witnesses = vec![String::from("w0d1"), String::from("w1d1")];
+        assert!(OurDelegationCredential::select_witnesses(&[p0], &witnesses, &[]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_legacy_dc() -> Result<(), Box<dyn std::error::Error>> {
+        // Credentials issued before permissions had a resource and an action, and before the typed
+        // encoding, carry bare strings
+        let mut dc: OurDelegationCredential = serde_json::from_str(DC_LEGACY)?;
+        let p0 = Permission::from_str("p0")?;
+        let p1 = Permission::from_str("p1")?;
+        assert_eq!(*dc.permissions(), vec![p0.clone(), p1.clone()]);
+        assert_eq!(dc.element_encoding(), ElementEncoding::Legacy);
+
+        // Serializing it again keeps the permissions, and so the accumulated elements, unchanged
+        let round_trip = OurDelegationCredential::from_map(dc.to_map()?)?;
+        let original: Value = serde_json::from_str(DC_LEGACY)?;
+        assert_eq!(serde_json::to_value(&round_trip)?["per"], original["per"]);
+        assert_eq!(*round_trip.permissions(), *dc.permissions());
+
+        assert_eq!(dc.retain_only(vec![p1.clone()])?, vec![0]);
+        assert_eq!(*dc.permissions(), vec![p1]);
+        Ok(())
+    }
+
+    pub const DC_LEGACY: &str = r#"{
+        "sub": "https://vc.example/delegators/d2",
+        "av": "accumulator_value_d2",
+        "iat": "0000000002",
+        "exp": "1000000000",
+        "per": [ "p0", "p1" ],
+        "mw": [ "w_delegatee_id_d2", "w_iat_d2", "w_exp_d2" ],
+        "pw": [ "w0d2", "w1d2" ],
+        "hierarchy": [
+            {
+                "id": "https://vc.example/delegators/d0",
+                "sub": "https://vc.example/delegators/d1",
+                "iat": "0000000001",
+                "exp": "1000000000",
+                        "av": "accumulator_value_d1",
+                "mw": [ "w_delegatee_id_d1", "w_iat_d1", "w_exp_d1" ],
+                "pw": [ "w0d1", "w1d1" ]
+            }
+        ]
+    }"#;
+
     pub const DC_D1: &str = r#"{
         "sub": "https://vc.example/delegators/d1",
         "av": "accumulator_value_d1",
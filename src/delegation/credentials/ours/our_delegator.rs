@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
+use crate::delegation::accumulators::accumulator_element::ElementEncoding;
 use crate::delegation::credentials::ours::our_delegation::OurDelegation;
+use crate::delegation::credentials::caveat::Caveat;
+use crate::delegation::credentials::permission::Permission;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct OurDelegator {
@@ -12,17 +15,29 @@ pub struct OurDelegator {
     iat: String,
     #[serde(rename = "exp")]
     exp: String,
+    // Maximum number of delegations that may follow the delegation, none if unlimited
+    #[serde(rename = "md", default, skip_serializing_if = "Option::is_none")]
+    max_depth: Option<usize>,
+    #[serde(rename = "cav", default, skip_serializing_if = "Vec::is_empty")]
+    caveats: Vec<Caveat>,
+    #[serde(rename = "enc", default)]
+    element_encoding: ElementEncoding,
     #[serde(rename = "av")]
     accumulator_value: String,
     #[serde(rename = "mw")]
     metadata_witnesses: Vec<String>,
+    // Permissions proven by the witnesses when the credential was delegated from broader ones,
+    // none when they are the permissions of the credential
+    #[serde(rename = "per", default, skip_serializing_if = "Option::is_none")]
+    permissions: Option<Vec<Permission>>,
     #[serde(rename = "pw")]
     permission_witnesses: Vec<String>,
 }
 
 impl OurDelegator {
-    pub fn new(id: String, delegatee_id: String, iat: String, exp: String, accumulator_value: String, metadata_witnesses: Vec<String>, permission_witnesses: Vec<String>) -> OurDelegator {
-        OurDelegator { id, delegatee_id, iat, exp, accumulator_value, metadata_witnesses, permission_witnesses }
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(id: String, delegatee_id: String, iat: String, exp: String, element_encoding: ElementEncoding, accumulator_value: String, metadata_witnesses: Vec<String>, permission_witnesses: Vec<String>) -> OurDelegator {
+        OurDelegator { id, delegatee_id, iat, exp, element_encoding, accumulator_value, metadata_witnesses, max_depth: None, caveats: vec![], permissions: None, permission_witnesses }
     }
 
     pub fn id(&self) -> &String {
@@ -33,6 +48,51 @@ impl OurDelegator {
         &mut self.permission_witnesses
     }
 
+    /// Sets the maximum number of delegations that may follow the one of the delegator, which is
+    /// part of the key of its accumulator.
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.max_depth = max_depth;
+    }
+
+    /// Sets the caveats restricting the requests the delegation of the delegator may be used for, which are part of
+    /// the key of its accumulator.
+    pub fn set_caveats(&mut self, caveats: Vec<Caveat>) {
+        self.caveats = caveats;
+    }
+
+    /// Returns the permissions granted to the delegator that its permission witnesses prove, none
+    /// when they are the permissions of the credential holding the delegator.
+    pub fn permissions(&self) -> Option<&Vec<Permission>> {
+        self.permissions.as_ref()
+    }
+
+    /// Sets the permissions proven by the permission witnesses, none meaning the permissions of
+    /// the credential holding the delegator.
+    pub fn set_permissions(&mut self, permissions: Option<Vec<Permission>>) {
+        self.permissions = permissions;
+    }
+
+    /// Returns the permissions proven by the permission witnesses.
+    ///
+    /// # Arguments
+    /// * `credential_permissions` - Permissions of the credential holding the delegator.
+    pub fn proven_permissions<'a>(&'a self, credential_permissions: &'a [Permission]) -> &'a [Permission] {
+        self.permissions.as_deref().unwrap_or(credential_permissions)
+    }
+
+    /// Keeps the proven permissions for which `keep` holds, together with their witnesses. Nothing
+    /// is removed when the delegator proves the permissions of the credential.
+    pub fn retain_permissions<F: Fn(&Permission) -> bool>(&mut self, keep: F) {
+        if let Some(permissions) = self.permissions.take() {
+            let (permissions, witnesses): (Vec<Permission>, Vec<String>) = permissions.into_iter()
+                .zip(self.permission_witnesses.drain(..))
+                .filter(|(permission, _)| keep(permission))
+                .unzip();
+            self.permissions = Some(permissions);
+            self.permission_witnesses = witnesses;
+        }
+    }
+
 }
 
 impl OurDelegation for OurDelegator {
@@ -48,12 +108,26 @@ impl OurDelegation for OurDelegator {
     fn exp(&self) -> &String {
         &self.exp
     }
+    fn element_encoding(&self) -> ElementEncoding {
+        self.element_encoding
+    }
+    fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+    fn caveats(&self) -> &Vec<Caveat> {
+        &self.caveats
+    }
     fn metadata_witnesses(&self) -> &Vec<String> {
         &self.metadata_witnesses
     }
     fn permission_witnesses(&self) -> &Vec<String> {
         &self.permission_witnesses
     }
+    fn set_accumulator(&mut self, accumulator_value: String, metadata_witnesses: Vec<String>, permission_witnesses: Vec<String>) {
+        self.accumulator_value = accumulator_value;
+        self.metadata_witnesses = metadata_witnesses;
+        self.permission_witnesses = permission_witnesses;
+    }
 }
 
 impl Display for OurDelegator {
@@ -61,10 +135,7 @@ impl Display for OurDelegator {
 
         match serde_json::to_string(self) {
             Ok(result) => write!(f, "{}", result),
-            Err(e) => {
-                eprintln!("OurDelegator serialization failed: {}", e);
-                Err(std::fmt::Error)
-            }
+            Err(_) => Err(std::fmt::Error),
         }
     }
 }
@@ -87,21 +158,21 @@ mod tests {
         }"#;
 
     #[test]
-    fn display_delegator() -> Result<(), String> {
+    fn display_delegator() -> Result<(), Box<dyn std::error::Error>> {
 
         let delegator_value = match serde_json::from_str::<Value>(DELEGATOR_OBJECT) {
             Ok(delegator_value) => delegator_value,
-            Err(e) => return Err(format!("Failed to parse delegator object: [{e}]")),
+            Err(e) => return Err(format!("Failed to parse delegator object: [{e}]").into()),
         };
         let delegator_map = match delegator_value {
             Value::Object(delegator_map) => delegator_map,
-            _ => return Err(format!("Parsed delegator {delegator_value} is not an object.")),
+            _ => return Err(format!("Parsed delegator {delegator_value} is not an object.").into()),
         };
 
         let delegator_value = Value::Object(delegator_map);
         let delegator = match serde_json::from_value::<OurDelegator>(delegator_value) {
             Ok(delegator) => delegator,
-            Err(err) => return Err(format!("Failed to parse delegator object: [{err}]")),
+            Err(err) => return Err(format!("Failed to parse delegator object: [{err}]").into()),
         };
 
         println!("{delegator}");
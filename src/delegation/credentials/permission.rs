@@ -0,0 +1,345 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::str::FromStr;
+use crate::delegation::error::DelegationError;
+
+/// Resource or action matching any other.
+const WILDCARD: &str = "*";
+/// Suffix of the resources matching a single path segment below their prefix.
+const SEGMENT_WILDCARD: &str = "/*";
+/// Suffix of the resources matching their prefix and any path below it.
+const PATH_WILDCARD: &str = "/**";
+/// Actions implied by a stronger action, e.g. a delegator allowed to write a resource may delegate
+/// reading it.
+const IMPLIED_ACTIONS: [(&str, &[&str]); 2] = [("write", &["read"]), ("admin", &["write", "read"])];
+
+/// Permission granted by a delegation credential: an action on a resource, optionally restricted by
+/// named constraints.
+///
+/// Permissions are serialized, and accumulated, in their canonical form
+/// `{resource}:{action}?{name}={value}&...`, where the constraints are sorted by name and omitted
+/// when there are none. A permission without constraints is therefore encoded as the bare strings
+/// of the credentials issued before this type, e.g. `https://vc.example/resources/r1:p0`. Bare
+/// strings without any `:`, e.g. `p0`, are kept as opaque permissions: they have no action, their
+/// resource being the whole string, and only imply themselves.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Permission {
+    resource: String,
+    // None for opaque permissions
+    action: Option<String>,
+    constraints: BTreeMap<String, String>,
+}
+
+impl Permission {
+
+    /// Creates a permission without constraints.
+    ///
+    /// # Arguments
+    /// * `resource` - URI of the resource, which cannot contain a `?`.
+    /// * `action` - Action allowed on the resource, which cannot contain a `:` or a `?`.
+    ///
+    /// # Returns
+    /// A result wrapping the permission or the error, if the resource or the action is not valid.
+    pub fn new(resource: String, action: String) -> Result<Self, DelegationError> {
+        let invalid = |reason| DelegationError::InvalidPermission { permission: format!("{resource}:{action}"), reason };
+        if resource.is_empty() || resource.contains('?') {
+            return Err(invalid("the resource must be a non-empty URI without query"));
+        }
+        if action.is_empty() || action.contains([':', '?']) {
+            return Err(invalid("the action must be non-empty and cannot contain ':' or '?'"));
+        }
+        Ok(Permission { resource, action: Some(action), constraints: BTreeMap::new() })
+    }
+
+    /// Creates an opaque permission, as granted by the credentials issued before permissions had a
+    /// resource and an action.
+    ///
+    /// # Arguments
+    /// * `permission` - Bare permission, which cannot contain a `:`.
+    ///
+    /// # Returns
+    /// A result wrapping the permission or the error, if the permission is empty or contains a `:`.
+    pub fn opaque(permission: String) -> Result<Self, DelegationError> {
+        if permission.is_empty() || permission.contains(':') {
+            return Err(DelegationError::InvalidPermission { permission, reason: "opaque permissions must be non-empty and cannot contain ':'" });
+        }
+        Ok(Permission { resource: permission, action: None, constraints: BTreeMap::new() })
+    }
+
+    /// Restricts the permission with a constraint, replacing any previous constraint with the same
+    /// name.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the constraint, which cannot contain `=` or `&`.
+    /// * `value` - Value of the constraint, which cannot contain `&`.
+    ///
+    /// # Returns
+    /// A result wrapping the restricted permission or the error, if the constraint is not valid.
+    pub fn with_constraint(mut self, name: String, value: String) -> Result<Self, DelegationError> {
+        if self.is_opaque() {
+            return Err(DelegationError::InvalidPermission { permission: self.to_string(), reason: "opaque permissions cannot carry constraints" });
+        }
+        if name.is_empty() || name.contains(['=', '&']) || value.contains('&') {
+            return Err(DelegationError::InvalidPermission {
+                permission: self.to_string(),
+                reason: "constraint names must be non-empty and cannot contain '=' or '&', nor can their values contain '&'",
+            });
+        }
+        self.constraints.insert(name, value);
+        Ok(self)
+    }
+
+    pub fn resource(&self) -> &String {
+        &self.resource
+    }
+
+    /// Returns the action allowed on the resource, none for opaque permissions.
+    pub fn action(&self) -> Option<&String> {
+        self.action.as_ref()
+    }
+
+    pub fn is_opaque(&self) -> bool {
+        self.action.is_none()
+    }
+
+    pub fn constraints(&self) -> &BTreeMap<String, String> {
+        &self.constraints
+    }
+
+    /// Returns whether the permission grants everything `other` grants, so that a delegator holding
+    /// it may delegate `other`. The resource of the permission must match the one of `other`, either
+    /// exactly or through a trailing `/*`, `/**` or a `*` resource, its action must be equal to, a
+    /// wildcard for or stronger than the one of `other`, and `other` must keep every constraint.
+    /// Trailing wildcards never match empty, `.` or `..` segments, even percent-encoded. Opaque
+    /// permissions only imply, and are only implied by, themselves.
+    ///
+    /// # Example
+    /// ```
+    /// use delegation::delegation::credentials::permission::Permission;
+    /// use std::str::FromStr;
+    ///
+    /// let parent = Permission::from_str("/buildings/a/**:write").unwrap();
+    /// assert!(parent.implies(&Permission::from_str("/buildings/a/floor2:read").unwrap()));
+    /// assert!(!parent.implies(&Permission::from_str("/buildings/b:read").unwrap()));
+    /// ```
+    pub fn implies(&self, other: &Permission) -> bool {
+        match (&self.action, &other.action) {
+            (Some(action), Some(other_action)) => Self::resource_implies(&self.resource, &other.resource)
+                && Self::action_implies(action, other_action)
+                && self.constraints.iter().all(|(name, value)| other.constraints.get(name) == Some(value)),
+            _ => self == other,
+        }
+    }
+
+    fn resource_implies(resource: &str, other: &str) -> bool {
+        if resource == other || resource == WILDCARD {
+            return true;
+        }
+        if let Some(prefix) = resource.strip_suffix(PATH_WILDCARD) {
+            // Any path below the prefix, including the ones matched by a narrower wildcard
+            let other = other.strip_suffix(PATH_WILDCARD).or_else(|| other.strip_suffix(SEGMENT_WILDCARD)).unwrap_or(other);
+            return other == prefix || other.strip_prefix(prefix)
+                .and_then(|path| path.strip_prefix('/'))
+                .is_some_and(|path| path.split('/').all(Self::is_plain_segment));
+        }
+        if let Some(prefix) = resource.strip_suffix(SEGMENT_WILDCARD) {
+            return other.strip_prefix(prefix)
+                .and_then(|path| path.strip_prefix('/'))
+                .is_some_and(|segment| Self::is_plain_segment(segment) && !segment.contains(WILDCARD));
+        }
+        false
+    }
+
+    // Paths are not normalized, so a wildcard only matches segments that name a resource below its
+    // prefix: empty, `.` and `..` segments could reach a sibling resource once normalized by the
+    // verifier, and so could their percent-encoded forms, however many times they are encoded.
+    fn is_plain_segment(segment: &str) -> bool {
+        let mut decoded = segment.as_bytes().to_vec();
+        loop {
+            let next = Self::percent_decode(&decoded);
+            if next == decoded {
+                break;
+            }
+            decoded = next;
+        }
+        !decoded.is_empty() && decoded != b"." && decoded != b".." && !decoded.contains(&b'/') && !decoded.contains(&b'\\')
+    }
+
+    fn percent_decode(bytes: &[u8]) -> Vec<u8> {
+        let hex = |byte: u8| (byte as char).to_digit(16).map(|digit| digit as u8);
+        let mut decoded = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            match (bytes[i], bytes.get(i + 1).copied().and_then(hex), bytes.get(i + 2).copied().and_then(hex)) {
+                (b'%', Some(high), Some(low)) => {
+                    decoded.push(high << 4 | low);
+                    i += 3;
+                }
+                (byte, _, _) => {
+                    decoded.push(byte);
+                    i += 1;
+                }
+            }
+        }
+        decoded
+    }
+
+    fn action_implies(action: &str, other: &str) -> bool {
+        action == other || action == WILDCARD || IMPLIED_ACTIONS.iter()
+            .any(|(stronger, implied)| *stronger == action && implied.contains(&other))
+    }
+}
+
+impl Display for Permission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.action {
+            Some(action) => write!(f, "{}:{action}", self.resource)?,
+            None => { return write!(f, "{}", self.resource) }
+        }
+        for (i, (name, value)) in self.constraints.iter().enumerate() {
+            write!(f, "{}{name}={value}", if i == 0 { '?' } else { '&' })?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Permission {
+    type Err = DelegationError;
+
+    fn from_str(permission: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason| DelegationError::InvalidPermission { permission: permission.to_string(), reason };
+
+        // Permissions of the credentials issued before they had a resource and an action
+        if !permission.contains(':') {
+            return Permission::opaque(permission.to_string());
+        }
+
+        let (resource_action, constraints) = match permission.split_once('?') {
+            Some((resource_action, constraints)) => (resource_action, Some(constraints)),
+            None => (permission, None),
+        };
+        let (resource, action) = match resource_action.rsplit_once(':') {
+            Some((resource, action)) => (resource, action),
+            None => { return Err(invalid("the resource and the action must be separated by ':'")) }
+        };
+
+        let mut result = Permission::new(resource.to_string(), action.to_string())?;
+        for constraint in constraints.into_iter().flat_map(|constraints| constraints.split('&')) {
+            let (name, value) = match constraint.split_once('=') {
+                Some((name, value)) => (name, value),
+                None => { return Err(invalid("constraints must be written as name=value")) }
+            };
+            if result.constraints.contains_key(name) {
+                return Err(invalid("constraints cannot be repeated"));
+            }
+            result = result.with_constraint(name.to_string(), value.to_string())?;
+        }
+        Ok(result)
+    }
+}
+
+impl TryFrom<String> for Permission {
+    type Error = DelegationError;
+
+    fn try_from(permission: String) -> Result<Self, Self::Error> {
+        Permission::from_str(&permission)
+    }
+}
+
+impl From<Permission> for String {
+    fn from(permission: Permission) -> Self {
+        permission.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_form() -> Result<(), Box<dyn std::error::Error>> {
+        let permission = Permission::new(String::from("https://vc.example/resources/r1"), String::from("read"))?;
+        assert_eq!(permission.to_string(), "https://vc.example/resources/r1:read");
+        assert_eq!(Permission::from_str("https://vc.example/resources/r1:read")?, permission);
+
+        // Constraints are sorted whatever the order they are given in
+        let constrained = permission
+            .with_constraint(String::from("until"), String::from("18:00"))?
+            .with_constraint(String::from("amount"), String::from("100"))?;
+        assert_eq!(constrained.to_string(), "https://vc.example/resources/r1:read?amount=100&until=18:00");
+        assert_eq!(Permission::from_str("https://vc.example/resources/r1:read?until=18:00&amount=100")?, constrained);
+        assert_eq!(constrained.constraints().get("amount"), Some(&String::from("100")));
+
+        let json = serde_json::to_string(&vec![constrained.clone()])?;
+        assert_eq!(json, r#"["https://vc.example/resources/r1:read?amount=100&until=18:00"]"#);
+        assert_eq!(serde_json::from_str::<Vec<Permission>>(&json)?, vec![constrained]);
+
+        for invalid in ["", "r1:", ":read", "r1:read?amount", "r1:read?amount=1&amount=2", "r1?x:read"] {
+            if Permission::from_str(invalid).is_ok() {
+                return Err(format!("Permission {invalid} should not be valid").into());
+            }
+        }
+        assert!(Permission::new(String::from("r1"), String::from("a:b")).is_err());
+
+        // Bare permissions of older credentials are kept as they are
+        let opaque = Permission::from_str("p0")?;
+        assert!(opaque.is_opaque() && opaque.action().is_none());
+        assert_eq!(opaque.to_string(), "p0");
+        assert_eq!(serde_json::from_str::<Vec<Permission>>(r#"["p0"]"#)?, vec![opaque.clone()]);
+        assert!(opaque.with_constraint(String::from("amount"), String::from("100")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn imply_narrower_permissions() -> Result<(), Box<dyn std::error::Error>> {
+        let implies = |parent: &str, child: &str| -> Result<bool, DelegationError> {
+            Ok(Permission::from_str(parent)?.implies(&Permission::from_str(child)?))
+        };
+
+        assert!(implies("https://vc.example/resources/r1:*", "https://vc.example/resources/r1:read")?);
+        assert!(implies("https://vc.example/resources/r1:write", "https://vc.example/resources/r1:read")?);
+        assert!(implies("https://vc.example/resources/r1:admin", "https://vc.example/resources/r1:write")?);
+        assert!(!implies("https://vc.example/resources/r1:read", "https://vc.example/resources/r1:write")?);
+        assert!(!implies("https://vc.example/resources/r1:read", "https://vc.example/resources/r1:*")?);
+        assert!(!implies("https://vc.example/resources/r1:*", "https://vc.example/resources/r2:read")?);
+
+        assert!(implies("/buildings/a/**:read", "/buildings/a:read")?);
+        assert!(implies("/buildings/a/**:read", "/buildings/a/floor2/room1:read")?);
+        assert!(implies("/buildings/a/**:read", "/buildings/a/floor2/*:read")?);
+        assert!(!implies("/buildings/a/**:read", "/buildings/ab:read")?);
+        assert!(implies("/buildings/a/*:read", "/buildings/a/floor2:read")?);
+        assert!(!implies("/buildings/a/*:read", "/buildings/a/floor2/room1:read")?);
+        assert!(!implies("/buildings/a/*:read", "/buildings/a/**:read")?);
+        assert!(!implies("/buildings/a/floor2:read", "/buildings/a/*:read")?);
+        assert!(implies("*:*", "/buildings/a/floor2:write")?);
+
+        // Opaque permissions only imply themselves
+        assert!(implies("p0", "p0")?);
+        assert!(!implies("p0", "p1")?);
+        assert!(!implies("*:*", "p0")?);
+        assert!(!implies("p0", "p0:read")?);
+
+        // Wildcards do not reach sibling resources through segments normalized by the verifier
+        for traversal in ["/buildings/a/../b", "/buildings/a/floor2/../../b", "/buildings/a/./floor2", "/buildings/a//floor2",
+                          "/buildings/a/%2e%2e/b", "/buildings/a/.%2E/b", "/buildings/a/%252e%252e/b", "/buildings/a/..%2fb", "/buildings/a/..%5cb"] {
+            if implies("/buildings/a/**:read", &format!("{traversal}:read"))? {
+                return Err(format!("/buildings/a/** should not imply {traversal}").into());
+            }
+        }
+        for traversal in ["/buildings/..", "/buildings/.", "/buildings/", "/buildings/%2E%2E", "/buildings/%2e", "/buildings/..%2Fa"] {
+            if implies("/buildings/*:read", &format!("{traversal}:read"))? {
+                return Err(format!("/buildings/* should not imply {traversal}").into());
+            }
+        }
+        assert!(implies("/buildings/a/**:read", "/buildings/a/floor%202:read")?);
+        assert!(implies("/buildings/*:read", "/buildings/a..b:read")?);
+
+        // Constraints can only be added
+        assert!(implies("/buildings/a:read?floor=2", "/buildings/a:read?floor=2&until=18:00")?);
+        assert!(!implies("/buildings/a:read?floor=2", "/buildings/a:read")?);
+        assert!(!implies("/buildings/a:read?floor=2", "/buildings/a:read?floor=3")?);
+        Ok(())
+    }
+}
@@ -8,6 +8,7 @@ use crate::delegation::traits::credential::Credential;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use std::fmt::Display;
+use crate::delegation::error::DelegationError;
 use crate::delegation::credentials::pjv::pjv_signature::PJVSignature;
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -20,7 +21,7 @@ pub struct PJVDelegationCredential {
 
 
 impl PJVDelegationCredential {
-    pub fn new(delegator: PJVDelegator, signature: PJVSignature) -> Result<PJVDelegationCredential, String> {
+    pub fn new(delegator: PJVDelegator, signature: PJVSignature) -> Result<PJVDelegationCredential, DelegationError> {
         Ok(PJVDelegationCredential { delegator, signature })
     }
 
@@ -31,50 +32,52 @@ impl PJVDelegationCredential {
 }
 
 impl Credential for PJVDelegationCredential {
+    type Claim = String;
+
     fn credential_type(&self) -> &'static str {
         "PJVDelegationCredential"
     }
 
-    fn from_map(map: Map<String, Value>) -> Result<Self, String> {
+    fn from_map(map: Map<String, Value>) -> Result<Self, DelegationError> {
         match serde_json::from_value::<PJVDelegationCredential>(Value::Object(map.clone())) {
             Ok(credential) => Ok(credential),
-            Err(err) => Err(format!("Error in parsing PJVDelegationCredential: {err}")),
+            Err(err) => Err(DelegationError::Json { context: "Error in parsing PJVDelegationCredential", source: err }),
         }
     }
 
-    fn from_string(str: String) -> Result<Self, String>
+    fn from_string(str: String) -> Result<Self, DelegationError>
     {
         match serde_json::from_str::<PJVDelegationCredential>(&str) {
             Ok(credential) => Ok(credential),
-            Err(err) => { Err(format!("Failed to deserialize PJVDelegationCredential [{err}]")) }
+            Err(err) => { Err(DelegationError::Json { context: "Failed to deserialize PJVDelegationCredential", source: err }) }
         }
     }
 
-    fn to_map(&self) -> Result<Map<String, Value>, String> {
-        let map_value = match::serde_json::to_value(&self) {
+    fn to_map(&self) -> Result<Map<String, Value>, DelegationError> {
+        let map_value = match::serde_json::to_value(self) {
             Ok(map_value) => map_value,
-            Err(err) => { return Err(format!("Failed to serialize PJVDelegationCredential to map [{err}]")) }
+            Err(err) => { return Err(DelegationError::Json { context: "Failed to serialize PJVDelegationCredential to map", source: err }) }
         };
 
         match map_value {
             Value::Object(map) => Ok(map),
-            _ => Err(format!("Serialized map is not an object [{map_value}]")),
+            _ => Err(DelegationError::NotAnObject),
         }
     }
 
-    fn to_string(&self) -> Result<String, String> {
+    fn to_string(&self) -> Result<String, DelegationError> {
         match serde_json::to_string(&self) {
             Ok(str) => Ok(str),
-            Err(err) => { Err(format!("Failed to serialize PJVDelegationCredential to json string [{err}]")) }
+            Err(err) => { Err(DelegationError::Json { context: "Failed to serialize PJVDelegationCredential to json string", source: err }) }
         }
     }
 
-    fn retain_only(&mut self, allowed: Vec<String>) -> Result<Vec<usize>, String> {
+    fn retain_only(&mut self, allowed: Vec<String>) -> Result<Vec<usize>, DelegationError> {
 
         let mut removable_indices: Vec<usize> = vec![];
 
         for (i, operation) in self.delegator.operations().iter().enumerate() {
-            if !allowed.contains(&operation) {
+            if !allowed.contains(operation) {
                 removable_indices.push(i);
             }
         }
@@ -96,10 +99,7 @@ impl Display for PJVDelegationCredential {
 
         match Credential::to_string(self) {
             Ok(result) => write!(f, "{}", result),
-            Err(e) => {
-                eprintln!("PJVDelegationCredential serialization failed: {}", e);
-                Err(std::fmt::Error)
-            }
+            Err(_) => Err(std::fmt::Error),
         }
     }
 }
\ No newline at end of file
@@ -1,3 +1,5 @@
+use crate::delegation::credentials::permission::Permission;
+use crate::delegation::error::DelegationError;
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
@@ -17,13 +19,17 @@ pub struct PJVDelegator {
     resource_uri: String,
     #[serde(rename = "ops")]
     operations: Vec<String>,
+    // Maximum number of delegations that may follow the delegation, none if unlimited
+    #[serde(rename = "md", default, skip_serializing_if = "Option::is_none")]
+    max_depth: Option<usize>,
     #[serde(rename = "hierarchy")]
     hierarchy: String,
 }
 
 impl PJVDelegator {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(owner: String, iss: String, sub: String, iat: String, exp: String, resource_uri: String, operations: Vec<String>, hierarchy: String) -> PJVDelegator {
-        PJVDelegator { owner, iss, sub, iat, exp, resource_uri, operations, hierarchy}
+        PJVDelegator { owner, iss, sub, iat, exp, resource_uri, operations, max_depth: None, hierarchy}
     }
 
     pub fn owner(&self) -> &String {&self.owner}
@@ -35,6 +41,23 @@ impl PJVDelegator {
     pub fn operations(&self) -> &Vec<String> {&self.operations}
     pub fn mut_operations(&mut self) -> &mut Vec<String> {&mut self.operations}
     pub fn hierarchy(&self) -> &String {&self.hierarchy}
+    pub fn max_depth(&self) -> Option<usize> {self.max_depth}
+
+    /// Sets the maximum number of delegations that may follow the delegation, before the delegator
+    /// is signed.
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.max_depth = max_depth;
+    }
+
+    /// Returns the operations granted by the delegator as permissions on its resource.
+    ///
+    /// # Returns
+    /// A result wrapping the permissions or the error, if the resource or an operation is not valid.
+    pub fn permissions(&self) -> Result<Vec<Permission>, DelegationError> {
+        self.operations.iter()
+            .map(|operation| Permission::new(self.resource_uri.clone(), operation.clone()))
+            .collect()
+    }
 }
 
 impl Display for PJVDelegator {
@@ -42,10 +65,7 @@ impl Display for PJVDelegator {
 
         match serde_json::to_string(self) {
             Ok(result) => write!(f, "{}", result),
-            Err(e) => {
-                eprintln!("PJVDelegator serialization failed: {}", e);
-                Err(std::fmt::Error)
-            }
+            Err(_) => Err(std::fmt::Error),
         }
     }
 }
@@ -69,21 +89,21 @@ mod tests {
         }"#;
 
     #[test]
-    fn display_delegator() -> Result<(), String> {
+    fn display_delegator() -> Result<(), Box<dyn std::error::Error>> {
 
         let delegator_value = match serde_json::from_str::<Value>(DELEGATOR_OBJECT) {
             Ok(delegator_value) => delegator_value,
-            Err(e) => return Err(format!("Failed to parse delegator object: [{e}]")),
+            Err(e) => return Err(format!("Failed to parse delegator object: [{e}]").into()),
         };
         let delegator_map = match delegator_value {
             Value::Object(delegator_map) => delegator_map,
-            _ => return Err(format!("Parsed delegator {delegator_value} is not an object.")),
+            _ => return Err(format!("Parsed delegator {delegator_value} is not an object.").into()),
         };
 
         let delegator_value = Value::Object(delegator_map);
         let delegator = match serde_json::from_value::<PJVDelegator>(delegator_value) {
             Ok(delegator) => delegator,
-            Err(err) => return Err(format!("Failed to parse delegator object: [{err}]")),
+            Err(err) => return Err(format!("Failed to parse delegator object: [{err}]").into()),
         };
 
         println!("{delegator}");
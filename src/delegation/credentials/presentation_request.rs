@@ -0,0 +1,59 @@
+use ark_std::rand::prelude::StdRng;
+use ark_std::rand::{RngCore, SeedableRng};
+use multibase::Base::Base64Url;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use crate::delegation::clock::clock::Clock;
+use crate::delegation::clock::system_clock::SystemClock;
+use crate::delegation::error::DelegationError;
+
+/// Length in bytes of the random nonces.
+const NONCE_LENGTH: usize = 16;
+
+/// Generates a random base64url encoded nonce.
+pub fn random_nonce() -> String {
+    let mut rng: StdRng = StdRng::from_entropy();
+    let mut nonce = [0u8; NONCE_LENGTH];
+    rng.fill_bytes(&mut nonce);
+    multibase::encode(Base64Url, nonce)
+}
+
+/// Presentation request created by a verifier and sent to the holder. The holder embeds the nonce
+/// and the audience in the signed presentation, so that the presentation is only accepted once,
+/// by this verifier and before the request expires.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PresentationRequest {
+    nonce: String,
+    #[serde(rename = "aud")]
+    audience: String,
+    /// Expiration of the request in nanoseconds since the Unix epoch.
+    #[serde(rename = "exp")]
+    expires_at: u128,
+}
+
+impl PresentationRequest {
+
+    /// Creates a presentation request with a fresh random nonce.
+    ///
+    /// # Arguments
+    /// * `audience` - Id of the verifier the presentation is meant for.
+    /// * `validity_period` - Time the holder has to answer the request.
+    ///
+    /// # Returns
+    /// A result wrapping the request to be sent to the holder or the error, if the clock is not valid.
+    pub fn new(audience: String, validity_period: Duration) -> Result<Self, DelegationError> {
+        Self::with_clock(audience, validity_period, &SystemClock)
+    }
+
+    /// Creates a presentation request expiring `validity_period` after the current time of `clock`.
+    pub fn with_clock(audience: String, validity_period: Duration, clock: &dyn Clock) -> Result<Self, DelegationError> {
+        let now = clock.now()?;
+        Ok(PresentationRequest { nonce: random_nonce(), audience, expires_at: now + validity_period.as_nanos() })
+    }
+
+    pub fn nonce(&self) -> &String { &self.nonce }
+
+    pub fn audience(&self) -> &String { &self.audience }
+
+    pub fn expires_at(&self) -> u128 { self.expires_at }
+}
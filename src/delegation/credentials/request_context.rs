@@ -0,0 +1,46 @@
+use std::net::IpAddr;
+
+/// Context of a request to a resource, against which the caveats of a delegation chain are
+/// evaluated. The time of the request is the one of the verifier.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RequestContext {
+    client_ip: Option<IpAddr>,
+    amount: Option<u64>,
+    audience: Option<String>,
+}
+
+impl RequestContext {
+
+    /// Creates an empty context, which only satisfies the time caveats.
+    pub fn new() -> Self {
+        RequestContext::default()
+    }
+
+    pub fn with_client_ip(mut self, client_ip: IpAddr) -> Self {
+        self.client_ip = Some(client_ip);
+        self
+    }
+
+    pub fn with_amount(mut self, amount: u64) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    /// Sets the verifier the request is meant for, usually the id of the verifier itself.
+    pub fn with_audience(mut self, audience: String) -> Self {
+        self.audience = Some(audience);
+        self
+    }
+
+    pub fn client_ip(&self) -> Option<&IpAddr> {
+        self.client_ip.as_ref()
+    }
+
+    pub fn amount(&self) -> Option<u64> {
+        self.amount
+    }
+
+    pub fn audience(&self) -> Option<&String> {
+        self.audience.as_ref()
+    }
+}
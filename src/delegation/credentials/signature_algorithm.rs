@@ -0,0 +1,152 @@
+use crate::delegation::credentials::presentation_request::random_nonce;
+use crate::delegation::error::DelegationError;
+use ark_std::rand::prelude::StdRng;
+use ark_std::rand::{RngCore, SeedableRng};
+use ed25519_dalek::{SecretKey, SigningKey};
+use josekit::jwk::{Jwk, KeyPair};
+use josekit::jws::{EdDSA, JwsSigner, JwsVerifier, ES256, ES256K};
+use multibase::Base::Base64Url;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt::Display;
+
+/// Algorithms used to sign presentations and delegators, as named in the JOSE `alg` header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SignatureAlgorithm {
+    /// EdDSA over Ed25519.
+    EdDSA,
+    /// ECDSA over P-256 with SHA-256.
+    ES256,
+    /// ECDSA over secp256k1 with SHA-256.
+    ES256K,
+}
+
+impl SignatureAlgorithm {
+
+    /// Every supported algorithm, which is also the default allow-list of the verifiers.
+    pub const ALL: [SignatureAlgorithm; 3] = [SignatureAlgorithm::EdDSA, SignatureAlgorithm::ES256, SignatureAlgorithm::ES256K];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SignatureAlgorithm::EdDSA => "EdDSA",
+            SignatureAlgorithm::ES256 => "ES256",
+            SignatureAlgorithm::ES256K => "ES256K",
+        }
+    }
+
+    /// Returns the algorithm named in a JOSE `alg` header.
+    pub fn from_name(name: &str) -> Result<Self, DelegationError> {
+        match name {
+            "EdDSA" => Ok(SignatureAlgorithm::EdDSA),
+            "ES256" => Ok(SignatureAlgorithm::ES256),
+            "ES256K" => Ok(SignatureAlgorithm::ES256K),
+            _ => Err(DelegationError::AlgorithmNotAllowed { alg: name.to_string() }),
+        }
+    }
+
+    /// Returns the algorithm a key is meant for, based on its key type and curve.
+    pub fn for_jwk(jwk: &Jwk) -> Result<Self, DelegationError> {
+        match (jwk.key_type(), jwk.curve()) {
+            ("OKP", Some("Ed25519")) => Ok(SignatureAlgorithm::EdDSA),
+            ("EC", Some("P-256")) => Ok(SignatureAlgorithm::ES256),
+            ("EC", Some("secp256k1")) => Ok(SignatureAlgorithm::ES256K),
+            (kty, crv) => Err(DelegationError::UnsupportedKey { kty: kty.to_string(), crv: crv.map(str::to_string) }),
+        }
+    }
+
+    /// Generates a signing key for the algorithm.
+    ///
+    /// # Arguments
+    /// * `id` - Id of the entity owning the key, used to derive the key id.
+    ///
+    /// # Returns
+    /// A result wrapping the private and the public key, or the error if it occurs.
+    pub fn generate_key(&self, id: &str) -> Result<(Jwk, Jwk), DelegationError> {
+        let (mut private_jwk, mut public_jwk) = match self {
+            SignatureAlgorithm::EdDSA => Self::generate_ed25519_key()?,
+            SignatureAlgorithm::ES256 => match ES256.generate_key_pair() {
+                Ok(key_pair) => (key_pair.to_jwk_private_key(), key_pair.to_jwk_public_key()),
+                Err(e) => { return Err(DelegationError::Jose { context: "Failed to generate P-256 signing key", source: e }); }
+            },
+            SignatureAlgorithm::ES256K => match ES256K.generate_key_pair() {
+                Ok(key_pair) => (key_pair.to_jwk_private_key(), key_pair.to_jwk_public_key()),
+                Err(e) => { return Err(DelegationError::Jose { context: "Failed to generate secp256k1 signing key", source: e }); }
+            },
+        };
+
+        // Every key gets its own id, so that signatures made with a rotated key are told apart
+        let key_id = format!("{id}#{}", random_nonce());
+        private_jwk.set_key_id(key_id.clone());
+        public_jwk.set_key_id(key_id);
+
+        Ok((private_jwk, public_jwk))
+    }
+
+    fn generate_ed25519_key() -> Result<(Jwk, Jwk), DelegationError> {
+        let mut rng: StdRng = StdRng::from_entropy();
+        let mut sk: SecretKey = [0u8; 32];
+        rng.fill_bytes(&mut sk);
+        let signing_key = SigningKey::from_bytes(&sk);
+        let public_key_bytes = signing_key.verifying_key().to_bytes();
+        let private_key_bytes = signing_key.to_bytes();
+
+        let mut signature_jwk = Jwk::new("OKP");
+        match signature_jwk.set_parameter("crv", Some(Value::String(String::from("Ed25519")))) {
+            Ok(()) => {},
+            Err(e) => { return Err(DelegationError::Jose { context: "Failed to set parameter crv for signing key", source: e }); }
+        };
+        match signature_jwk.set_parameter("x", Some(Value::String(Base64Url.encode(public_key_bytes)))) {
+            Ok(()) => {},
+            Err(e) => { return Err(DelegationError::Jose { context: "Failed to set parameter x for signing key", source: e }); }
+        };
+
+        // Take the public key for verification before adding the private parameter d
+        let public_signature_jwk = signature_jwk.clone();
+
+        match signature_jwk.set_parameter("d", Some(Value::String(Base64Url.encode(private_key_bytes)))) {
+            Ok(()) => {},
+            Err(e) => { return Err(DelegationError::Jose { context: "Failed to set parameter d for signing key", source: e }); }
+        };
+
+        Ok((signature_jwk, public_signature_jwk))
+    }
+
+    pub fn signer_from_jwk(&self, jwk: &Jwk) -> Result<Box<dyn JwsSigner>, DelegationError> {
+        let signer: Result<Box<dyn JwsSigner>, _> = match self {
+            SignatureAlgorithm::EdDSA => EdDSA.signer_from_jwk(jwk).map(|signer| Box::new(signer) as Box<dyn JwsSigner>),
+            SignatureAlgorithm::ES256 => ES256.signer_from_jwk(jwk).map(|signer| Box::new(signer) as Box<dyn JwsSigner>),
+            SignatureAlgorithm::ES256K => ES256K.signer_from_jwk(jwk).map(|signer| Box::new(signer) as Box<dyn JwsSigner>),
+        };
+        match signer {
+            Ok(signer) => Ok(signer),
+            Err(e) => Err(DelegationError::Jose { context: "Failed to create signer", source: e }),
+        }
+    }
+
+    pub fn verifier_from_jwk(&self, jwk: &Jwk) -> Result<Box<dyn JwsVerifier>, DelegationError> {
+        let verifier: Result<Box<dyn JwsVerifier>, _> = match self {
+            SignatureAlgorithm::EdDSA => EdDSA.verifier_from_jwk(jwk).map(|verifier| Box::new(verifier) as Box<dyn JwsVerifier>),
+            SignatureAlgorithm::ES256 => ES256.verifier_from_jwk(jwk).map(|verifier| Box::new(verifier) as Box<dyn JwsVerifier>),
+            SignatureAlgorithm::ES256K => ES256K.verifier_from_jwk(jwk).map(|verifier| Box::new(verifier) as Box<dyn JwsVerifier>),
+        };
+        match verifier {
+            Ok(verifier) => Ok(verifier),
+            Err(e) => Err(DelegationError::Jose { context: "Could not create verifier", source: e }),
+        }
+    }
+
+    /// Returns the algorithm of a key, failing if it is not in the allow-list.
+    pub fn allowed_for_jwk(jwk: &Jwk, allowed_algorithms: &[SignatureAlgorithm]) -> Result<Self, DelegationError> {
+        let algorithm = Self::for_jwk(jwk)?;
+        if !allowed_algorithms.contains(&algorithm) {
+            return Err(DelegationError::AlgorithmNotAllowed { alg: algorithm.name().to_string() });
+        }
+        Ok(algorithm)
+    }
+}
+
+impl Display for SignatureAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
@@ -14,14 +14,16 @@ pub struct VerifiableCredential<C: Credential> {
     issuer: String,
     #[serde(rename = "validFrom")]
     valid_from: String,
+    #[serde(rename = "validUntil", default, skip_serializing_if = "Option::is_none")]
+    valid_until: Option<String>,
     #[serde(rename = "credentialSubject")]
     credential: C,
 }
 
 impl<C: Credential> VerifiableCredential<C> {
-    pub fn new(context: Vec<String>, id: String, issuer: String, valid_from: String, credential: C) -> VerifiableCredential<C> {
+    pub fn new(context: Vec<String>, id: String, issuer: String, valid_from: String, valid_until: Option<String>, credential: C) -> VerifiableCredential<C> {
         let credential_type = vec![ credential.credential_type().to_string() ];
-        VerifiableCredential { context, credential_type, id, issuer, valid_from, credential }
+        VerifiableCredential { context, credential_type, id, issuer, valid_from, valid_until, credential }
     }
 
     pub fn context(&self) -> &Vec<String> { &self.context }
@@ -34,7 +36,11 @@ impl<C: Credential> VerifiableCredential<C> {
 
     pub fn valid_from(&self) -> &String { &self.valid_from }
 
+    pub fn valid_until(&self) -> Option<&String> { self.valid_until.as_ref() }
+
     pub fn credential(&self) -> &C { &self.credential }
+
+    pub fn mut_credential(&mut self) -> &mut C { &mut self.credential }
 }
 
 impl<C: Credential> Display for VerifiableCredential<C> {
@@ -42,10 +48,7 @@ impl<C: Credential> Display for VerifiableCredential<C> {
 
         match serde_json::to_string(self) {
             Ok(result) => write!(f, "{}", result),
-            Err(e) => {
-                eprintln!("Verifiable Credential serialization failed: {}", e);
-                Err(std::fmt::Error)
-            }
+            Err(_) => Err(std::fmt::Error),
         }
     }
 }
@@ -57,7 +60,7 @@ mod tests {
     use crate::delegation::credentials::ours::our_delegation_credential::OurDelegationCredential;
 
     #[test]
-    fn parse_dc() -> Result<(), String> {
+    fn parse_dc() -> Result<(), Box<dyn std::error::Error>> {
 
         let vcs: Vec<&str> = vec![RAW_VC_D1, RAW_VC_D2, RAW_VC_D3, RAW_VC_D4];
         let names: Vec<&str> = vec!["D1", "D2", "D3", "D4"];
@@ -65,17 +68,17 @@ mod tests {
         for (name, vc) in names.iter().zip(vcs.iter()) {
             let value_raw_vc: Value = match serde_json::from_str::<Value>(vc) {
                 Ok(value_raw_vc) => { value_raw_vc }
-                Err(err) => { return Err(format!("Failed to parse [{name}] Raw Verifiable Credential from string. [{err}]")); }
+                Err(err) => { return Err(format!("Failed to parse [{name}] Raw Verifiable Credential from string. [{err}]").into()); }
             };
 
             let raw_vc = match serde_json::from_value::<Map<String, Value>>(value_raw_vc) {
                 Ok(raw_vc) => raw_vc,
-                Err(err) => { return Err(format!("Failed to parse [{name}] Raw Verifiable Credential from Value. [{err}]")); }
+                Err(err) => { return Err(format!("Failed to parse [{name}] Raw Verifiable Credential from Value. [{err}]").into()); }
             };
 
             let vc = match serde_json::from_value::<VerifiableCredential<OurDelegationCredential>>(Value::Object(raw_vc)) {
                 Ok(vc) => vc,
-                Err(err) => { return Err(format!("Error in serialization of vc: [{err}]"))}
+                Err(err) => { return Err(format!("Error in serialization of vc: [{err}]").into())}
             };
 
             println!("Parsed VC [{name}]: {vc}");
@@ -93,13 +96,13 @@ mod tests {
     "validFrom": "2010-01-01T00:00:00Z",
 
     "credentialSubject": {
-        "delegatee_id": "https://vc.example/delegators/d1",
-        "accumulator_value": "accumulator_value_d1",
+        "sub": "https://vc.example/delegators/d1",
+        "av": "accumulator_value_d1",
         "iat": "0000000001",
         "exp": "1000000000",
-        "permissions": [ "https://vc.example/resources/r1:p0", "https://vc.example/resources/r1:p1", "https://vc.example/resources/r1:p2" ],
-        "metadata_witnesses": [ "w_delegatee_id_d1", "w_iat_d1", "w_exp_d1" ],
-        "permission_witnesses": [ "w0d1", "w1d1", "w2d1" ],
+        "per": [ "https://vc.example/resources/r1:p0", "https://vc.example/resources/r1:p1", "https://vc.example/resources/r1:p2" ],
+        "mw": [ "w_delegatee_id_d1", "w_iat_d1", "w_exp_d1" ],
+        "pw": [ "w0d1", "w1d1", "w2d1" ],
         "hierarchy": []
     }
 }"#;
@@ -112,22 +115,22 @@ mod tests {
     "validFrom": "2010-01-01T00:00:00Z",
 
     "credentialSubject": {
-        "delegatee_id": "https://vc.example/delegators/d2",
-        "accumulator_value": "accumulator_value_d2",
+        "sub": "https://vc.example/delegators/d2",
+        "av": "accumulator_value_d2",
         "iat": "0000000002",
         "exp": "1000000000",
-        "permissions": [ "https://vc.example/resources/r1:p0", "https://vc.example/resources/r1:p1" ],
-        "metadata_witnesses": [ "w_delegatee_id_d2", "w_iat_d2", "w_exp_d2" ],
-        "permission_witnesses": [ "w0d2", "w1d2" ],
+        "per": [ "https://vc.example/resources/r1:p0", "https://vc.example/resources/r1:p1" ],
+        "mw": [ "w_delegatee_id_d2", "w_iat_d2", "w_exp_d2" ],
+        "pw": [ "w0d2", "w1d2" ],
         "hierarchy": [
             {
                 "id": "https://vc.example/delegators/d0",
-                "delegatee_id": "https://vc.example/delegators/d1",
+                "sub": "https://vc.example/delegators/d1",
                 "iat": "0000000001",
                 "exp": "1000000000",
-                "accumulator_value": "accumulator_value_d1",
-                "metadata_witnesses": [ "w_delegatee_id_d1", "w_iat_d1", "w_exp_d1" ],
-                "permission_witnesses": [ "w0d1", "w1d1" ]
+                "av": "accumulator_value_d1",
+                "mw": [ "w_delegatee_id_d1", "w_iat_d1", "w_exp_d1" ],
+                "pw": [ "w0d1", "w1d1" ]
             }
         ]
     }
@@ -141,31 +144,31 @@ mod tests {
     "validFrom": "2010-01-01T00:00:00Z",
 
     "credentialSubject": {
-        "delegatee_id": "https://vc.example/delegators/d3",
-        "accumulator_value": "accumulator_value_d3",
+        "sub": "https://vc.example/delegators/d3",
+        "av": "accumulator_value_d3",
         "iat": "0000000003",
         "exp": "1000000000",
-        "permissions": [ "https://vc.example/resources/r1:p0", "https://vc.example/resources/r1:p1" ],
-        "metadata_witnesses": [ "w_delegatee_id_d3", "w_iat_d3", "w_exp_d3" ],
-        "permission_witnesses": [ "w0d3", "w1d3" ],
+        "per": [ "https://vc.example/resources/r1:p0", "https://vc.example/resources/r1:p1" ],
+        "mw": [ "w_delegatee_id_d3", "w_iat_d3", "w_exp_d3" ],
+        "pw": [ "w0d3", "w1d3" ],
         "hierarchy": [
             {
                 "id": "https://vc.example/delegators/d0",
-                "delegatee_id": "https://vc.example/delegators/d1",
+                "sub": "https://vc.example/delegators/d1",
                 "iat": "0000000001",
                 "exp": "1000000000",
-                "accumulator_value": "accumulator_value_d1",
-                "metadata_witnesses": [ "w_delegatee_id_d1", "w_iat_d1", "w_exp_d1" ],
-                "permission_witnesses": [ "w0d1", "w1d1" ]
+                "av": "accumulator_value_d1",
+                "mw": [ "w_delegatee_id_d1", "w_iat_d1", "w_exp_d1" ],
+                "pw": [ "w0d1", "w1d1" ]
             },
             {
                 "id": "https://vc.example/delegators/d1",
-                "delegatee_id": "https://vc.example/delegators/d2",
+                "sub": "https://vc.example/delegators/d2",
                 "iat": "0000000002",
                 "exp": "1000000000",
-                "accumulator_value": "accumulator_value_d2",
-                "metadata_witnesses": [ "w_delegatee_id_d2", "w_iat_d2", "w_exp_d2" ],
-                "permission_witnesses": [ "w0d2", "w1d2" ]
+                "av": "accumulator_value_d2",
+                "mw": [ "w_delegatee_id_d2", "w_iat_d2", "w_exp_d2" ],
+                "pw": [ "w0d2", "w1d2" ]
             }
         ]
     }
@@ -180,40 +183,40 @@ mod tests {
     "validFrom": "2010-01-01T00:00:00Z",
 
     "credentialSubject": {
-        "delegatee_id": "https://vc.example/delegators/d4",
-        "accumulator_value": "accumulator_value_d4",
+        "sub": "https://vc.example/delegators/d4",
+        "av": "accumulator_value_d4",
         "iat": "0000000004",
         "exp": "1000000000",
-        "permissions": [ "https://vc.example/resources/r1:p0" ],
-        "metadata_witnesses": [ "w_delegatee_id_d4", "w_iat_d4", "w_exp_d4" ],
-        "permission_witnesses": [ "w0d4" ],
+        "per": [ "https://vc.example/resources/r1:p0" ],
+        "mw": [ "w_delegatee_id_d4", "w_iat_d4", "w_exp_d4" ],
+        "pw": [ "w0d4" ],
         "hierarchy": [
             {
                 "id": "https://vc.example/delegators/d0",
-                "delegatee_id": "https://vc.example/delegators/d1",
+                "sub": "https://vc.example/delegators/d1",
                 "iat": "0000000001",
                 "exp": "1000000000",
-                "accumulator_value": "accumulator_value_d1",
-                "metadata_witnesses": [ "w_delegatee_id_d1", "w_iat_d1", "w_exp_d1" ],
-                "permission_witnesses": [ "w0d1" ]
+                "av": "accumulator_value_d1",
+                "mw": [ "w_delegatee_id_d1", "w_iat_d1", "w_exp_d1" ],
+                "pw": [ "w0d1" ]
             },
             {
                 "id": "https://vc.example/delegators/d1",
-                "delegatee_id": "https://vc.example/delegators/d2",
+                "sub": "https://vc.example/delegators/d2",
                 "iat": "0000000002",
                 "exp": "1000000000",
-                "accumulator_value": "accumulator_value_d2",
-                "metadata_witnesses": [ "w_delegatee_id_d2", "w_iat_d2", "w_exp_d2" ],
-                "permission_witnesses": [ "w0d2" ]
+                "av": "accumulator_value_d2",
+                "mw": [ "w_delegatee_id_d2", "w_iat_d2", "w_exp_d2" ],
+                "pw": [ "w0d2" ]
             },
             {
                 "id": "https://vc.example/delegators/d2",
-                "delegatee_id": "https://vc.example/delegators/d3",
+                "sub": "https://vc.example/delegators/d3",
                 "iat": "0000000003",
                 "exp": "1000000000",
-                "accumulator_value": "accumulator_value_d3",
-                "metadata_witnesses": [ "w_delegatee_id_d3", "w_iat_d3", "w_exp_d3" ],
-                "permission_witnesses": [ "w0d3" ]
+                "av": "accumulator_value_d3",
+                "mw": [ "w_delegatee_id_d3", "w_iat_d3", "w_exp_d3" ],
+                "pw": [ "w0d3" ]
             }
         ]
     }
@@ -1,13 +1,17 @@
+use crate::delegation::credentials::presentation_request::{random_nonce, PresentationRequest};
+use crate::delegation::credentials::signature_algorithm::SignatureAlgorithm;
 use crate::delegation::credentials::verifiable_credential::VerifiableCredential;
 use crate::delegation::traits::credential::Credential;
 use josekit::jwk::Jwk;
-use josekit::jws::{EdDSA, JwsHeader};
+use josekit::jws::JwsHeader;
 use josekit::jwt;
 use josekit::jwt::JwtPayload;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::fmt::Display;
+use std::time::{Duration, UNIX_EPOCH};
+use crate::delegation::error::DelegationError;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct VerifiablePresentation<C: Credential> {
@@ -21,16 +25,23 @@ pub struct VerifiablePresentation<C: Credential> {
     issuer: String,
     #[serde(rename = "validFrom")]
     valid_from: String,
+    #[serde(rename = "validUntil", default, skip_serializing_if = "Option::is_none")]
+    valid_until: Option<String>,
     #[serde(rename = "credentialSubject")]
     credential: C,
+    // Presentation request of the verifier, for presentations bound to their holder
+    #[serde(rename = "nonce", default, skip_serializing_if = "Option::is_none")]
+    nonce: Option<String>,
+    #[serde(rename = "aud", default, skip_serializing_if = "Option::is_none")]
+    audience: Option<String>,
 }
 
 impl <C: Credential> VerifiablePresentation<C> {
 
     pub fn new(context: Vec<String>, credential_type: Vec<String>, id: String, issuer: String,
-               valid_from: String, credential: C) -> Self {
+               valid_from: String, valid_until: Option<String>, credential: C) -> Self {
 
-        VerifiablePresentation { context, credential_type, id, issuer, valid_from, credential }
+        VerifiablePresentation { context, credential_type, id, issuer, valid_from, valid_until, credential, nonce: None, audience: None }
     }
 
     pub fn context(&self) -> &Vec<String> { &self.context }
@@ -38,17 +49,41 @@ impl <C: Credential> VerifiablePresentation<C> {
     pub fn id(&self) -> &String { &self.id }
     pub fn issuer(&self) -> &String { &self.issuer }
     pub fn valid_from(&self) -> &String { &self.valid_from }
+    pub fn valid_until(&self) -> Option<&String> { self.valid_until.as_ref() }
     pub fn credential(&self) -> &C { &self.credential }
+    pub fn nonce(&self) -> Option<&String> { self.nonce.as_ref() }
+    pub fn audience(&self) -> Option<&String> { self.audience.as_ref() }
 
     pub fn mut_credential(&mut self) -> &mut C { &mut self.credential }
 
-    pub fn from_verifiable_credential(vc: VerifiableCredential<C>, claims_to_keep: Vec<String>) -> Result<Self, String> {
+    /// Binds the presentation to the request of the verifier it is presented to.
+    pub fn set_request(&mut self, request: &PresentationRequest) {
+        self.nonce = Some(request.nonce().clone());
+        self.audience = Some(request.audience().clone());
+    }
+
+    /// Checks that the presentation answers the request of the verifier.
+    ///
+    /// # Arguments
+    /// * `request` - Presentation request previously sent to the holder.
+    ///
+    /// # Returns
+    /// The result of the check or the error, if the nonce or the audience do not match.
+    pub fn verify_request(&self, request: &PresentationRequest) -> Result<(), DelegationError> {
+        if self.nonce.as_ref() != Some(request.nonce()) || self.audience.as_ref() != Some(request.audience()) {
+            return Err(DelegationError::RequestMismatch { nonce: self.nonce.clone(), audience: self.audience.clone() });
+        }
+        Ok(())
+    }
+
+    pub fn from_verifiable_credential(vc: VerifiableCredential<C>, claims_to_keep: Vec<C::Claim>) -> Result<Self, DelegationError> {
         let mut vc = VerifiablePresentation::new(
             vc.context().clone(),
             vc.credential_type().clone(),
             vc.id().clone(),
             vc.issuer().clone(),
             vc.valid_from().clone(),
+            vc.valid_until().cloned(),
             vc.credential().clone(),
         );
 
@@ -57,60 +92,88 @@ impl <C: Credential> VerifiablePresentation<C> {
         // TODO: check for no removal using the result?
 
         match vc.credential.is_empty() {
-            true => Err(String::from("VerifiablePresentation is empty")),
+            true => Err(DelegationError::EmptyPresentation),
             false => Ok(vc),
         }
     }
 
-    pub fn from_signed_jwt<CC: Credential + DeserializeOwned>(jwt: String, public_key: &Jwk) -> Result<VerifiablePresentation<CC>, String> {
-
-        let verifier = match EdDSA.verifier_from_jwk(public_key) {
-            Ok(verifier) => { verifier}
-            Err(err) => { return Err(format!("Could not create verifier [{}]", err.to_string())) }
-        };
-
-        let (payload, _) = match jwt::decode_with_verifier(jwt, &verifier) {
+    /// Verifies the signature of a presentation and decodes it.
+    ///
+    /// # Arguments
+    /// * `jwt` - Signed presentation.
+    /// * `public_key` - Public key of the presenter, which determines the signature algorithm.
+    /// * `allowed_algorithms` - Signature algorithms accepted by the verifier.
+    ///
+    /// # Returns
+    /// A result wrapping the presentation or the error, if the signature is not valid.
+    pub fn from_signed_jwt<CC: Credential + DeserializeOwned>(jwt: String, public_key: &Jwk, allowed_algorithms: &[SignatureAlgorithm]) -> Result<VerifiablePresentation<CC>, DelegationError> {
+
+        let algorithm = SignatureAlgorithm::allowed_for_jwk(public_key, allowed_algorithms)?;
+        let verifier = algorithm.verifier_from_jwk(public_key)?;
+
+        // The alg header must match the algorithm of the verifier
+        let (payload, header) = match jwt::decode_with_verifier(jwt, verifier.as_ref()) {
             Ok((payload, header)) => { (payload, header) }
-            Err(err) => { return Err(format!("Failed to decode and verify jwt [{}]", err.to_string())) }
+            Err(err) => { return Err(DelegationError::Jose { context: "Failed to decode and verify jwt", source: err }) }
         };
 
+        if let (Some(header_kid), Some(key_kid)) = (header.key_id(), public_key.key_id()) && header_kid != key_kid {
+            return Err(DelegationError::KeyIdMismatch { header: header_kid.to_string(), key: key_kid.to_string() });
+        }
+
         let vp_map = Value::Object(payload.claims_set().clone());
 
         match serde_json::from_value(vp_map) {
             Ok(vp) => { Ok(vp) }
-            Err(err) => { Err(format!("Could not deserialize VerifiablePresentation [{}]", err.to_string())) }
+            Err(err) => { Err(DelegationError::Json { context: "Could not deserialize VerifiablePresentation", source: err }) }
         }
 
     }
 
-    pub fn to_signed_jwt(&self, private_key: &Jwk) -> Result<String, String> {
+    /// Signs the presentation as a JWT.
+    ///
+    /// # Arguments
+    /// * `private_key` - Private key of the presenter, which determines the signature algorithm.
+    /// * `issued_at` - Issuance time of the JWT, in nanoseconds since the Unix epoch, as given by
+    ///   the clock of the presenter.
+    ///
+    /// # Returns
+    /// A result wrapping the signed presentation or the error, if it occurs.
+    pub fn to_signed_jwt(&self, private_key: &Jwk, issued_at: u128) -> Result<String, DelegationError> {
 
         let map_value = match serde_json::to_value(self) {
             Ok(map_value) => map_value,
-            Err(err) => { return Err(format!("Failed to encode VerifiablePresentation to a value {err}")) }
+            Err(err) => { return Err(DelegationError::Json { context: "Failed to encode VerifiablePresentation to a value", source: err }) }
         };
 
         let map = match map_value {
             Value::Object(map) => map,
-            _ => { return Err(String::from("VerifiablePresentation is not an object")); }
+            _ => { return Err(DelegationError::NotAnObject); }
         };
 
+        let algorithm = SignatureAlgorithm::for_jwk(private_key)?;
         let mut header: JwsHeader = JwsHeader::new();
-        header.set_algorithm("P256");
+        header.set_algorithm(algorithm.name());
+        if let Some(key_id) = private_key.key_id() {
+            header.set_key_id(key_id);
+        }
 
-        let payload: JwtPayload = match JwtPayload::from_map(map) {
+        let mut payload: JwtPayload = match JwtPayload::from_map(map) {
             Ok(payload) => { payload }
-            Err(err) => { return Err(format!("Failed to encode payload from map: [{err}]")); }
+            Err(err) => { return Err(DelegationError::Jose { context: "Failed to encode payload from map", source: err }); }
         };
-
-        let signer = match EdDSA.signer_from_jwk(private_key) {
-            Ok(signer) => { signer }
-            Err(err) => { return Err(format!("Failed to create signer: [{err}]"));}
+        let issued_at = match u64::try_from(issued_at) {
+            Ok(issued_at) => UNIX_EPOCH + Duration::from_nanos(issued_at),
+            Err(_) => { return Err(DelegationError::TimestampOutOfRange { timestamp: issued_at }); }
         };
+        payload.set_issued_at(&issued_at);
+        payload.set_jwt_id(random_nonce());
+
+        let signer = algorithm.signer_from_jwk(private_key)?;
 
-        let jwt = match jwt::encode_with_signer(&payload, &header, &signer) {
+        let jwt = match jwt::encode_with_signer(&payload, &header, signer.as_ref()) {
             Ok(jwt) => { jwt }
-            Err(err) => { return Err(format!("Failed to encode and sign jwt: [{err}]")); }
+            Err(err) => { return Err(DelegationError::Jose { context: "Failed to encode and sign jwt", source: err }); }
         };
 
         Ok(jwt)
@@ -121,10 +184,7 @@ impl <C: Credential> Display for VerifiablePresentation<C> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match serde_json::to_string(self) {
             Ok(result) => write!(f, "{}", result),
-            Err(e) => {
-                eprintln!("Verifiable Presentation serialization failed: {}", e);
-                Err(std::fmt::Error)
-            }
+            Err(_) => Err(std::fmt::Error),
         }
     }
 }
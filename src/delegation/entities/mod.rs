@@ -1,4 +1,3 @@
 pub mod ours;
 pub mod pjv;
-pub mod verifier;
-pub mod dtl_sim;
\ No newline at end of file
+pub mod verifier;
\ No newline at end of file
@@ -1,3 +1,4 @@
-pub mod dlt_acc_entry;
+pub mod our_holder;
 pub mod our_issuer;
+pub mod our_scheme;
 pub mod our_verifier;
\ No newline at end of file
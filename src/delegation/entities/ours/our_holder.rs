@@ -0,0 +1,192 @@
+use crate::delegation::accumulators::witness_updater::WitnessUpdater;
+use crate::delegation::credentials::ours::our_delegation::OurDelegation;
+use crate::delegation::credentials::ours::our_delegation_credential::OurDelegationCredential;
+use crate::delegation::credentials::permission::Permission;
+use crate::delegation::credentials::verifiable_credential::VerifiableCredential;
+use crate::delegation::error::DelegationError;
+use crate::delegation::registry::verifiable_data_registry::VerifiableDataRegistry;
+use crate::delegation::traits::credential::Credential;
+use ark_ec::pairing::Pairing;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// Holder of delegation credentials. It keeps the witnesses of its credentials up to date with the
+/// revocations published by the issuers in the registry.
+pub struct OurHolder<E: Pairing> {
+    registry: Arc<dyn VerifiableDataRegistry>,
+    phantom: PhantomData<E>,
+}
+
+impl<E: Pairing> OurHolder<E> {
+    pub fn new(registry: Arc<dyn VerifiableDataRegistry>) -> Self {
+        OurHolder { registry, phantom: PhantomData }
+    }
+
+    // Collects the updates published after the accumulator value contained in the delegation
+    fn witness_updater<D: OurDelegation>(&self, issuer: &str, delegation: &D) -> Result<(WitnessUpdater<E>, String), DelegationError> {
+        let revocation_key = delegation.revocation_key(issuer);
+
+        let entry = match self.registry.resolve_accumulator_value(&revocation_key)? {
+            None => { return Err(DelegationError::DelegationNotPublished { key: revocation_key }) }
+            Some(entry) => { entry }
+        };
+
+        let updates = entry.updates_since(delegation.accumulator_value())?;
+        Ok((WitnessUpdater::new(updates)?, entry.accumulator_value.clone()))
+    }
+
+    fn update_delegation<D: OurDelegation>(issuer: &str, delegation: &mut D, updater: &WitnessUpdater<E>, accumulator_value: String, permissions: &[Permission]) -> Result<(), DelegationError> {
+        let metadata = delegation.metadata_elements(issuer);
+
+        if metadata.iter().any(|element| updater.is_removed(element)) {
+            return Err(DelegationError::Revoked { key: delegation.revocation_key(issuer) });
+        }
+
+        let metadata_witnesses = updater.update_witnesses(delegation.metadata_witnesses(), &metadata)?;
+        let permission_witnesses = updater.update_witnesses(delegation.permission_witnesses(), &delegation.permission_elements(issuer, permissions))?;
+
+        delegation.set_accumulator(accumulator_value, metadata_witnesses, permission_witnesses);
+        Ok(())
+    }
+
+    /// Updates the accumulator values and witnesses of a delegation credential and of every
+    /// delegator in its hierarchy using the public information published by the issuers. Permissions
+    /// revoked anywhere in the hierarchy are removed from the credential.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable credential whose witnesses need to be updated.
+    ///
+    /// # Returns
+    /// A result wrapping the revoked permissions that were removed or the error, if it occurs.
+    pub fn update_witnesses(&self, vc: &mut VerifiableCredential<OurDelegationCredential>) -> Result<Vec<Permission>, DelegationError> {
+        let issuer = vc.issuer().clone();
+        let dc = vc.mut_credential();
+
+        let leaf_index = dc.hierarchy().len();
+        let mut updaters: Vec<(WitnessUpdater<E>, String)> = vec![];
+        for (index, delegator) in dc.hierarchy().iter().enumerate() {
+            updaters.push(self.witness_updater(delegator.id(), delegator).map_err(|e| e.at_delegator(index))?);
+        }
+        let (updater, accumulator_value) = self.witness_updater(&issuer, dc).map_err(|e| e.at_delegator(leaf_index))?;
+
+        let is_removed = |delegation: &dyn OurDelegation, issuer: &str, updater: &WitnessUpdater<E>, permission: &Permission| {
+            updater.is_removed(&delegation.permission_elements(issuer, std::slice::from_ref(permission))[0])
+        };
+
+        // Broader permissions proven by the delegators survive if they were neither removed from
+        // their accumulator nor lost the grant of the level above
+        let mut granted: Option<Vec<Permission>> = None;
+        let mut surviving: Vec<Option<Vec<Permission>>> = vec![];
+        for (delegator, (u, _)) in dc.hierarchy().iter().zip(updaters.iter()) {
+            surviving.push(delegator.permissions().map(|proven| {
+                let kept: Vec<Permission> = proven.iter()
+                    .filter(|permission| !is_removed(delegator, delegator.id(), u, permission))
+                    .filter(|permission| granted.as_ref().is_none_or(|granted| granted.iter().any(|granted| granted.implies(permission))))
+                    .cloned()
+                    .collect();
+                granted = Some(kept.clone());
+                kept
+            }));
+        }
+
+        // A permission removed from any accumulator in the chain can no longer be presented
+        let is_revoked = |permission: &Permission| {
+            is_removed(dc, &issuer, &updater, permission) ||
+                dc.hierarchy().iter().zip(updaters.iter())
+                    .filter(|(delegator, _)| delegator.permissions().is_none())
+                    .any(|(delegator, (u, _))| is_removed(delegator, delegator.id(), u, permission)) ||
+                granted.as_ref().is_some_and(|granted| !granted.iter().any(|granted| granted.implies(permission)))
+        };
+        let (revoked_permissions, permissions): (Vec<Permission>, Vec<Permission>) = dc.permissions().iter().cloned()
+            .partition(is_revoked);
+
+        for (delegator, kept) in dc.mut_hierarchy().iter_mut().zip(surviving) {
+            if let Some(kept) = kept {
+                delegator.retain_permissions(|permission| kept.contains(permission));
+            }
+        }
+        if !revoked_permissions.is_empty() {
+            dc.retain_only(permissions.clone())?;
+            if dc.is_empty() {
+                return Err(DelegationError::AllPermissionsRevoked);
+            }
+        }
+
+        for (index, (delegator, (delegator_updater, delegator_accumulator_value))) in dc.mut_hierarchy().iter_mut().zip(updaters).enumerate() {
+            let delegator_issuer = delegator.id().clone();
+            let proven_permissions = delegator.proven_permissions(&permissions).to_vec();
+            Self::update_delegation(&delegator_issuer, delegator, &delegator_updater, delegator_accumulator_value, &proven_permissions)
+                .map_err(|e| e.at_delegator(index))?;
+        }
+        Self::update_delegation(&issuer, dc, &updater, accumulator_value, &permissions)
+            .map_err(|e| e.at_delegator(leaf_index))?;
+
+        Ok(revoked_permissions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Bn254;
+    use std::time::Duration;
+    use std::str::FromStr;
+    use crate::delegation::registry::in_memory_registry::InMemoryRegistry;
+    use crate::delegation::entities::ours::our_issuer::OurIssuer;
+    use crate::delegation::accumulators::verification_mode::VerificationMode;
+    use crate::delegation::entities::ours::our_verifier::OurVerifier;
+
+    #[test]
+    fn update_witnesses() -> Result<(), Box<dyn std::error::Error>> {
+        type Curve = Bn254;
+        let registry = InMemoryRegistry::shared();
+
+        let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
+        let valid_from = String::from("2026-01-01T00:00:00Z");
+        let validity_period: Duration = Duration::new(3600, 0);
+        let permissions: Vec<Permission> = vec![Permission::from_str("https://vc.example/resources/r1:p0")?, Permission::from_str("https://vc.example/resources/r1:p1")?, Permission::from_str("https://vc.example/resources/r1:p2")?];
+
+        let root: OurIssuer<Curve> = OurIssuer::new(String::from("https://vc.example/delegators/d0"), registry.clone())?;
+        let root_credential_id = String::from("http://delegation.example/credentials/1337");
+        let delegatee_id = String::from("https://vc.example/delegators/d1");
+        let root_vc = root.issue_delegation_verifiable_credential(context.clone(), root_credential_id.clone(), valid_from.clone(), delegatee_id.clone(), validity_period, permissions.clone(), None)?;
+
+        let delegator: OurIssuer<Curve> = OurIssuer::new(delegatee_id.clone(), registry.clone())?;
+        let credential_id = String::from("http://delegation.example/credentials/1338");
+        let mut vc = delegator.issue_delegation_verifiable_credential(context, credential_id.clone(), valid_from, String::from("https://vc.example/delegators/d2"), validity_period, permissions.clone(), Some(root_vc))?;
+
+        let holder: OurHolder<Curve> = OurHolder::new(registry.clone());
+        let verifier: OurVerifier<Curve> = OurVerifier::new(registry)?;
+
+        // Nothing has been revoked yet, so no permission is removed
+        if !holder.update_witnesses(&mut vc)?.is_empty() {
+            return Err(String::from("Permissions removed although nothing was revoked").into());
+        }
+
+        // Revoke one permission in the hierarchy and one in the leaf credential
+        root.revoke_permissions(&root_credential_id, &permissions[2..])?;
+        delegator.revoke_permissions(&credential_id, &permissions[1..2])?;
+
+        let stale_vp = delegator.issue_delegation_verifiable_presentation(vc.clone(), permissions[..1].to_vec())?;
+        for mode in [VerificationMode::Sequential, VerificationMode::Batched] {
+            if verifier.verify_verifiable_presentation(delegatee_id.clone(), stale_vp.clone(), mode).is_ok() {
+                return Err(format!("Presentation with stale witnesses verified in {mode:?} mode").into());
+            }
+        }
+
+        let revoked_permissions = holder.update_witnesses(&mut vc)?;
+        if revoked_permissions != permissions[1..] || *vc.credential().permissions() != permissions[..1] {
+            return Err(format!("Unexpected revoked permissions {revoked_permissions:?}").into());
+        }
+
+        let signed_vp = delegator.issue_delegation_verifiable_presentation(vc.clone(), permissions[..1].to_vec())?;
+        verifier.verify_verifiable_presentation(delegatee_id, signed_vp, VerificationMode::Sequential)?;
+
+        // Once the delegatee is revoked, witnesses can no longer be updated
+        delegator.revoke_delegatee(&credential_id)?;
+        match holder.update_witnesses(&mut vc) {
+            Err(DelegationError::Delegator { index: 1, source }) if matches!(*source, DelegationError::Revoked { .. }) => Ok(()),
+            other => Err(format!("Expected revocation of delegation 1, got {other:?}").into()),
+        }
+    }
+}
@@ -1,97 +1,182 @@
+use crate::delegation::accumulators::accumulator_element::{AccumulatorElement, ElementEncoding, ElementRole};
 use crate::delegation::accumulators::accumulator_manager::AccumulatorManager;
+use crate::delegation::accumulators::accumulator_update::AccumulatorUpdate;
 use crate::delegation::accumulators::accumulator_utils::AccumulatorUtils;
+use crate::delegation::accumulators::in_memory_state::InMemoryState;
+use crate::delegation::credentials::caveat::Caveat;
+use crate::delegation::credentials::presentation_request::PresentationRequest;
+use crate::delegation::credentials::signature_algorithm::SignatureAlgorithm;
 use crate::delegation::credentials::ours::our_delegation_credential::OurDelegationCredential;
 use crate::delegation::credentials::ours::our_delegator::OurDelegator;
+use crate::delegation::credentials::permission::Permission;
 use crate::delegation::credentials::verifiable_credential::VerifiableCredential;
 use crate::delegation::credentials::verifiable_presentation::VerifiablePresentation;
-use crate::delegation::entities::ours::dlt_acc_entry::DLTSimAccEntry;
+use crate::delegation::registry::accumulator_params_entry::AccumulatorParamsEntry;
+use crate::delegation::registry::accumulator_value_entry::AccumulatorValueEntry;
+use crate::delegation::registry::verifiable_data_registry::{KeyPurpose, VerifiableDataRegistry};
 use ark_ec::pairing::Pairing;
 use ark_std::rand::prelude::StdRng;
-use ark_std::rand::{RngCore, SeedableRng};
-use ed25519_dalek::{SecretKey, SigningKey};
+use ark_std::rand::SeedableRng;
 use josekit::jwk::Jwk;
-use multibase::Base::Base64Url;
-use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use std::str::FromStr;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use vb_accumulator::prelude::{Keypair, SetupParams};
+use std::time::Duration;
+use vb_accumulator::persistence::State;
+use vb_accumulator::prelude::{Keypair, PositiveAccumulator, SetupParams};
+use crate::delegation::clock::clock::Clock;
+use crate::delegation::clock::system_clock::SystemClock;
 use crate::delegation::credentials::ours::our_delegation::OurDelegation;
-use crate::delegation::entities::dtl_sim::DLTSim;
+use crate::delegation::entities::verifier::check_validity_period;
+use crate::delegation::error::DelegationError;
+use crate::delegation::utils::utils::format_date_time;
+
+/// Accumulator of an issued delegation credential, kept by the issuer to be able to revoke it.
+struct IssuedAccumulator<E: Pairing> {
+    revocation_key: String,
+    delegatee_id: String,
+    element_encoding: ElementEncoding,
+    accumulator: PositiveAccumulator<E>,
+    state: InMemoryState<E::ScalarField>,
+}
 
 pub struct OurIssuer<E: Pairing> {
     id: String,
     params: SetupParams<E>,
     acc_keypair: Keypair<E>,
     signature_jwk: Jwk,
+    registry: Arc<dyn VerifiableDataRegistry>,
+    element_encoding: ElementEncoding,
+    clock: Arc<dyn Clock>,
+    // Accumulators of the issued credentials, indexed by credential id
+    issued_accumulators: Mutex<HashMap<String, IssuedAccumulator<E>>>,
 }
 
 
 impl <E: Pairing> OurIssuer<E> {
 
-    pub fn new(id: String, accumulator_dlt: DLTSim<DLTSimAccEntry<E>>, verification_dlt: DLTSim<Jwk>) -> Result<Self, String> {
+    pub fn new(id: String, registry: Arc<dyn VerifiableDataRegistry>) -> Result<Self, DelegationError> {
+        Self::with_signature_algorithm(id, registry, SignatureAlgorithm::EdDSA)
+    }
+
+    /// Creates an issuer signing its presentations with the given algorithm.
+    ///
+    /// # Arguments
+    /// * `id` - Id of the issuer.
+    /// * `registry` - Registry the public material of the issuer is published to.
+    /// * `signature_algorithm` - Algorithm of the signing key.
+    ///
+    /// # Returns
+    /// A result wrapping the issuer or the error, if its keys cannot be generated or published.
+    pub fn with_signature_algorithm(id: String, registry: Arc<dyn VerifiableDataRegistry>, signature_algorithm: SignatureAlgorithm) -> Result<Self, DelegationError> {
 
         let mut rng: StdRng = StdRng::from_entropy();
         let params = SetupParams::<E>::generate_using_rng(&mut rng);
         let acc_keypair = Keypair::<E>::generate_using_rng(&mut rng, &params);
 
-        let entry = DLTSimAccEntry::new(acc_keypair.public_key.clone(), params.clone());
+        let entry = AccumulatorParamsEntry::new(acc_keypair.public_key.clone(), params.clone());
 
-        accumulator_dlt.borrow_mut().insert(id.clone(), entry);
+        registry.publish_accumulator_params(&id, entry.encode()?)?;
 
-        let mut sk: SecretKey = [0u8; 32];
-        // let signing_algorithm = String::from("EdDSA");
+        let (signature_jwk, public_signature_jwk) = signature_algorithm.generate_key(&id)?;
 
-        // =====================================================
-        // Ed25519 SIGNATURE - Public and Private Key generation
-        // =====================================================
-        rng.fill_bytes(&mut sk);
-        let signing_key = SigningKey::from_bytes(&sk);
-        let public_key_bytes = signing_key.verifying_key().to_bytes();
-        let private_key_bytes = signing_key.to_bytes();
+        // Take the public key for verification and put it in the registry
+        registry.publish_key(KeyPurpose::Verification, &id, public_signature_jwk)?;
 
-        let mut signature_jwk = Jwk::new("OKP");
-        match signature_jwk.set_parameter("crv", Some(Value::String(String::from("Ed25519")))) {
-            Ok(()) => {},
-            Err(e) => { return Err(format!("Failed to set parameter crv for signing key [{}]", e)); }
-        };
-        match signature_jwk.set_parameter("x", Some(Value::String(Base64Url.encode(public_key_bytes)))) {
-            Ok(()) => {},
-            Err(e) => { return Err(format!("Failed to set parameter x for signing key [{}]", e)); }
-        };
+        let issued_accumulators = Mutex::new(HashMap::new());
 
-        // Take the public key for verification and put it in the DLT
-        let public_signature_jwk = signature_jwk.clone();
-        verification_dlt.borrow_mut().insert(id.clone(), public_signature_jwk);
+        Ok(OurIssuer { id, params, acc_keypair, signature_jwk, registry, element_encoding: ElementEncoding::Typed, clock: SystemClock::shared(), issued_accumulators })
+    }
 
-        // Add the private parameter d to the jwk to enable the signing operation.
-        match signature_jwk.set_parameter("d", Some(Value::String(Base64Url.encode(private_key_bytes)))) {
-            Ok(()) => {},
-            Err(e) => { return Err(format!("Failed to set parameter d for signing key [{}]", e)); }
-        };
+    pub fn id(&self) -> &String {
+        &self.id
+    }
 
-        Ok(OurIssuer { id, params, acc_keypair, signature_jwk })
+    /// Sets the encoding of the elements accumulated in the credentials issued from now on. The
+    /// typed encoding is used by default, the legacy one only serves to interoperate with
+    /// verifiers and holders that predate it.
+    pub fn set_element_encoding(&mut self, element_encoding: ElementEncoding) {
+        self.element_encoding = element_encoding;
+    }
+
+    /// Sets the clock giving the issuance time of the credentials. The system clock is used by
+    /// default.
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
     }
 
     // Validity_period refers to a short-lived credential: since its issuance moment, the delegation
     // credential could be valid for a month, a week, a day, or anything really.
+    #[allow(clippy::too_many_arguments)]
     pub fn issue_delegation_verifiable_credential(&self, context: Vec<String>, credential_id: String,
                                                   valid_from: String, delegatee_id: String,
-                                                  validity_period: Duration, permissions: Vec<String>,
+                                                  validity_period: Duration, permissions: Vec<Permission>,
                                                   optional_issuer_vc: Option<VerifiableCredential<OurDelegationCredential>>)
-        -> Result<VerifiableCredential<OurDelegationCredential>, String> {
+        -> Result<VerifiableCredential<OurDelegationCredential>, DelegationError> {
+        self.issue_delegation_verifiable_credential_with_max_depth(context, credential_id, valid_from, delegatee_id,
+                                                                   validity_period, permissions, optional_issuer_vc, None)
+    }
+
+    /// Issues a delegation credential that limits how many times it may be delegated further. The
+    /// limit can only be lowered along the chain: the one of the issued credential is also bounded
+    /// by the one of the credential of the issuer.
+    ///
+    /// # Arguments
+    /// * `max_depth` - Maximum number of delegations that may follow the issued one, `Some(0)` for
+    ///   permissions the delegatee may use but not pass on, none if unlimited.
+    ///
+    /// See `issue_delegation_verifiable_credential` for the other arguments.
+    ///
+    /// # Returns
+    /// A result wrapping the credential or the error, if the credential of the issuer cannot be
+    /// delegated further or the permissions are not granted.
+    #[allow(clippy::too_many_arguments)]
+    pub fn issue_delegation_verifiable_credential_with_max_depth(&self, context: Vec<String>, credential_id: String,
+                                                                 valid_from: String, delegatee_id: String,
+                                                                 validity_period: Duration, permissions: Vec<Permission>,
+                                                                 optional_issuer_vc: Option<VerifiableCredential<OurDelegationCredential>>,
+                                                                 max_depth: Option<usize>)
+        -> Result<VerifiableCredential<OurDelegationCredential>, DelegationError> {
+        self.issue_restricted_delegation_verifiable_credential(context, credential_id, valid_from, delegatee_id, validity_period,
+                                                               permissions, optional_issuer_vc, max_depth, vec![])
+    }
+
+    /// Issues a delegation credential whose use is restricted by caveats, evaluated against the
+    /// context of each request by the verifier. The caveats of the credential of the issuer are
+    /// carried over, unless a requested caveat is stricter than one of them, in which case the
+    /// requested one replaces it.
+    ///
+    /// # Arguments
+    /// * `caveats` - Caveats restricting the requests the delegatee may make.
+    ///
+    /// See `issue_delegation_verifiable_credential_with_max_depth` for the other arguments.
+    ///
+    /// # Returns
+    /// A result wrapping the credential or the error, if the credential of the issuer cannot be
+    /// delegated further or the permissions are not granted.
+    #[allow(clippy::too_many_arguments)]
+    pub fn issue_restricted_delegation_verifiable_credential(&self, context: Vec<String>, credential_id: String,
+                                                             valid_from: String, delegatee_id: String,
+                                                             validity_period: Duration, permissions: Vec<Permission>,
+                                                             optional_issuer_vc: Option<VerifiableCredential<OurDelegationCredential>>,
+                                                             max_depth: Option<usize>, caveats: Vec<Caveat>)
+        -> Result<VerifiableCredential<OurDelegationCredential>, DelegationError> {
 
         let issuer = self.id.clone();
 
         if permissions.is_empty() {
-            return Err("Permissions array is empty".to_string());
+            return Err(DelegationError::EmptyPermissions);
         }
+        // A permission is accumulated, and granted, once however many times it is requested
+        let mut unique_permissions: Vec<Permission> = vec![];
+        for permission in permissions {
+            if !unique_permissions.contains(&permission) {
+                unique_permissions.push(permission);
+            }
+        }
+        let permissions = unique_permissions;
 
-        let since_epoch: Duration = match SystemTime::now().duration_since(UNIX_EPOCH) {
-            Ok(duration) => duration,
-            Err(e) => return Err(format!("Error encountered in computing issuance time: {e}")),
-        };
-
-        let numeric_iat: u128 = since_epoch.as_nanos();
+        let numeric_iat: u128 = self.clock.now()?;
         let mut numeric_exp: u128 = numeric_iat + validity_period.as_nanos();
         let iat = numeric_iat.to_string();
         let mut exp = numeric_exp.to_string();
@@ -101,7 +186,7 @@ impl <E: Pairing> OurIssuer<E> {
             for delegator in vc.credential().hierarchy() {
                 let delegator_exp = match u128::from_str(delegator.exp()) {
                     Ok(delegator_exp) => { delegator_exp }
-                    Err(err) => { return Err(format!("Could not parse delegator exp {} [{err}]", delegator.exp())); }
+                    Err(err) => { return Err(DelegationError::InvalidTimestamp { timestamp: delegator.exp().clone(), source: err }); }
                 };
 
                 if delegator_exp < numeric_exp {
@@ -111,45 +196,46 @@ impl <E: Pairing> OurIssuer<E> {
             }
         }
 
-        // Generate an AccumulatorManager to simplify the steps for accumulating claims
-        let mut am = AccumulatorManager::<E>::new(&self.acc_keypair.secret_key, &self.params);
-
-        // Convert each permission into a scalar
-        let mut permission_scalars: Vec<E::ScalarField> = vec![];
-        for permission in &permissions {
-            permission_scalars.push(AccumulatorUtils::<E>::convert_string_to_scalar(permission));
+        // The credential of the issuer must allow one more delegation, whose maximum depth is
+        // bounded by the remaining one
+        let mut max_depth = max_depth;
+        if let Some(vc) = &optional_issuer_vc {
+            match vc.credential().max_depth() {
+                Some(0) => { return Err(DelegationError::NotRedelegable); }
+                Some(issuer_max_depth) => { max_depth = Some(max_depth.map_or(issuer_max_depth - 1, |max_depth| max_depth.min(issuer_max_depth - 1))); }
+                None => {}
+            }
         }
 
-        // Convert each metadata into a scalar
-        let delegatee_id_scalar = AccumulatorUtils::<E>::convert_string_to_scalar(&delegatee_id);
-        let iat_scalar = AccumulatorUtils::<E>::convert_string_to_scalar(&iat.to_string());
-        let exp_scalar = AccumulatorUtils::<E>::convert_string_to_scalar(&exp.to_string());
-
-        // Accumulate every scalar
-        am.add_elements(permission_scalars.clone())?;
-        am.add_element(delegatee_id_scalar.clone())?;
-        am.add_element(iat_scalar.clone())?;
-        am.add_element(exp_scalar.clone())?;
-
-        // Retrieve the accumulated value
-        let accumulator_value = am.clone_accumulator()?;
+        // Caveats can only become stricter along the chain: those of the credential of the issuer
+        // are kept, unless a requested caveat narrows them
+        let mut unique_caveats: Vec<Caveat> = vec![];
+        for caveat in caveats {
+            if !unique_caveats.contains(&caveat) {
+                unique_caveats.push(caveat);
+            }
+        }
+        let mut caveats = unique_caveats;
+        if let Some(vc) = &optional_issuer_vc {
+            let mut inherited_caveats: Vec<Caveat> = vc.credential().caveats().iter()
+                .filter(|issuer_caveat| !caveats.iter().any(|caveat| caveat.is_within(issuer_caveat)))
+                .cloned()
+                .collect();
+            inherited_caveats.append(&mut caveats);
+            caveats = inherited_caveats;
+        }
 
-        // Compute each witness
-        let delegatee_id_witness = am.compute_witness(delegatee_id_scalar)?;
-        let iat_witness = am.compute_witness(iat_scalar)?;
-        let exp_witness = am.compute_witness(exp_scalar)?;
-        let metadata_witnesses: Vec<String> = vec![delegatee_id_witness, iat_witness, exp_witness];
-        let permission_witnesses: Vec<String> = am.compute_witnesses(permission_scalars.as_slice())?;
+        // The envelope of the credential expires together with the delegation
+        let valid_until = format_date_time(numeric_exp)?;
+        check_validity_period(&valid_from, Some(&valid_until), &iat, &exp)?;
 
-        match optional_issuer_vc {
+        // The hierarchy is built, and the permissions checked against the credential of the
+        // issuer, before anything is published: a rejected issuance must not leave an accumulator
+        // value behind in the registry
+        let hierarchy: Vec<OurDelegator> = match &optional_issuer_vc {
             // If the issued credential is from the root delegator, we simply set the hierarchy to an
             // empty array.
-            None => {
-                let hierarchy: Vec<OurDelegator> = vec![];
-                let dc = OurDelegationCredential::new(delegatee_id, accumulator_value, iat, exp, permissions, metadata_witnesses, permission_witnesses, hierarchy)?;
-                let vc = VerifiableCredential::new(context, credential_id, issuer, valid_from, dc);
-                Ok(vc)
-            }
+            None => vec![],
 
             // If not, we have to check that the permissions are indeed included in previously
             // issued credentials and filter out the permissions and witnesses to grant
@@ -159,105 +245,254 @@ impl <E: Pairing> OurIssuer<E> {
                 let mut issuer_permissions = issuer_dc.permissions().clone();
                 let mut issuer_permission_witnesses = issuer_dc.permission_witnesses().clone();
 
-                // Permissions are only available in the VC, not in hierarchy, so no need to check those
+                // Permissions are only available in the VC, not in hierarchy, so no need to check
+                // those. A permission is granted by an equal permission or, failing that, by a
+                // broader one implying it
+                let mut granting_indices: Vec<usize> = vec![];
                 for permission in &permissions {
-                    if ! issuer_permissions.contains(&permission) {
-                        return Err(format!("Permission {permission} cannot be granted since it was not included in the previous Delegation Credential"))
+                    let granting_index = issuer_permissions.iter().position(|issuer_permission| issuer_permission == permission)
+                        .or_else(|| issuer_permissions.iter().position(|issuer_permission| issuer_permission.implies(permission)));
+                    match granting_index {
+                        Some(i) => granting_indices.push(i),
+                        None => { return Err(DelegationError::PermissionNotGranted { permission: permission.to_string() }) }
                     }
                 }
+                let delegated_exactly = permissions.iter().all(|permission| issuer_permissions.contains(permission));
 
                 let mut issuer_hierarchy = issuer_dc.hierarchy().clone();
                 let issuer_permissions_size = issuer_permissions.len();
                 let permissions_size = permissions.len();
                 // We check that the issuer's permissions have the same cardinality of the witnesses
                 if issuer_permissions_size != issuer_permission_witnesses.len() {
-                    return Err(format!("Witnesses and permissions have different cardinality [{} - {}]", issuer_permissions_size, issuer_permission_witnesses.len()))
+                    return Err(DelegationError::MalformedCredential { reason: format!("witnesses and permissions have different cardinality [{} - {}]", issuer_permissions_size, issuer_permission_witnesses.len()) })
                 }
                 // We check that every delegator in the hierarchy has an amount of witnesses that
-                // is equal to the number of permissions that the issuer has
+                // is equal to the number of permissions it proves
                 for delegator in issuer_hierarchy.iter() {
-                    if issuer_permissions_size != delegator.permission_witnesses().len() {
-                        return Err(format!("Delegation Credential is not well formatted: delegator contains more witnesses than the permits the credential grants [{} - {}]", issuer_permissions_size, delegator.permission_witnesses().len()))
+                    let proven_permissions_size = delegator.proven_permissions(&issuer_permissions).len();
+                    if proven_permissions_size != delegator.permission_witnesses().len() {
+                        return Err(DelegationError::MalformedCredential { reason: format!("delegator contains more witnesses than the permits the credential grants [{} - {}]", proven_permissions_size, delegator.permission_witnesses().len()) })
                     }
                 }
 
-                // If the delegation credential does have more permissions than the previous one,
-                // it incurs in an error
-                if permissions_size > issuer_permissions_size {
-                    return Err(format!("Cannot grant more permissions than those included in the previous Delegation Credential [{} < {}]", permissions_size, issuer_permissions_size))
-                }
-                // Otherwise, if it has fewer permissions than the previous one, we must filter out
-                // the unnecessary permissions and witnesses from the previous one (and its hierarchy)
-                // We assume here that permissions are granted in the same order as the previous ones
-                else if permissions_size < issuer_permissions_size {
-                    let mut removable_indices: Vec<usize> = vec![];
-
-                    // For every issuer permission check whether it is contained in the permissions
-                    // to be delegated. If not, add it to an array of indices to be removed
-                    for (i, issuer_permission) in issuer_permissions.iter().enumerate() {
-                        if !permissions.contains(&issuer_permission) {
-                            removable_indices.push(i);
-                        }
+                if delegated_exactly {
+                    // If the delegation credential does have more permissions than the previous one,
+                    // it incurs in an error
+                    if permissions_size > issuer_permissions_size {
+                        return Err(DelegationError::TooManyPermissions { requested: permissions_size, granted: issuer_permissions_size })
                     }
 
-                    // Remove indices from issuer permissions, issuer witnesses, and delegator
-                    // witnesses contained in hierarchy
-                    for i in removable_indices.iter().rev() {
-                        issuer_permissions.remove(*i);
-                        issuer_permission_witnesses.remove(*i);
-
-                        for delegator in issuer_hierarchy.iter_mut() {
-                            delegator.mut_permission_witnesses().remove(*i);
-                        }
+                    // Otherwise, we must select the witnesses of the delegated permissions from the
+                    // previous one (and its hierarchy). Witnesses are selected by permission, so the
+                    // permissions may be delegated in any order
+                    issuer_permission_witnesses = OurDelegationCredential::select_witnesses(&issuer_permissions, &issuer_permission_witnesses, &permissions)?;
+                    for delegator in issuer_hierarchy.iter_mut().filter(|delegator| delegator.permissions().is_none()) {
+                        let witnesses = OurDelegationCredential::select_witnesses(&issuer_permissions, delegator.permission_witnesses(), &permissions)?;
+                        *delegator.mut_permission_witnesses() = witnesses;
                     }
+                    issuer_permissions = permissions.clone();
                 }
+                // Otherwise, the issuer's witnesses prove broader permissions than the delegated
+                // ones, so the issuer and the delegators that proved the issuer's permissions must
+                // now disclose the permissions they prove
+                else {
+                    for delegator in issuer_hierarchy.iter_mut().filter(|delegator| delegator.permissions().is_none()) {
+                        delegator.set_permissions(Some(issuer_permissions.clone()));
+                    }
 
-                let issuer_delegator = OurDelegator::new(
+                    granting_indices.sort();
+                    granting_indices.dedup();
+                    issuer_permissions = granting_indices.iter().map(|i| issuer_permissions[*i].clone()).collect();
+                    issuer_permission_witnesses = granting_indices.iter().map(|i| issuer_permission_witnesses[*i].clone()).collect();
+                }
+                OurDelegationCredential::narrow_hierarchy(&mut issuer_hierarchy, &issuer_permissions);
+
+                let mut issuer_delegator = OurDelegator::new(
                     issuer_vc.issuer().clone(),
                     issuer_dc.delegatee_id().clone(), // should be equal to self.id
                     issuer_dc.iat().clone(),
                     issuer_dc.exp().clone(),
+                    issuer_dc.element_encoding(),
                     issuer_dc.accumulator_value().clone(),
                     issuer_dc.metadata_witnesses().clone(),
                     issuer_permission_witnesses.clone()
                 );
+                issuer_delegator.set_max_depth(issuer_dc.max_depth());
+                issuer_delegator.set_caveats(issuer_dc.caveats().clone());
+                if !delegated_exactly {
+                    issuer_delegator.set_permissions(Some(issuer_permissions));
+                }
                 issuer_hierarchy.push(issuer_delegator);
+                issuer_hierarchy
+            }
+        };
 
-                let result_dc = OurDelegationCredential::new(
-                    delegatee_id,
-                    accumulator_value,
-                    iat,
-                    exp,
-                    permissions,
-                    metadata_witnesses,
-                    permission_witnesses,
-                    issuer_hierarchy.clone()
-                )?;
-
-                let result_vc = VerifiableCredential::new(
-                    context,
-                    credential_id,
-                    issuer,
-                    valid_from,
-                    result_dc
-                );
+        // Generate an AccumulatorManager to simplify the steps for accumulating claims
+        let mut am = AccumulatorManager::<E>::new(&self.acc_keypair.secret_key, &self.params);
 
-                Ok(result_vc)
+        // Elements are bound to the key under which the accumulator value is published
+        let revocation_key = AccumulatorValueEntry::key(&issuer, &delegatee_id, &iat, max_depth, &caveats);
+        let element_encoding = self.element_encoding;
 
-            }
+        // Convert each permission into a scalar
+        let mut permission_scalars: Vec<E::ScalarField> = vec![];
+        for permission in &permissions {
+            let element = AccumulatorElement::new(element_encoding, ElementRole::Permission, &revocation_key, &permission.to_string());
+            permission_scalars.push(AccumulatorUtils::<E>::convert_element_to_scalar(&element));
         }
+
+        // Convert each metadata into a scalar
+        let delegatee_id_scalar = AccumulatorUtils::<E>::convert_element_to_scalar(&AccumulatorElement::new(element_encoding, ElementRole::DelegateeId, &revocation_key, &delegatee_id));
+        let iat_scalar = AccumulatorUtils::<E>::convert_element_to_scalar(&AccumulatorElement::new(element_encoding, ElementRole::IssuedAt, &revocation_key, &iat));
+        let exp_scalar = AccumulatorUtils::<E>::convert_element_to_scalar(&AccumulatorElement::new(element_encoding, ElementRole::ExpiresAt, &revocation_key, &exp));
+
+        // Accumulate every scalar
+        am.add_elements(permission_scalars.clone())?;
+        am.add_element(delegatee_id_scalar)?;
+        am.add_element(iat_scalar)?;
+        am.add_element(exp_scalar)?;
+
+        // Retrieve the accumulated value
+        let accumulator_value = am.clone_accumulator()?;
+
+        // Compute each witness
+        let delegatee_id_witness = am.compute_witness(delegatee_id_scalar)?;
+        let iat_witness = am.compute_witness(iat_scalar)?;
+        let exp_witness = am.compute_witness(exp_scalar)?;
+        let metadata_witnesses: Vec<String> = vec![delegatee_id_witness, iat_witness, exp_witness];
+        let permission_witnesses: Vec<String> = am.compute_witnesses(permission_scalars.as_slice())?;
+
+        // Publish the accumulator value in the registry and keep the accumulator so that the
+        // credential can be revoked later on. The lock is held while publishing, so that two
+        // issuances with the same credential id cannot both publish their value.
+        let (accumulator, state) = am.into_parts();
+        let mut issued_accumulators = match self.issued_accumulators.lock() {
+            Ok(issued_accumulators) => issued_accumulators,
+            Err(_) => { return Err(DelegationError::LockPoisoned) }
+        };
+        if issued_accumulators.contains_key(&credential_id) {
+            return Err(DelegationError::CredentialAlreadyIssued { credential_id });
+        }
+        self.registry.publish_accumulator_value(&revocation_key, accumulator_value.clone())?;
+        issued_accumulators.insert(credential_id.clone(), IssuedAccumulator {
+            revocation_key, delegatee_id: delegatee_id.clone(), element_encoding, accumulator, state
+        });
+        drop(issued_accumulators);
+
+        let mut dc = OurDelegationCredential::new(delegatee_id, accumulator_value, iat, exp, element_encoding, permissions, metadata_witnesses, permission_witnesses, hierarchy)?;
+        dc.set_max_depth(max_depth);
+        dc.set_caveats(caveats);
+        Ok(VerifiableCredential::new(context, credential_id, issuer, valid_from, Some(valid_until), dc))
     }
 
     pub fn issue_delegation_verifiable_presentation(&self, vc: VerifiableCredential<OurDelegationCredential>,
-                                                    disclosed_permissions: Vec<String>)
-                                                    -> Result<String, String> {
+                                                    disclosed_permissions: Vec<Permission>)
+                                                    -> Result<String, DelegationError> {
 
         let vp: VerifiablePresentation<OurDelegationCredential> = VerifiablePresentation::from_verifiable_credential(vc, disclosed_permissions)?;
 
         // TODO: remove this.
         // println!("{}", serde_json::to_string_pretty(&vp).unwrap());
 
-        vp.to_signed_jwt(&self.signature_jwk)
+        vp.to_signed_jwt(&self.signature_jwk, self.clock.now()?)
+    }
+
+    /// Issues a presentation bound to its holder: the presentation answers the request of the
+    /// verifier and must be signed by the subject of the delegation credential.
+    ///
+    /// # Arguments
+    /// * `vc` - Delegation credential issued to this entity.
+    /// * `disclosed_permissions` - Permissions to be disclosed to the verifier.
+    /// * `request` - Presentation request received from the verifier.
+    ///
+    /// # Returns
+    /// A result wrapping the signed presentation or the error, if it occurs.
+    pub fn issue_bound_delegation_verifiable_presentation(&self, vc: VerifiableCredential<OurDelegationCredential>,
+                                                          disclosed_permissions: Vec<Permission>, request: &PresentationRequest)
+                                                          -> Result<String, DelegationError> {
+
+        if *vc.credential().delegatee_id() != self.id {
+            return Err(DelegationError::PresenterMismatch { presenter: self.id.clone(), subject: vc.credential().delegatee_id().clone() });
+        }
+
+        let mut vp: VerifiablePresentation<OurDelegationCredential> = VerifiablePresentation::from_verifiable_credential(vc, disclosed_permissions)?;
+        vp.set_request(request);
+
+        vp.to_signed_jwt(&self.signature_jwk, self.clock.now()?)
+    }
+
+    /// Revokes a delegation credential as a whole by removing its delegatee id from the
+    /// accumulator. The metadata witnesses of the credential can no longer be verified.
+    ///
+    /// # Arguments
+    /// * `credential_id` - Id of the delegation credential previously issued by this issuer.
+    ///
+    /// # Returns
+    /// The result of the operation or the error, if it occurs.
+    pub fn revoke_delegatee(&self, credential_id: &String) -> Result<(), DelegationError> {
+        let mut issued_accumulators = match self.issued_accumulators.lock() {
+            Ok(issued_accumulators) => issued_accumulators,
+            Err(_) => { return Err(DelegationError::LockPoisoned) }
+        };
+        let issued = match issued_accumulators.get_mut(credential_id) {
+            Some(issued) => issued,
+            None => { return Err(DelegationError::CredentialNotIssued { credential_id: credential_id.clone() }); }
+        };
+
+        let delegatee_id_element = AccumulatorElement::new(issued.element_encoding, ElementRole::DelegateeId, &issued.revocation_key, &issued.delegatee_id);
+        let delegatee_id_scalar = AccumulatorUtils::<E>::convert_element_to_scalar(&delegatee_id_element);
+
+        let mut am = AccumulatorManager::<E>::restore(&self.acc_keypair.secret_key, issued.accumulator.clone(), issued.state.clone());
+        let update = am.remove_elements_with_update(&[delegatee_id_scalar])?;
+        self.publish_accumulator(issued, am, update)
+    }
+
+    /// Revokes a subset of the permissions granted by a delegation credential by removing them
+    /// from the accumulator. The remaining permissions stay valid.
+    ///
+    /// # Arguments
+    /// * `credential_id` - Id of the delegation credential previously issued by this issuer.
+    /// * `permissions` - Permissions to be revoked.
+    ///
+    /// # Returns
+    /// The result of the operation or the error, if it occurs.
+    pub fn revoke_permissions(&self, credential_id: &String, permissions: &[Permission]) -> Result<(), DelegationError> {
+        if permissions.is_empty() {
+            return Err(DelegationError::EmptyPermissions);
+        }
+
+        let mut issued_accumulators = match self.issued_accumulators.lock() {
+            Ok(issued_accumulators) => issued_accumulators,
+            Err(_) => { return Err(DelegationError::LockPoisoned) }
+        };
+        let issued = match issued_accumulators.get_mut(credential_id) {
+            Some(issued) => issued,
+            None => { return Err(DelegationError::CredentialNotIssued { credential_id: credential_id.clone() }); }
+        };
+
+        let permission_scalars: Vec<E::ScalarField> = permissions.iter()
+            .map(|permission| AccumulatorElement::new(issued.element_encoding, ElementRole::Permission, &issued.revocation_key, &permission.to_string()))
+            .map(|element| AccumulatorUtils::<E>::convert_element_to_scalar(&element))
+            .collect();
+        for (permission, scalar) in permissions.iter().zip(permission_scalars.iter()) {
+            if !issued.state.has(scalar) {
+                return Err(DelegationError::PermissionNotGranted { permission: permission.to_string() });
+            }
+        }
+
+        let mut am = AccumulatorManager::<E>::restore(&self.acc_keypair.secret_key, issued.accumulator.clone(), issued.state.clone());
+        let update = am.remove_elements_with_update(&permission_scalars)?;
+        self.publish_accumulator(issued, am, update)
+    }
+
+    // Stores the updated accumulator and publishes the witness update information in the
+    // registry
+    fn publish_accumulator(&self, issued: &mut IssuedAccumulator<E>, am: AccumulatorManager<E>, update: AccumulatorUpdate) -> Result<(), DelegationError> {
+        let (accumulator, state) = am.into_parts();
+        issued.accumulator = accumulator;
+        issued.state = state;
+
+        self.registry.publish_accumulator_update(&issued.revocation_key, update)
     }
 
 }
@@ -267,24 +502,24 @@ impl <E: Pairing> OurIssuer<E> {
 mod tests {
     use super::*;
     use ark_bn254::Bn254;
-    use crate::delegation::entities::dtl_sim::new_dlt_sim;
+    use crate::delegation::clock::fixed_clock::FixedClock;
+    use crate::delegation::registry::in_memory_registry::InMemoryRegistry;
 
     #[test]
-    fn issue_vc() -> Result<(), String> {
+    fn issue_vc() -> Result<(), Box<dyn std::error::Error>> {
 
         type Curve = Bn254;
-        let acc_sim: DLTSim<DLTSimAccEntry<Curve>> = new_dlt_sim();
-        let ecc_sim:  DLTSim<Jwk> = new_dlt_sim();
+        let registry = InMemoryRegistry::shared();
 
         let id = String::from("https://vc.example/delegators/d0");
         let previous_vc = None;
-        let issuer: OurIssuer<Curve> = OurIssuer::new(id, acc_sim.clone(), ecc_sim.clone())?;
+        let issuer: OurIssuer<Curve> = OurIssuer::new(id, registry.clone())?;
         let context: Vec<String> = vec![ String::from("https://www.w3.org/ns/credentials/v2") ];
         let credential_id = String::from("http://delegation.example/credentials/1337");
         let valid_from =  String::from("2026-01-01T00:00:00Z");
         let delegatee_id = String::from("https://vc.example/delegators/d1");
         let validity_period: Duration = Duration::new(3600, 0);
-        let permissions: Vec<String> = vec![ String::from("https://vc.example/resources/r1:p0"), String::from("https://vc.example/resources/r1:p1"), String::from("https://vc.example/resources/r1:p2") ];
+        let permissions: Vec<Permission> = vec![ Permission::from_str("https://vc.example/resources/r1:p0")?, Permission::from_str("https://vc.example/resources/r1:p1")?, Permission::from_str("https://vc.example/resources/r1:p2")? ];
         let vc = issuer.issue_delegation_verifiable_credential(context, credential_id, valid_from, delegatee_id, validity_period, permissions, previous_vc)?;
 
         let vc_str = serde_json::to_string_pretty(&vc).unwrap();
@@ -295,13 +530,13 @@ mod tests {
 
         let id = String::from("https://vc.example/delegators/d1");
         let previous_vc = Some(vc);
-        let issuer: OurIssuer<Bn254> = OurIssuer::new(id, acc_sim.clone(), ecc_sim.clone())?;
+        let issuer: OurIssuer<Bn254> = OurIssuer::new(id, registry.clone())?;
         let context: Vec<String> = vec![ String::from("https://www.w3.org/ns/credentials/v2") ];
         let credential_id = String::from("http://delegation.example/credentials/1338");
         let valid_from =  String::from("2026-01-01T00:00:00Z");
         let delegatee_id = String::from("https://vc.example/delegators/d2");
         let validity_period: Duration = Duration::new(3600, 0);
-        let permissions: Vec<String> = vec![ String::from("https://vc.example/resources/r1:p0"), String::from("https://vc.example/resources/r1:p1") ];
+        let permissions: Vec<Permission> = vec![ Permission::from_str("https://vc.example/resources/r1:p0")?, Permission::from_str("https://vc.example/resources/r1:p1")? ];
         let vc = issuer.issue_delegation_verifiable_credential(context, credential_id, valid_from, delegatee_id, validity_period, permissions, previous_vc)?;
 
         let vc_str = serde_json::to_string_pretty(&vc).unwrap();
@@ -312,13 +547,13 @@ mod tests {
 
         let id = String::from("https://vc.example/delegators/d2");
         let previous_vc = Some(vc);
-        let issuer: OurIssuer<Bn254> = OurIssuer::new(id, acc_sim.clone(), ecc_sim.clone())?;
+        let issuer: OurIssuer<Bn254> = OurIssuer::new(id, registry.clone())?;
         let context: Vec<String> = vec![ String::from("https://www.w3.org/ns/credentials/v2") ];
         let credential_id = String::from("http://delegation.example/credentials/1339");
         let valid_from =  String::from("2026-01-01T00:00:00Z");
         let delegatee_id = String::from("https://vc.example/delegators/d3");
         let validity_period: Duration = Duration::new(3600, 0);
-        let permissions: Vec<String> = vec![ String::from("https://vc.example/resources/r1:p0"), String::from("https://vc.example/resources/r1:p1") ];
+        let permissions: Vec<Permission> = vec![ Permission::from_str("https://vc.example/resources/r1:p0")?, Permission::from_str("https://vc.example/resources/r1:p1")? ];
         let vc = issuer.issue_delegation_verifiable_credential(context, credential_id, valid_from, delegatee_id, validity_period, permissions, previous_vc)?;
 
         let vc_str = serde_json::to_string_pretty(&vc).unwrap();
@@ -330,13 +565,13 @@ mod tests {
 
         let id = String::from("https://vc.example/delegators/d3");
         let previous_vc = Some(vc);
-        let issuer: OurIssuer<Bn254> = OurIssuer::new(id, acc_sim.clone(), ecc_sim.clone())?;
+        let issuer: OurIssuer<Bn254> = OurIssuer::new(id, registry.clone())?;
         let context: Vec<String> = vec![ String::from("https://www.w3.org/ns/credentials/v2") ];
         let credential_id = String::from("http://delegation.example/credentials/1340");
         let valid_from = String::from("2026-01-01T00:00:00Z");
         let delegatee_id = String::from("https://vc.example/delegators/d4");
         let validity_period: Duration = Duration::new(3600, 0);
-        let permissions: Vec<String> = vec![ String::from("https://vc.example/resources/r1:p0") ];
+        let permissions: Vec<Permission> = vec![ Permission::from_str("https://vc.example/resources/r1:p0")? ];
         let vc = issuer.issue_delegation_verifiable_credential(context, credential_id, valid_from, delegatee_id, validity_period, permissions, previous_vc)?;
 
 
@@ -350,46 +585,45 @@ mod tests {
     }
 
     #[test]
-    fn issue_vp() -> Result<(), String> {
+    fn issue_vp() -> Result<(), Box<dyn std::error::Error>> {
         type Curve = Bn254;
-        let acc_sim: DLTSim<DLTSimAccEntry<Curve>> = new_dlt_sim();
-        let ecc_sim: DLTSim<Jwk> = new_dlt_sim();
+        let registry = InMemoryRegistry::shared();
 
         let id = String::from("https://vc.example/delegators/d0");
         let previous_vc = None;
-        let issuer: OurIssuer<Curve> = OurIssuer::new(id, acc_sim.clone(), ecc_sim.clone())?;
+        let issuer: OurIssuer<Curve> = OurIssuer::new(id, registry.clone())?;
         let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
         let credential_id = String::from("http://delegation.example/credentials/1337");
         let valid_from = String::from("2026-01-01T00:00:00Z");
         let delegatee_id = String::from("https://vc.example/delegators/d1");
         let validity_period: Duration = Duration::new(3600, 0);
-        let permissions: Vec<String> = vec![String::from("https://vc.example/resources/r1:p0"), String::from("https://vc.example/resources/r1:p1"), String::from("https://vc.example/resources/r1:p2")];
+        let permissions: Vec<Permission> = vec![Permission::from_str("https://vc.example/resources/r1:p0")?, Permission::from_str("https://vc.example/resources/r1:p1")?, Permission::from_str("https://vc.example/resources/r1:p2")?];
         let vc = issuer.issue_delegation_verifiable_credential(context, credential_id, valid_from, delegatee_id, validity_period, permissions, previous_vc)?;
 
         let id = String::from("https://vc.example/delegators/d1");
         let previous_vc = Some(vc);
-        let issuer: OurIssuer<Bn254> = OurIssuer::new(id, acc_sim.clone(), ecc_sim.clone())?;
+        let issuer: OurIssuer<Bn254> = OurIssuer::new(id, registry.clone())?;
         let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
         let credential_id = String::from("http://delegation.example/credentials/1338");
         let valid_from = String::from("2026-01-01T00:00:00Z");
         let delegatee_id = String::from("https://vc.example/delegators/d2");
         let validity_period: Duration = Duration::new(3600, 0);
-        let permissions: Vec<String> = vec![String::from("https://vc.example/resources/r1:p0"), String::from("https://vc.example/resources/r1:p1")];
+        let permissions: Vec<Permission> = vec![Permission::from_str("https://vc.example/resources/r1:p0")?, Permission::from_str("https://vc.example/resources/r1:p1")?];
         let vc = issuer.issue_delegation_verifiable_credential(context, credential_id, valid_from, delegatee_id, validity_period, permissions, previous_vc)?;
 
         let id = String::from("https://vc.example/delegators/d2");
         let previous_vc = Some(vc);
-        let issuer: OurIssuer<Bn254> = OurIssuer::new(id, acc_sim.clone(), ecc_sim.clone())?;
+        let issuer: OurIssuer<Bn254> = OurIssuer::new(id, registry.clone())?;
         let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
         let credential_id = String::from("http://delegation.example/credentials/1339");
         let valid_from = String::from("2026-01-01T00:00:00Z");
         let delegatee_id = String::from("https://vc.example/delegators/d3");
         let validity_period: Duration = Duration::new(3600, 0);
-        let permissions: Vec<String> = vec![String::from("https://vc.example/resources/r1:p0"), String::from("https://vc.example/resources/r1:p1")];
+        let permissions: Vec<Permission> = vec![Permission::from_str("https://vc.example/resources/r1:p0")?, Permission::from_str("https://vc.example/resources/r1:p1")?];
         let vc = issuer.issue_delegation_verifiable_credential(context, credential_id, valid_from, delegatee_id, validity_period, permissions, previous_vc)?;
         println!("{vc}");
 
-        let disclosed_permissions: Vec<String> = vec![String::from("https://vc.example/resources/r1:p1")];
+        let disclosed_permissions: Vec<Permission> = vec![Permission::from_str("https://vc.example/resources/r1:p1")?];
         let signed_vp = issuer.issue_delegation_verifiable_presentation(vc, disclosed_permissions)?;
 
         println!("{signed_vp}");
@@ -398,4 +632,71 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn rejected_issuance_publishes_nothing() -> Result<(), Box<dyn std::error::Error>> {
+        type Curve = Bn254;
+        let registry = InMemoryRegistry::shared();
+        let clock = Arc::new(FixedClock::new(1_767_225_600_000_000_000));
+
+        let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
+        let valid_from = String::from("2026-01-01T00:00:00Z");
+        let validity_period: Duration = Duration::new(3600, 0);
+        let permissions: Vec<Permission> = vec![Permission::from_str("https://vc.example/resources/r1:p0")?];
+
+        let mut root: OurIssuer<Curve> = OurIssuer::new(String::from("https://vc.example/delegators/d0"), registry.clone())?;
+        root.set_clock(clock.clone());
+        let delegatee_id = String::from("https://vc.example/delegators/d1");
+        let root_vc = root.issue_delegation_verifiable_credential(context.clone(), String::from("http://delegation.example/credentials/1337"), valid_from.clone(), delegatee_id.clone(), validity_period, permissions.clone(), None)?;
+
+        // A permission that is not granted by the credential of the issuer is rejected before its
+        // accumulator value is published
+        let mut delegator: OurIssuer<Curve> = OurIssuer::new(delegatee_id.clone(), registry.clone())?;
+        delegator.set_clock(clock.clone());
+        let credential_id = String::from("http://delegation.example/credentials/1338");
+        let subdelegatee_id = String::from("https://vc.example/delegators/d2");
+        let not_granted: Vec<Permission> = vec![Permission::from_str("https://vc.example/resources/r1:p1")?];
+        match delegator.issue_delegation_verifiable_credential(context.clone(), credential_id.clone(), valid_from.clone(), subdelegatee_id.clone(), validity_period, not_granted, Some(root_vc.clone())) {
+            Err(DelegationError::PermissionNotGranted { .. }) => {}
+            other => { return Err(format!("Expected PermissionNotGranted, got {:?}", other.map(|vc| vc.id().clone())).into()); }
+        }
+        let key = AccumulatorValueEntry::key(&delegatee_id, &subdelegatee_id, &clock.now()?.to_string(), None, &[]);
+        assert!(registry.resolve_accumulator_value(&key)?.is_none());
+
+        // The rejected credential id is still available, but cannot be issued twice
+        delegator.issue_delegation_verifiable_credential(context.clone(), credential_id.clone(), valid_from.clone(), subdelegatee_id.clone(), validity_period, permissions.clone(), Some(root_vc.clone()))?;
+        clock.advance(Duration::new(1, 0))?;
+        match delegator.issue_delegation_verifiable_credential(context, credential_id, valid_from, subdelegatee_id.clone(), validity_period, permissions, Some(root_vc)) {
+            Err(DelegationError::CredentialAlreadyIssued { .. }) => {}
+            other => { return Err(format!("Expected CredentialAlreadyIssued, got {:?}", other.map(|vc| vc.id().clone())).into()); }
+        }
+        let key = AccumulatorValueEntry::key(&delegatee_id, &subdelegatee_id, &clock.now()?.to_string(), None, &[]);
+        assert!(registry.resolve_accumulator_value(&key)?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn issue_vp_at_clock_time() -> Result<(), Box<dyn std::error::Error>> {
+        type Curve = Bn254;
+        let registry = InMemoryRegistry::shared();
+        let clock = Arc::new(FixedClock::new(1_767_225_600_000_000_000));
+
+        let root_id = String::from("https://vc.example/delegators/d0");
+        let mut root: OurIssuer<Curve> = OurIssuer::new(root_id.clone(), registry.clone())?;
+        root.set_clock(clock.clone());
+        let permissions: Vec<Permission> = vec![Permission::from_str("https://vc.example/resources/r1:p0")?];
+        let vc = root.issue_delegation_verifiable_credential(vec![String::from("https://www.w3.org/ns/credentials/v2")], String::from("http://delegation.example/credentials/1337"),
+                                                             String::from("2026-01-01T00:00:00Z"), String::from("https://vc.example/delegators/d1"), Duration::new(3600, 0), permissions.clone(), None)?;
+
+        // The presentation is issued at the time of the clock of the presenter, not of the system
+        clock.advance(Duration::new(60, 0))?;
+        let signed_vp = root.issue_delegation_verifiable_presentation(vc, permissions)?;
+
+        let jwk = registry.resolve_key(KeyPurpose::Verification, &root_id)?.ok_or("Key of the presenter not published")?;
+        let verifier = SignatureAlgorithm::for_jwk(&jwk)?.verifier_from_jwk(&jwk)?;
+        let (payload, _) = josekit::jwt::decode_with_verifier(&signed_vp, verifier.as_ref())?;
+        assert_eq!(payload.issued_at(), Some(std::time::UNIX_EPOCH + Duration::from_secs(1_767_225_660)));
+        Ok(())
+    }
+
 }
@@ -0,0 +1,79 @@
+use crate::delegation::accumulators::verification_mode::VerificationMode;
+use crate::delegation::credentials::ours::our_delegation_credential::OurDelegationCredential;
+use crate::delegation::credentials::permission::Permission;
+use crate::delegation::credentials::presentation_request::PresentationRequest;
+use crate::delegation::credentials::verifiable_credential::VerifiableCredential;
+use crate::delegation::entities::ours::our_issuer::OurIssuer;
+use crate::delegation::entities::ours::our_verifier::OurVerifier;
+use crate::delegation::error::DelegationError;
+use crate::delegation::registry::verifiable_data_registry::VerifiableDataRegistry;
+use crate::delegation::replay::in_memory_replay_cache::InMemoryReplayCache;
+use crate::delegation::replay::replay_cache::ReplayCache;
+use crate::delegation::traits::delegation_scheme::{CredentialParameters, DelegationScheme, SignedPresentation};
+use ark_ec::pairing::Pairing;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// Delegation scheme based on accumulators. Presentations are signed by the holder of the
+/// credential and verified in the given mode.
+pub struct OurScheme<E: Pairing> {
+    registry: Arc<dyn VerifiableDataRegistry>,
+    replay_cache: Arc<dyn ReplayCache>,
+    mode: VerificationMode,
+    phantom: PhantomData<E>,
+}
+
+impl <E: Pairing> OurScheme<E> {
+    pub fn new(registry: Arc<dyn VerifiableDataRegistry>, mode: VerificationMode) -> Self {
+        Self::with_replay_cache(registry, InMemoryReplayCache::shared(), mode)
+    }
+
+    /// Creates the scheme with verifiers sharing the given cache of the nonces already used.
+    pub fn with_replay_cache(registry: Arc<dyn VerifiableDataRegistry>, replay_cache: Arc<dyn ReplayCache>, mode: VerificationMode) -> Self {
+        OurScheme { registry, replay_cache, mode, phantom: PhantomData }
+    }
+}
+
+impl <E: Pairing> DelegationScheme for OurScheme<E> {
+    type Credential = OurDelegationCredential;
+    type Issuer = OurIssuer<E>;
+    type Verifier = OurVerifier<E>;
+
+    fn name(&self) -> &str {
+        match self.mode {
+            VerificationMode::Parallel => "Ours",
+            VerificationMode::Sequential => "Ours (sequential)",
+            VerificationMode::Batched => "Ours (batched)",
+        }
+    }
+
+    fn issuer(&self, id: String) -> Result<Self::Issuer, DelegationError> {
+        OurIssuer::new(id, self.registry.clone())
+    }
+
+    // Any entity can verify the presentations, the owner is only known from the root of the chain
+    fn verifier(&self, _owner: &Self::Issuer) -> Result<Self::Verifier, DelegationError> {
+        OurVerifier::with_replay_cache(self.registry.clone(), 0, self.replay_cache.clone())
+    }
+
+    fn issue_verifiable_credential(&self, issuer: &Self::Issuer, parameters: &CredentialParameters,
+                                   parent: Option<VerifiableCredential<Self::Credential>>)
+                                   -> Result<VerifiableCredential<Self::Credential>, DelegationError> {
+        issuer.issue_delegation_verifiable_credential(
+            parameters.context.clone(), parameters.credential_id.clone(), parameters.valid_from.clone(),
+            parameters.delegatee_id.clone(), parameters.validity_period, parameters.permissions.clone(), parent
+        )
+    }
+
+    fn issue_verifiable_presentation(&self, _issuer: &Self::Issuer, holder: &Self::Issuer,
+                                     vc: VerifiableCredential<Self::Credential>, disclosed_permissions: Vec<Permission>,
+                                     request: &PresentationRequest) -> Result<SignedPresentation, DelegationError> {
+        let jwt = holder.issue_bound_delegation_verifiable_presentation(vc, disclosed_permissions, request)?;
+        Ok(SignedPresentation { presenter_id: holder.id().clone(), jwt })
+    }
+
+    fn verify_verifiable_presentation(&self, verifier: &Self::Verifier, presentation: &SignedPresentation,
+                                      request: &PresentationRequest) -> Result<(), DelegationError> {
+        verifier.verify_bound_verifiable_presentation(presentation.presenter_id.clone(), presentation.jwt.clone(), request, self.mode)
+    }
+}
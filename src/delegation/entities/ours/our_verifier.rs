@@ -1,64 +1,163 @@
 use crate::delegation::accumulators::accumulator_verifier::AccumulatorVerifier;
+use crate::delegation::accumulators::batch_verifier::BatchVerifier;
+use crate::delegation::accumulators::verification_mode::VerificationMode;
+use crate::delegation::clock::clock::Clock;
+use crate::delegation::clock::system_clock::SystemClock;
+use crate::delegation::credentials::presentation_request::PresentationRequest;
+use crate::delegation::credentials::request_context::RequestContext;
+use crate::delegation::credentials::signature_algorithm::SignatureAlgorithm;
 use crate::delegation::credentials::verifiable_presentation::VerifiablePresentation;
 use ark_ec::pairing::Pairing;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use josekit::jwk::Jwk;
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::time::Duration;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 use crate::delegation::credentials::ours::our_delegation::OurDelegation;
 use crate::delegation::credentials::ours::our_delegation_credential::OurDelegationCredential;
-use crate::delegation::entities::dtl_sim::DLTSim;
-use crate::delegation::entities::ours::dlt_acc_entry::DLTSimAccEntry;
-use crate::delegation::entities::verifier::verify_timings;
+use crate::delegation::credentials::permission::Permission;
+use crate::delegation::entities::verifier::{consume_nonce, verify_presentation_request, verify_timings, verify_validity_period};
+use crate::delegation::error::DelegationError;
+use crate::delegation::registry::accumulator_params_entry::AccumulatorParamsEntry;
+use crate::delegation::registry::verifiable_data_registry::{KeyPurpose, VerifiableDataRegistry};
+use crate::delegation::replay::in_memory_replay_cache::InMemoryReplayCache;
+use crate::delegation::replay::replay_cache::ReplayCache;
 
 pub struct OurVerifier<E: Pairing> {
-    accumulator_dlt: DLTSim<DLTSimAccEntry<E>>,
-    verification_dlt: DLTSim<Jwk>
+    registry: Arc<dyn VerifiableDataRegistry>,
+    // Accumulator parameters already resolved from the registry, indexed by issuer. They are never
+    // rotated, so they can be decoded once.
+    accumulator_params: RwLock<HashMap<String, AccumulatorParamsEntry<E>>>,
+    // Workers verifying the delegations and the witnesses in parallel mode
+    pool: ThreadPool,
+    // Nonces of the presentation requests already answered
+    replay_cache: Arc<dyn ReplayCache>,
+    // Algorithms accepted for the signatures of the presentations
+    allowed_algorithms: Vec<SignatureAlgorithm>,
+    clock: Arc<dyn Clock>,
+    // Tolerated skew between the clocks of the issuers and of this verifier
+    leeway: Duration,
 }
 
 impl<E: Pairing> OurVerifier<E> {
-    pub fn new(accumulator_dlt: DLTSim<DLTSimAccEntry<E>>, verification_dlt: DLTSim<Jwk>) -> Result<Self, String> {
-        Ok(OurVerifier { accumulator_dlt, verification_dlt })
+    pub fn new(registry: Arc<dyn VerifiableDataRegistry>) -> Result<Self, DelegationError> {
+        Self::with_workers(registry, 0)
     }
 
-    fn verify_delegation<D: OurDelegation>(&self, delegation: &D, issuer: &String, permissions: &Vec<String>, now_ns: u128, parallel: bool) -> Result<(), String> {
-
-        verify_timings(now_ns, delegation.iat(), delegation.exp())?;
+    /// Creates a verifier with a bounded number of workers for the parallel verification mode.
+    ///
+    /// # Arguments
+    /// * `registry` - Registry the public material of the issuers is resolved from.
+    /// * `workers` - Number of worker threads, 0 to use one for each available CPU.
+    ///
+    /// # Returns
+    /// A result wrapping the verifier or the error, if the worker pool cannot be built.
+    pub fn with_workers(registry: Arc<dyn VerifiableDataRegistry>, workers: usize) -> Result<Self, DelegationError> {
+        Self::with_replay_cache(registry, workers, InMemoryReplayCache::shared())
+    }
 
-        let entry = match self.accumulator_dlt.borrow().get(issuer) {
-            None => { return Err(format!("Could not find issuer {issuer} in DLTSim")) }
-            Some(entry) => { entry.clone() }
+    /// Creates a verifier sharing the nonces of the answered presentation requests with other
+    /// verifiers, e.g. the replicas of the same service.
+    ///
+    /// # Arguments
+    /// * `registry` - Registry the public material of the issuers is resolved from.
+    /// * `workers` - Number of worker threads, 0 to use one for each available CPU.
+    /// * `replay_cache` - Cache of the nonces already used.
+    ///
+    /// # Returns
+    /// A result wrapping the verifier or the error, if the worker pool cannot be built.
+    pub fn with_replay_cache(registry: Arc<dyn VerifiableDataRegistry>, workers: usize, replay_cache: Arc<dyn ReplayCache>) -> Result<Self, DelegationError> {
+        let pool = match ThreadPoolBuilder::new().num_threads(workers).build() {
+            Ok(pool) => pool,
+            Err(e) => { return Err(DelegationError::WorkerPool(e)) }
         };
+        Ok(OurVerifier {
+            registry, accumulator_params: RwLock::new(HashMap::new()), pool, replay_cache,
+            allowed_algorithms: SignatureAlgorithm::ALL.to_vec(), clock: SystemClock::shared(), leeway: Duration::ZERO
+        })
+    }
 
-        let accumulator_value = delegation.accumulator_value().clone();
-        let metadata_witnesses = delegation.metadata_witnesses().clone();
-        let metadata = vec![ delegation.delegatee_id().clone(), delegation.iat().clone(), delegation.exp().clone() ];
-        let permission_witnesses = delegation.permission_witnesses().clone();
+    /// Restricts the algorithms accepted for the signatures of the presentations. Every supported
+    /// algorithm is accepted by default.
+    pub fn set_allowed_algorithms(&mut self, allowed_algorithms: Vec<SignatureAlgorithm>) {
+        self.allowed_algorithms = allowed_algorithms;
+    }
 
-        let delegator_av = AccumulatorVerifier::new(accumulator_value, entry.public_key, entry.setup_params)?;
-        delegator_av.verify_accumulator_witnesses(metadata_witnesses, metadata, parallel)?;
-        delegator_av.verify_accumulator_witnesses(permission_witnesses, permissions.clone(), parallel)?;
+    /// Sets the clock giving the time of the verifications. The system clock is used by default.
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
 
-        Ok(())
+    /// Sets the tolerated skew between the clocks of the issuers and of this verifier, none by
+    /// default.
+    pub fn set_leeway(&mut self, leeway: Duration) {
+        self.leeway = leeway;
     }
 
-    pub fn verify_verifiable_presentation(&self, presenter_id: String, signed_jwt: String, parallel: bool) -> Result<(), String>{
+    fn accumulator_params(&self, issuer: &String) -> Result<AccumulatorParamsEntry<E>, DelegationError> {
+        match self.accumulator_params.read() {
+            Ok(accumulator_params) => {
+                if let Some(entry) = accumulator_params.get(issuer) {
+                    return Ok(entry.clone());
+                }
+            }
+            Err(_) => { return Err(DelegationError::LockPoisoned) }
+        };
 
-        let ecc_pk = match self.verification_dlt.borrow().get(&presenter_id) {
-            None => { return Err(format!("Could not find presenter {presenter_id} in DLTSim")) }
-            Some(ecc_pk) => { ecc_pk.clone() }
+        let entry = match self.registry.resolve_accumulator_params(issuer)? {
+            None => { return Err(DelegationError::IssuerNotFound { id: issuer.clone() }) }
+            Some(encoded_entry) => { AccumulatorParamsEntry::<E>::decode(&encoded_entry)? }
         };
+        match self.accumulator_params.write() {
+            Ok(mut accumulator_params) => { accumulator_params.insert(issuer.clone(), entry.clone()); }
+            Err(_) => { return Err(DelegationError::LockPoisoned) }
+        };
+        Ok(entry)
+    }
 
-        let vp: VerifiablePresentation<OurDelegationCredential> =
-            VerifiablePresentation::<OurDelegationCredential>::from_signed_jwt(signed_jwt, &ecc_pk)?;
-        let dc = vp.credential();
+    // Verifies a single delegation of the chain. When a batch is given, the membership checks are
+    // added to it instead of being verified right away.
+    fn verify_delegation<D: OurDelegation>(&self, delegation: &D, issuer: &String, permissions: &[Permission], now_ns: u128,
+                                           mode: VerificationMode, batch: Option<&mut BatchVerifier<E>>) -> Result<(), DelegationError> {
 
-        let permissions = dc.permissions().iter().map(|s| s.clone()).collect::<Vec<String>>();
+        verify_timings(now_ns, delegation.iat(), delegation.exp(), self.leeway)?;
 
-        // Get now timestamp and convert it to nanoseconds
-        let now: Duration = match SystemTime::now().duration_since(UNIX_EPOCH) {
-            Ok(duration) => duration,
-            Err(e) => return Err(format!("Error encountered in computing issuance time: {e}")),
+        // The accumulator value must be the latest one published by the issuer, otherwise some
+        // elements have been removed from it after the issuance and the delegation is revoked
+        let revocation_key = delegation.revocation_key(issuer);
+        let latest_accumulator_value = match self.registry.resolve_accumulator_value(&revocation_key)? {
+            None => { return Err(DelegationError::DelegationNotPublished { key: revocation_key }) }
+            Some(entry) => { entry.accumulator_value }
         };
-        let now_ns = now.as_nanos();
+        if *delegation.accumulator_value() != latest_accumulator_value {
+            return Err(DelegationError::Revoked { key: revocation_key });
+        }
+
+        let entry = self.accumulator_params(issuer)?;
+
+        let accumulator_value = delegation.accumulator_value().clone();
+        let metadata = delegation.metadata_elements(issuer);
+        let permissions = delegation.permission_elements(issuer, permissions);
+
+        let delegator_av = AccumulatorVerifier::new(accumulator_value, entry.public_key, entry.setup_params)?;
+        match batch {
+            Some(batch) => {
+                delegator_av.add_to_batch(batch, delegation.metadata_witnesses(), &metadata)?;
+                delegator_av.add_to_batch(batch, delegation.permission_witnesses(), &permissions)?;
+            }
+            None => {
+                delegator_av.verify_accumulator_witnesses(delegation.metadata_witnesses(), &metadata, mode)?;
+                delegator_av.verify_accumulator_witnesses(delegation.permission_witnesses(), &permissions, mode)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn verify_hierarchy(&self, issuer: &String, dc: &OurDelegationCredential, now_ns: u128, mode: VerificationMode,
+                        mut batch: Option<&mut BatchVerifier<E>>) -> Result<(), DelegationError> {
+
+        let permissions = dc.permissions().to_vec();
 
         // Assert:
         //  - the hierarchy is valid by using each permission and metadata
@@ -67,24 +166,188 @@ impl<E: Pairing> OurVerifier<E> {
         //  - every timing constraint is respected
         let hierarchy = dc.hierarchy();
         let mut previous: &String;
-        let mut current: &String = vp.issuer();
-        if hierarchy.len() > 0 {
-            for delegator in hierarchy.iter().rev() {
-                previous = delegator.delegatee_id();
-                if previous != current {
-                    return Err(format!("Previous delegator {previous} does not match current delegatee {current}"));
+        let mut current: &String = issuer;
+        for (index, delegator) in hierarchy.iter().enumerate().rev() {
+            previous = delegator.delegatee_id();
+            if previous != current {
+                return Err(DelegationError::DelegatorMismatch { delegatee: previous.clone(), issuer: current.clone() }.at_delegator(index));
+            }
+            current = delegator.id();
+        }
+
+        // Every level must only prove permissions implied by the ones of the level above, the
+        // delegators proving the permissions of the credential trivially doing so
+        let levels: Vec<&[Permission]> = hierarchy.iter()
+            .map(|delegator| delegator.proven_permissions(&permissions))
+            .chain(std::iter::once(permissions.as_slice()))
+            .collect();
+        for (index, pair) in levels.windows(2).enumerate() {
+            let (granted, delegated) = (pair[0], pair[1]);
+            for permission in delegated.iter() {
+                if !granted.iter().any(|granted| granted.implies(permission)) {
+                    return Err(DelegationError::PermissionNotGranted { permission: permission.to_string() }.at_delegator(index + 1));
                 }
+            }
+        }
 
-                self.verify_delegation(delegator, &previous, &permissions, now_ns, parallel)?;
-                current = delegator.id();
+        // The credential at level i of the chain allows its maximum depth of delegations after
+        // it, so none can be deeper than the hierarchy of the presented credential
+        let depth = hierarchy.len();
+        let max_depths = hierarchy.iter().map(|delegator| delegator.max_depth()).chain(std::iter::once(dc.max_depth()));
+        for (index, max_depth) in max_depths.enumerate() {
+            if let Some(max_depth) = max_depth.map(|max_depth| index + max_depth) && depth > max_depth {
+                return Err(DelegationError::DepthExceeded { depth, max_depth }.at_delegator(max_depth + 1));
             }
         }
-        self.verify_delegation(dc, &vp.issuer(), &permissions, now_ns, parallel)?;
 
-        // TODO: generalization of credential, not only DelegationCredential
+        // Every level must keep the caveats of the level above, or narrow them
+        let caveats: Vec<_> = hierarchy.iter().map(|delegator| delegator.caveats()).chain(std::iter::once(dc.caveats())).collect();
+        for (index, pair) in caveats.windows(2).enumerate() {
+            let (restricted, delegated) = (pair[0], pair[1]);
+            if let Some(caveat) = restricted.iter().find(|caveat| !delegated.iter().any(|delegated| delegated.is_within(caveat))) {
+                return Err(DelegationError::CaveatLoosened { caveat: caveat.to_string() }.at_delegator(index + 1));
+            }
+        }
+
+        if mode == VerificationMode::Parallel && batch.is_none() {
+            // Every level of the hierarchy is verified by the workers of the pool, which stop at the
+            // first failure
+            let (hierarchy_result, leaf_result) = rayon::join(
+                || hierarchy.par_iter().enumerate().try_for_each(|(index, delegator)| {
+                    self.verify_delegation(delegator, delegator.id(), delegator.proven_permissions(&permissions), now_ns, mode, None)
+                        .map_err(|e| e.at_delegator(index))
+                }),
+                || self.verify_delegation(dc, issuer, &permissions, now_ns, mode, None)
+                    .map_err(|e| e.at_delegator(hierarchy.len()))
+            );
+            hierarchy_result?;
+            return leaf_result;
+        }
+
+        for (index, delegator) in hierarchy.iter().enumerate().rev() {
+            // The delegator's accumulator was generated by the one who issued its credential
+            self.verify_delegation(delegator, delegator.id(), delegator.proven_permissions(&permissions), now_ns, mode, batch.as_deref_mut())
+                .map_err(|e| e.at_delegator(index))?;
+        }
+        self.verify_delegation(dc, issuer, &permissions, now_ns, mode, batch)
+            .map_err(|e| e.at_delegator(hierarchy.len()))?;
 
         Ok(())
     }
+
+    // Evaluates the caveats of every level of the chain, from the root down, against the request
+    fn verify_caveats(dc: &OurDelegationCredential, now_ns: u128, context: &RequestContext) -> Result<(), DelegationError> {
+        let caveats = dc.hierarchy().iter().map(|delegator| delegator.caveats()).chain(std::iter::once(dc.caveats()));
+        for (index, caveats) in caveats.enumerate() {
+            if let Some(caveat) = caveats.iter().find(|caveat| !caveat.is_satisfied(now_ns, context)) {
+                return Err(DelegationError::CaveatNotSatisfied { caveat: caveat.to_string() }.at_delegator(index));
+            }
+        }
+        Ok(())
+    }
+
+    fn decode_presentation(&self, presenter_id: String, signed_jwt: String) -> Result<VerifiablePresentation<OurDelegationCredential>, DelegationError> {
+
+        let ecc_pk = match self.registry.resolve_key(KeyPurpose::Verification, &presenter_id)? {
+            None => { return Err(DelegationError::PresenterNotFound { id: presenter_id }) }
+            Some(ecc_pk) => { ecc_pk }
+        };
+
+        VerifiablePresentation::<OurDelegationCredential>::from_signed_jwt(signed_jwt, &ecc_pk, &self.allowed_algorithms)
+    }
+
+    fn verify_presentation(&self, vp: &VerifiablePresentation<OurDelegationCredential>, now_ns: u128, context: &RequestContext,
+                           mode: VerificationMode) -> Result<(), DelegationError> {
+        let dc = vp.credential();
+
+        // The envelope belongs to the last delegation of the chain
+        verify_validity_period(now_ns, vp.valid_from(), vp.valid_until(), dc.iat(), dc.exp(), self.leeway)
+            .map_err(|e| e.at_delegator(dc.hierarchy().len()))?;
+
+        if mode == VerificationMode::Batched {
+            // The memberships of the whole hierarchy are checked with a single multi-pairing
+            let mut batch = BatchVerifier::new();
+            self.verify_hierarchy(vp.issuer(), dc, now_ns, mode, Some(&mut batch))?;
+            if batch.verify().is_ok() {
+                return Self::verify_caveats(dc, now_ns, context);
+            }
+            // Check every delegation on its own to report the offending one
+            self.verify_hierarchy(vp.issuer(), dc, now_ns, VerificationMode::Sequential, None)?;
+            return Err(DelegationError::BatchNotVerified);
+        }
+        if mode == VerificationMode::Parallel {
+            self.pool.install(|| self.verify_hierarchy(vp.issuer(), dc, now_ns, mode, None))?;
+        } else {
+            self.verify_hierarchy(vp.issuer(), dc, now_ns, mode, None)?;
+        }
+
+        // TODO: generalization of credential, not only DelegationCredential
+
+        // The caveats are only evaluated once the chain carrying them is known to be valid
+        Self::verify_caveats(dc, now_ns, context)
+    }
+
+    /// Verifies a presentation made without a request context, which only satisfies the caveats
+    /// on the time of the day.
+    pub fn verify_verifiable_presentation(&self, presenter_id: String, signed_jwt: String, mode: VerificationMode) -> Result<(), DelegationError>{
+        self.verify_verifiable_presentation_in_context(presenter_id, signed_jwt, &RequestContext::default(), mode)
+    }
+
+    /// Verifies a presentation and evaluates the caveats of every delegation of its chain against
+    /// the context of the request it is presented for.
+    ///
+    /// # Arguments
+    /// * `presenter_id` - Id of the entity that signed the presentation.
+    /// * `signed_jwt` - Presentation signed by the presenter.
+    /// * `context` - Context of the request, e.g. the address of the client.
+    /// * `mode` - How the membership witnesses are verified.
+    ///
+    /// # Returns
+    /// The result of the verification or the error, if it occurs. A caveat that is not satisfied is
+    /// reported together with the index of the delegation carrying it.
+    pub fn verify_verifiable_presentation_in_context(&self, presenter_id: String, signed_jwt: String, context: &RequestContext,
+                                                     mode: VerificationMode) -> Result<(), DelegationError> {
+        let vp = self.decode_presentation(presenter_id, signed_jwt)?;
+        self.verify_presentation(&vp, self.clock.now()?, context, mode)
+    }
+
+    /// Verifies a presentation bound to its holder. Besides the delegation chain, the presentation
+    /// must be signed by the subject of the delegation credential and answer a request of this
+    /// verifier that has neither expired nor been answered yet, so that it cannot be replayed.
+    ///
+    /// # Arguments
+    /// * `presenter_id` - Id of the entity that signed the presentation.
+    /// * `signed_jwt` - Presentation signed by the presenter.
+    /// * `request` - Presentation request previously sent to the presenter.
+    /// * `mode` - How the membership witnesses are verified.
+    ///
+    /// # Returns
+    /// The result of the verification or the error, if it occurs.
+    pub fn verify_bound_verifiable_presentation(&self, presenter_id: String, signed_jwt: String, request: &PresentationRequest,
+                                                mode: VerificationMode) -> Result<(), DelegationError> {
+        self.verify_bound_verifiable_presentation_in_context(presenter_id, signed_jwt, request, &RequestContext::default(), mode)
+    }
+
+    /// Verifies a presentation bound to its holder and evaluates the caveats of its chain against
+    /// the context of the request.
+    ///
+    /// See `verify_bound_verifiable_presentation` and `verify_verifiable_presentation_in_context`
+    /// for the arguments.
+    pub fn verify_bound_verifiable_presentation_in_context(&self, presenter_id: String, signed_jwt: String, request: &PresentationRequest,
+                                                           context: &RequestContext, mode: VerificationMode) -> Result<(), DelegationError> {
+        let vp = self.decode_presentation(presenter_id.clone(), signed_jwt)?;
+
+        let subject = vp.credential().delegatee_id();
+        if presenter_id != *subject {
+            return Err(DelegationError::PresenterMismatch { presenter: presenter_id, subject: subject.clone() });
+        }
+
+        let now_ns = self.clock.now()?;
+        verify_presentation_request(now_ns, &vp, request)?;
+        self.verify_presentation(&vp, now_ns, context, mode)?;
+
+        consume_nonce(now_ns, request, self.replay_cache.as_ref())
+    }
 }
 
 #[cfg(test)]
@@ -92,69 +355,743 @@ mod tests {
     use super::*;
     use ark_bn254::Bn254;
     use std::time::Duration;
-    use josekit::jwk::Jwk;
-    use crate::delegation::entities::dtl_sim::new_dlt_sim;
+    use std::str::FromStr;
+    use crate::delegation::registry::in_memory_registry::InMemoryRegistry;
+    use std::thread;
+    use crate::delegation::entities::ours::our_holder::OurHolder;
     use crate::delegation::entities::ours::our_issuer::OurIssuer;
+    use crate::delegation::credentials::verifiable_credential::VerifiableCredential;
+    use crate::delegation::accumulators::accumulator_element::ElementEncoding;
+    use crate::delegation::clock::fixed_clock::FixedClock;
+    use crate::delegation::clock::offset_clock::OffsetClock;
+    use crate::delegation::credentials::caveat::Caveat;
+    use crate::delegation::accumulators::accumulator_update::AccumulatorUpdate;
 
     #[test]
-    fn verify_vp() -> Result<(), String> {
+    fn verify_vp() -> Result<(), Box<dyn std::error::Error>> {
         type Curve = Bn254;
-        let accumulator_dlt: DLTSim<DLTSimAccEntry<Curve>> = new_dlt_sim();
-        let verification_dlt: DLTSim<Jwk> = new_dlt_sim();
+        let registry = InMemoryRegistry::shared();
 
         let id = String::from("https://vc.example/delegators/d0");
         let previous_vc = None;
-        let issuer: OurIssuer<Curve> = OurIssuer::new(id, accumulator_dlt.clone(), verification_dlt.clone())?;
+        let issuer: OurIssuer<Curve> = OurIssuer::new(id, registry.clone())?;
         let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
         let credential_id = String::from("http://delegation.example/credentials/1337");
         let valid_from = String::from("2026-01-01T00:00:00Z");
         let delegatee_id = String::from("https://vc.example/delegators/d1");
         let validity_period: Duration = Duration::new(3600, 0);
-        let permissions: Vec<String> = vec![String::from("https://vc.example/resources/r1:p0"), String::from("https://vc.example/resources/r1:p1"), String::from("https://vc.example/resources/r1:p2")];
+        let permissions: Vec<Permission> = vec![Permission::from_str("https://vc.example/resources/r1:p0")?, Permission::from_str("https://vc.example/resources/r1:p1")?, Permission::from_str("https://vc.example/resources/r1:p2")?];
         let vc = issuer.issue_delegation_verifiable_credential(context, credential_id, valid_from, delegatee_id, validity_period, permissions, previous_vc)?;
 
         let id = String::from("https://vc.example/delegators/d1");
         let previous_vc = Some(vc);
-        let issuer: OurIssuer<Bn254> = OurIssuer::new(id, accumulator_dlt.clone(), verification_dlt.clone())?;
+        let issuer: OurIssuer<Bn254> = OurIssuer::new(id, registry.clone())?;
         let context: Vec<String> = vec![ String::from("https://www.w3.org/ns/credentials/v2") ];
         let credential_id = String::from("http://delegation.example/credentials/1338");
         let valid_from =  String::from("2026-01-01T00:00:00Z");
         let delegatee_id = String::from("https://vc.example/delegators/d2");
         let validity_period: Duration = Duration::new(3600, 0);
-        let permissions: Vec<String> = vec![ String::from("https://vc.example/resources/r1:p0"), String::from("https://vc.example/resources/r1:p1") ];
+        let permissions: Vec<Permission> = vec![ Permission::from_str("https://vc.example/resources/r1:p0")?, Permission::from_str("https://vc.example/resources/r1:p1")? ];
         let vc = issuer.issue_delegation_verifiable_credential(context, credential_id, valid_from, delegatee_id, validity_period, permissions, previous_vc)?;
 
         let id = String::from("https://vc.example/delegators/d2");
         let previous_vc = Some(vc);
-        let issuer: OurIssuer<Bn254> = OurIssuer::new(id, accumulator_dlt.clone(), verification_dlt.clone())?;
+        let issuer: OurIssuer<Bn254> = OurIssuer::new(id, registry.clone())?;
         let context: Vec<String> = vec![ String::from("https://www.w3.org/ns/credentials/v2") ];
         let credential_id = String::from("http://delegation.example/credentials/1339");
         let valid_from =  String::from("2026-01-01T00:00:00Z");
         let delegatee_id = String::from("https://vc.example/delegators/d3");
         let validity_period: Duration = Duration::new(3600, 0);
-        let permissions: Vec<String> = vec![ String::from("https://vc.example/resources/r1:p0"), String::from("https://vc.example/resources/r1:p1") ];
+        let permissions: Vec<Permission> = vec![ Permission::from_str("https://vc.example/resources/r1:p0")?, Permission::from_str("https://vc.example/resources/r1:p1")? ];
         let vc = issuer.issue_delegation_verifiable_credential(context, credential_id, valid_from, delegatee_id, validity_period, permissions, previous_vc)?;
 
         let id = String::from("https://vc.example/delegators/d3");
         let previous_vc = Some(vc);
-        let issuer: OurIssuer<Bn254> = OurIssuer::new(id, accumulator_dlt.clone(), verification_dlt.clone())?;
+        let issuer: OurIssuer<Bn254> = OurIssuer::new(id, registry.clone())?;
         let context: Vec<String> = vec![ String::from("https://www.w3.org/ns/credentials/v2") ];
         let credential_id = String::from("http://delegation.example/credentials/1340");
         let valid_from = String::from("2026-01-01T00:00:00Z");
         let delegatee_id = String::from("https://vc.example/delegators/d4");
         let validity_period: Duration = Duration::new(3600, 0);
-        let permissions: Vec<String> = vec![ String::from("https://vc.example/resources/r1:p0") ];
+        let permissions: Vec<Permission> = vec![ Permission::from_str("https://vc.example/resources/r1:p0")? ];
         let vc = issuer.issue_delegation_verifiable_credential(context, credential_id, valid_from, delegatee_id.clone(), validity_period, permissions, previous_vc)?;
 
         let id = delegatee_id.clone();
-        let issuer: OurIssuer<Bn254> = OurIssuer::new(id.clone(), accumulator_dlt.clone(), verification_dlt.clone())?;
+        let issuer: OurIssuer<Bn254> = OurIssuer::new(id.clone(), registry.clone())?;
 
-        let disclosed_permissions: Vec<String> = vec![String::from("https://vc.example/resources/r1:p0")];
+        let disclosed_permissions: Vec<Permission> = vec![Permission::from_str("https://vc.example/resources/r1:p0")?];
         let signed_vp = issuer.issue_delegation_verifiable_presentation(vc, disclosed_permissions)?;
 
-        let verifier = OurVerifier::new(accumulator_dlt, verification_dlt)?;
-        verifier.verify_verifiable_presentation(id, signed_vp, true)?;
+        let verifier: OurVerifier<Curve> = OurVerifier::new(registry)?;
+        verifier.verify_verifiable_presentation(id.clone(), signed_vp.clone(), VerificationMode::Parallel)?;
+        verifier.verify_verifiable_presentation(id, signed_vp, VerificationMode::Batched)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn verify_tampered_vp() -> Result<(), Box<dyn std::error::Error>> {
+        type Curve = Bn254;
+        let registry = InMemoryRegistry::shared();
+
+        let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
+        let valid_from = String::from("2026-01-01T00:00:00Z");
+        let validity_period: Duration = Duration::new(3600, 0);
+        let permissions: Vec<Permission> = vec![Permission::from_str("https://vc.example/resources/r1:p0")?, Permission::from_str("https://vc.example/resources/r1:p1")?];
+
+        let root: OurIssuer<Curve> = OurIssuer::new(String::from("https://vc.example/delegators/d0"), registry.clone())?;
+        let delegator_id = String::from("https://vc.example/delegators/d1");
+        let root_vc = root.issue_delegation_verifiable_credential(context.clone(), String::from("http://delegation.example/credentials/1337"), valid_from.clone(), delegator_id.clone(), validity_period, permissions.clone(), None)?;
+
+        let delegator: OurIssuer<Curve> = OurIssuer::new(delegator_id, registry.clone())?;
+        let delegatee_id = String::from("https://vc.example/delegators/d2");
+        let mut vc = delegator.issue_delegation_verifiable_credential(context, String::from("http://delegation.example/credentials/1338"), valid_from, delegatee_id.clone(), validity_period, permissions.clone(), Some(root_vc))?;
+
+        // Witnesses of the root delegation no longer match their permissions
+        vc.mut_credential().mut_hierarchy()[0].mut_permission_witnesses().swap(0, 1);
+
+        let delegatee: OurIssuer<Curve> = OurIssuer::new(delegatee_id.clone(), registry.clone())?;
+        let signed_vp = delegatee.issue_delegation_verifiable_presentation(vc, permissions)?;
+
+        let verifier: OurVerifier<Curve> = OurVerifier::with_workers(registry, 2)?;
+        for mode in [VerificationMode::Sequential, VerificationMode::Parallel, VerificationMode::Batched] {
+            match verifier.verify_verifiable_presentation(delegatee_id.clone(), signed_vp.clone(), mode) {
+                Err(DelegationError::Delegator { index: 0, source }) if matches!(*source, DelegationError::MembershipNotVerified { .. }) => {}
+                other => { return Err(format!("Expected invalid membership in delegation 0 in {mode:?} mode, got {other:?}").into()); }
+            }
+        }
 
         Ok(())
     }
+
+    #[test]
+    fn verify_vp_with_legacy_elements() -> Result<(), Box<dyn std::error::Error>> {
+        type Curve = Bn254;
+        let registry = InMemoryRegistry::shared();
+
+        let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
+        let valid_from = String::from("2026-01-01T00:00:00Z");
+        let validity_period: Duration = Duration::new(3600, 0);
+        let permissions: Vec<Permission> = vec![Permission::from_str("p0")?, Permission::from_str("p1")?];
+
+        // Credentials issued before the typed encoding, with bare permissions, remain verifiable
+        // within a typed chain
+        let mut root: OurIssuer<Curve> = OurIssuer::new(String::from("https://vc.example/delegators/d0"), registry.clone())?;
+        root.set_element_encoding(ElementEncoding::Legacy);
+        let delegator_id = String::from("https://vc.example/delegators/d1");
+        let root_vc = root.issue_delegation_verifiable_credential(context.clone(), String::from("http://delegation.example/credentials/1337"), valid_from.clone(), delegator_id.clone(), validity_period, permissions.clone(), None)?;
+
+        let delegator: OurIssuer<Curve> = OurIssuer::new(delegator_id, registry.clone())?;
+        let delegatee_id = String::from("https://vc.example/delegators/d2");
+        let vc = delegator.issue_delegation_verifiable_credential(context, String::from("http://delegation.example/credentials/1338"), valid_from, delegatee_id.clone(), validity_period, permissions.clone(), Some(root_vc))?;
+        if vc.credential().hierarchy()[0].element_encoding() != ElementEncoding::Legacy || vc.credential().element_encoding() != ElementEncoding::Typed {
+            return Err(String::from("Unexpected element encodings in the chain").into());
+        }
+
+        let delegatee: OurIssuer<Curve> = OurIssuer::new(delegatee_id.clone(), registry.clone())?;
+        let signed_vp = delegatee.issue_delegation_verifiable_presentation(vc, permissions)?;
+
+        let verifier: OurVerifier<Curve> = OurVerifier::new(registry)?;
+        for mode in [VerificationMode::Sequential, VerificationMode::Batched] {
+            verifier.verify_verifiable_presentation(delegatee_id.clone(), signed_vp.clone(), mode)?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn reject_elements_in_wrong_role() -> Result<(), Box<dyn std::error::Error>> {
+        type Curve = Bn254;
+        let registry = InMemoryRegistry::shared();
+
+        let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
+        let valid_from = String::from("2026-01-01T00:00:00Z");
+        let validity_period: Duration = Duration::new(3600, 0);
+        let delegatee_id = String::from("https://vc.example/delegators/d1");
+        // A permission that happens to be equal to the delegatee id
+        let permissions: Vec<Permission> = vec![Permission::from_str(&delegatee_id)?];
+
+        // Untagged elements collide in the accumulator
+        let mut root: OurIssuer<Curve> = OurIssuer::new(String::from("https://vc.example/delegators/d0"), registry.clone())?;
+        root.set_element_encoding(ElementEncoding::Legacy);
+        match root.issue_delegation_verifiable_credential(context.clone(), String::from("http://delegation.example/credentials/1337"), valid_from.clone(), delegatee_id.clone(), validity_period, permissions.clone(), None) {
+            Err(DelegationError::Accumulator(_)) => {}
+            other => { return Err(format!("Expected colliding legacy elements, got {:?}", other.map(|vc| vc.id().clone())).into()); }
+        }
+
+        root.set_element_encoding(ElementEncoding::Typed);
+        let mut vc = root.issue_delegation_verifiable_credential(context, String::from("http://delegation.example/credentials/1338"), valid_from, delegatee_id.clone(), validity_period, permissions.clone(), None)?;
+
+        // Present the witness of the permission as the one of the delegatee id
+        let dc = vc.mut_credential();
+        let mut metadata_witnesses = dc.metadata_witnesses().clone();
+        metadata_witnesses[0] = dc.permission_witnesses()[0].clone();
+        let (accumulator_value, permission_witnesses) = (dc.accumulator_value().clone(), dc.permission_witnesses().clone());
+        dc.set_accumulator(accumulator_value, metadata_witnesses, permission_witnesses);
+
+        let delegatee: OurIssuer<Curve> = OurIssuer::new(delegatee_id.clone(), registry.clone())?;
+        let signed_vp = delegatee.issue_delegation_verifiable_presentation(vc, permissions)?;
+
+        let verifier: OurVerifier<Curve> = OurVerifier::new(registry)?;
+        match verifier.verify_verifiable_presentation(delegatee_id, signed_vp, VerificationMode::Sequential) {
+            Err(DelegationError::Delegator { index: 0, source }) if matches!(*source, DelegationError::MembershipNotVerified { .. }) => Ok(()),
+            other => Err(format!("Expected invalid membership of the delegatee id, got {other:?}").into()),
+        }
+    }
+
+    #[test]
+    fn verify_vp_validity_period() -> Result<(), Box<dyn std::error::Error>> {
+        type Curve = Bn254;
+        let registry = InMemoryRegistry::shared();
+
+        let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
+        let permissions: Vec<Permission> = vec![Permission::from_str("https://vc.example/resources/r1:p0")?];
+        let root: OurIssuer<Curve> = OurIssuer::new(String::from("https://vc.example/delegators/d0"), registry.clone())?;
+        let delegatee_id = String::from("https://vc.example/delegators/d1");
+        let delegatee: OurIssuer<Curve> = OurIssuer::new(delegatee_id.clone(), registry.clone())?;
+        let verifier: OurVerifier<Curve> = OurVerifier::new(registry)?;
+
+        match root.issue_delegation_verifiable_credential(context.clone(), String::from("http://delegation.example/credentials/1336"), String::from("01/01/2026"), delegatee_id.clone(), Duration::new(3600, 0), permissions.clone(), None) {
+            Err(DelegationError::InvalidDateTime { .. }) => {}
+            other => { return Err(format!("Expected invalid validFrom, got {:?}", other.map(|vc| vc.id().clone())).into()); }
+        }
+
+        // The credential only becomes valid at its validFrom date
+        let validity_period: Duration = Duration::new(200 * 365 * 24 * 3600, 0);
+        let vc = root.issue_delegation_verifiable_credential(context.clone(), String::from("http://delegation.example/credentials/1337"), String::from("2100-01-01T00:00:00Z"), delegatee_id.clone(), validity_period, permissions.clone(), None)?;
+        let signed_vp = delegatee.issue_delegation_verifiable_presentation(vc, permissions.clone())?;
+        match verifier.verify_verifiable_presentation(delegatee_id.clone(), signed_vp, VerificationMode::Sequential) {
+            Err(DelegationError::Delegator { index: 0, source }) if matches!(*source, DelegationError::BeforeValidFrom { .. }) => {}
+            other => { return Err(format!("Expected credential not valid yet, got {other:?}").into()); }
+        }
+
+        // The envelope cannot outlive the accumulated expiration time
+        let vc = root.issue_delegation_verifiable_credential(context, String::from("http://delegation.example/credentials/1338"), String::from("2026-01-01T00:00:00Z"), delegatee_id.clone(), Duration::new(3600, 0), permissions.clone(), None)?;
+        let mut vc_value = serde_json::to_value(&vc)?;
+        vc_value["validUntil"] = serde_json::Value::String(String::from("2999-01-01T00:00:00Z"));
+        let vc: VerifiableCredential<OurDelegationCredential> = serde_json::from_value(vc_value)?;
+        let signed_vp = delegatee.issue_delegation_verifiable_presentation(vc, permissions)?;
+        match verifier.verify_verifiable_presentation(delegatee_id, signed_vp, VerificationMode::Sequential) {
+            Err(DelegationError::Delegator { index: 0, source }) if matches!(*source, DelegationError::InconsistentValidityPeriod { .. }) => Ok(()),
+            other => Err(format!("Expected inconsistent validity period, got {other:?}").into()),
+        }
+    }
+
+    #[test]
+    fn verify_vp_with_clock_skew() -> Result<(), Box<dyn std::error::Error>> {
+        type Curve = Bn254;
+        let registry = InMemoryRegistry::shared();
+
+        let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
+        let valid_from = String::from("2026-01-01T00:00:00Z");
+        let validity_period: Duration = Duration::new(3600, 0);
+        let permissions: Vec<Permission> = vec![Permission::from_str("https://vc.example/resources/r1:p0")?];
+
+        let issuance: u128 = 1_780_000_000_000_000_000;
+        let clock = Arc::new(FixedClock::new(issuance));
+        let mut root: OurIssuer<Curve> = OurIssuer::new(String::from("https://vc.example/delegators/d0"), registry.clone())?;
+        root.set_clock(clock.clone());
+        let delegatee_id = String::from("https://vc.example/delegators/d1");
+        let vc = root.issue_delegation_verifiable_credential(context, String::from("http://delegation.example/credentials/1337"), valid_from, delegatee_id.clone(), validity_period, permissions.clone(), None)?;
+        if *vc.credential().iat() != issuance.to_string() {
+            return Err(format!("Credential not issued at the time of the clock [{}]", vc.credential().iat()).into());
+        }
+
+        let delegatee: OurIssuer<Curve> = OurIssuer::new(delegatee_id.clone(), registry.clone())?;
+        let signed_vp = delegatee.issue_delegation_verifiable_presentation(vc, permissions)?;
+
+        // The clock of the verifier runs five seconds behind the one of the issuer
+        let mut verifier: OurVerifier<Curve> = OurVerifier::new(registry)?;
+        verifier.set_clock(Arc::new(OffsetClock::behind(clock.clone(), Duration::new(5, 0))));
+        match verifier.verify_verifiable_presentation(delegatee_id.clone(), signed_vp.clone(), VerificationMode::Sequential) {
+            Err(DelegationError::Delegator { index: 0, source }) if matches!(*source, DelegationError::NotYetValid { .. }) => {}
+            other => { return Err(format!("Expected credential not valid yet, got {other:?}").into()); }
+        }
+
+        let leeway = Duration::new(10, 0);
+        verifier.set_leeway(leeway);
+        verifier.verify_verifiable_presentation(delegatee_id.clone(), signed_vp.clone(), VerificationMode::Sequential)?;
+
+        // Still accepted within the leeway after the expiration, rejected right after
+        let expiration = issuance + validity_period.as_nanos() + Duration::new(5, 0).as_nanos();
+        clock.set(expiration + leeway.as_nanos())?;
+        verifier.verify_verifiable_presentation(delegatee_id.clone(), signed_vp.clone(), VerificationMode::Sequential)?;
+
+        // Expirations supplied by the holder cannot overflow with the leeway, nor can the clock
+        let max = u128::MAX.to_string();
+        verify_timings(expiration, &issuance.to_string(), &max, leeway)?;
+        verify_validity_period(u128::MAX, "2026-01-01T00:00:00Z", None, &issuance.to_string(), &max, leeway)?;
+
+        clock.advance(Duration::new(0, 1))?;
+        match verifier.verify_verifiable_presentation(delegatee_id, signed_vp, VerificationMode::Sequential) {
+            Err(DelegationError::Delegator { index: 0, source }) if matches!(*source, DelegationError::AfterValidUntil { .. }) => Ok(()),
+            other => Err(format!("Expected expired credential, got {other:?}").into()),
+        }
+    }
+
+    #[test]
+    fn verify_bound_vp() -> Result<(), Box<dyn std::error::Error>> {
+        type Curve = Bn254;
+        let registry = InMemoryRegistry::shared();
+
+        let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
+        let valid_from = String::from("2026-01-01T00:00:00Z");
+        let validity_period: Duration = Duration::new(3600, 0);
+        let permissions: Vec<Permission> = vec![Permission::from_str("https://vc.example/resources/r1:p0")?];
+
+        let root_id = String::from("https://vc.example/delegators/d0");
+        let root: OurIssuer<Curve> = OurIssuer::new(root_id.clone(), registry.clone())?;
+        let delegatee_id = String::from("https://vc.example/delegators/d1");
+        let vc = root.issue_delegation_verifiable_credential(context, String::from("http://delegation.example/credentials/1337"), valid_from, delegatee_id.clone(), validity_period, permissions.clone(), None)?;
+        let delegatee: OurIssuer<Curve> = OurIssuer::new(delegatee_id.clone(), registry.clone())?;
+
+        let verifier: OurVerifier<Curve> = OurVerifier::new(registry)?;
+        let audience = String::from("https://vc.example/verifiers/v0");
+        let request = PresentationRequest::new(audience.clone(), Duration::new(60, 0))?;
+        let signed_vp = delegatee.issue_bound_delegation_verifiable_presentation(vc.clone(), permissions.clone(), &request)?;
+        verifier.verify_bound_verifiable_presentation(delegatee_id.clone(), signed_vp.clone(), &request, VerificationMode::Sequential)?;
+
+        // The presentation can be neither replayed nor presented for another request
+        match verifier.verify_bound_verifiable_presentation(delegatee_id.clone(), signed_vp.clone(), &request, VerificationMode::Sequential) {
+            Err(DelegationError::NonceReused { .. }) => {}
+            other => { return Err(format!("Expected reused nonce, got {other:?}").into()); }
+        }
+        let other_request = PresentationRequest::new(audience.clone(), Duration::new(60, 0))?;
+        match verifier.verify_bound_verifiable_presentation(delegatee_id.clone(), signed_vp, &other_request, VerificationMode::Sequential) {
+            Err(DelegationError::RequestMismatch { .. }) => {}
+            other => { return Err(format!("Expected request mismatch, got {other:?}").into()); }
+        }
+
+        // Expired requests are rejected
+        let expired_request = PresentationRequest::new(audience, Duration::ZERO)?;
+        let signed_vp = delegatee.issue_bound_delegation_verifiable_presentation(vc.clone(), permissions.clone(), &expired_request)?;
+        match verifier.verify_bound_verifiable_presentation(delegatee_id.clone(), signed_vp, &expired_request, VerificationMode::Sequential) {
+            Err(DelegationError::RequestExpired { .. }) => {}
+            other => { return Err(format!("Expected expired request, got {other:?}").into()); }
+        }
+
+        // Nor can the credential be presented by anyone else than its subject
+        if root.issue_bound_delegation_verifiable_presentation(vc.clone(), permissions.clone(), &other_request).is_ok() {
+            return Err(String::from("Presentation bound to an entity other than the subject").into());
+        }
+        let stolen_vp = root.issue_delegation_verifiable_presentation(vc, permissions)?;
+        match verifier.verify_bound_verifiable_presentation(root_id, stolen_vp, &other_request, VerificationMode::Sequential) {
+            Err(DelegationError::PresenterMismatch { .. }) => Ok(()),
+            other => Err(format!("Expected presenter mismatch, got {other:?}").into()),
+        }
+    }
+
+    #[test]
+    fn verify_vp_with_algorithms() -> Result<(), Box<dyn std::error::Error>> {
+        type Curve = Bn254;
+        let registry = InMemoryRegistry::shared();
+
+        let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
+        let valid_from = String::from("2026-01-01T00:00:00Z");
+        let validity_period: Duration = Duration::new(3600, 0);
+        let permissions: Vec<Permission> = vec![Permission::from_str("https://vc.example/resources/r1:p0")?];
+
+        let root: OurIssuer<Curve> = OurIssuer::new(String::from("https://vc.example/delegators/d0"), registry.clone())?;
+        let mut verifier: OurVerifier<Curve> = OurVerifier::new(registry.clone())?;
+
+        let mut signed_vps = vec![];
+        for (i, algorithm) in SignatureAlgorithm::ALL.iter().enumerate() {
+            let delegatee_id = format!("https://vc.example/delegators/d{}", i + 1);
+            let vc = root.issue_delegation_verifiable_credential(context.clone(), format!("http://delegation.example/credentials/{i}"), valid_from.clone(), delegatee_id.clone(), validity_period, permissions.clone(), None)?;
+
+            let delegatee: OurIssuer<Curve> = OurIssuer::with_signature_algorithm(delegatee_id.clone(), registry.clone(), *algorithm)?;
+            let signed_vp = delegatee.issue_delegation_verifiable_presentation(vc, permissions.clone())?;
+
+            let header = josekit::jwt::decode_header(&signed_vp)?;
+            let key_id = header.claim("kid").and_then(|kid| kid.as_str()).unwrap_or_default();
+            if header.claim("alg").and_then(|alg| alg.as_str()) != Some(algorithm.name()) || !key_id.starts_with(&delegatee_id) {
+                return Err(format!("Unexpected header for {algorithm}: {header:?}").into());
+            }
+
+            verifier.verify_verifiable_presentation(delegatee_id.clone(), signed_vp.clone(), VerificationMode::Sequential)?;
+            signed_vps.push((delegatee_id, signed_vp));
+        }
+
+        // Only the algorithms in the allow-list are accepted
+        verifier.set_allowed_algorithms(vec![SignatureAlgorithm::ES256]);
+        for (i, (delegatee_id, signed_vp)) in signed_vps.into_iter().enumerate() {
+            match (SignatureAlgorithm::ALL[i], verifier.verify_verifiable_presentation(delegatee_id, signed_vp, VerificationMode::Sequential)) {
+                (SignatureAlgorithm::ES256, Ok(())) => {}
+                (_, Err(DelegationError::AlgorithmNotAllowed { .. })) => {}
+                (algorithm, other) => { return Err(format!("Unexpected result for {algorithm}: {other:?}").into()); }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn verify_vp_concurrently() -> Result<(), Box<dyn std::error::Error>> {
+        type Curve = Bn254;
+        assert_send_sync::<OurIssuer<Curve>>();
+        assert_send_sync::<OurVerifier<Curve>>();
+        assert_send_sync::<OurHolder<Curve>>();
+
+        let registry = InMemoryRegistry::shared();
+
+        let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
+        let valid_from = String::from("2026-01-01T00:00:00Z");
+        let validity_period: Duration = Duration::new(3600, 0);
+        let permissions: Vec<Permission> = vec![Permission::from_str("https://vc.example/resources/r1:p0")?, Permission::from_str("https://vc.example/resources/r1:p1")?];
+
+        let root: OurIssuer<Curve> = OurIssuer::new(String::from("https://vc.example/delegators/d0"), registry.clone())?;
+        let delegatee_id = String::from("https://vc.example/delegators/d1");
+        let vc = root.issue_delegation_verifiable_credential(context, String::from("http://delegation.example/credentials/1337"), valid_from, delegatee_id.clone(), validity_period, permissions.clone(), None)?;
+
+        let delegatee: OurIssuer<Curve> = OurIssuer::new(delegatee_id.clone(), registry.clone())?;
+        let signed_vp = delegatee.issue_delegation_verifiable_presentation(vc, permissions)?;
+
+        // A single verifier is shared among several threads, as it would be behind a server
+        let verifier: Arc<OurVerifier<Curve>> = Arc::new(OurVerifier::new(registry)?);
+        let modes = [VerificationMode::Sequential, VerificationMode::Parallel, VerificationMode::Batched];
+        let handles: Vec<_> = (0..4).map(|i| {
+            let verifier = verifier.clone();
+            let presenter_id = delegatee_id.clone();
+            let signed_vp = signed_vp.clone();
+            thread::spawn(move || verifier.verify_verifiable_presentation(presenter_id, signed_vp, modes[i % modes.len()]))
+        }).collect();
+
+        for handle in handles {
+            match handle.join() {
+                Ok(result) => result?,
+                Err(_) => { return Err("Verification thread panicked".into()); }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn verify_vp_with_reordered_permissions() -> Result<(), Box<dyn std::error::Error>> {
+        type Curve = Bn254;
+        let registry = InMemoryRegistry::shared();
+
+        let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
+        let valid_from = String::from("2026-01-01T00:00:00Z");
+        let validity_period: Duration = Duration::new(3600, 0);
+        let permissions: Vec<Permission> = vec![Permission::from_str("https://vc.example/resources/r1:p0")?, Permission::from_str("https://vc.example/resources/r1:p1")?, Permission::from_str("https://vc.example/resources/r1:p2")?];
+        let (p0, p2) = (permissions[0].clone(), permissions[2].clone());
+
+        let root: OurIssuer<Curve> = OurIssuer::new(String::from("https://vc.example/delegators/d0"), registry.clone())?;
+        let root_credential_id = String::from("http://delegation.example/credentials/1337");
+        let delegator_id = String::from("https://vc.example/delegators/d1");
+        let root_vc = root.issue_delegation_verifiable_credential(context.clone(), root_credential_id.clone(), valid_from.clone(), delegator_id.clone(), validity_period, permissions, None)?;
+
+        // A subset delegated in another order than it was granted
+        let delegator: OurIssuer<Curve> = OurIssuer::new(delegator_id, registry.clone())?;
+        let delegatee_id = String::from("https://vc.example/delegators/d2");
+        let vc = delegator.issue_delegation_verifiable_credential(context.clone(), String::from("http://delegation.example/credentials/1338"), valid_from.clone(), delegatee_id.clone(), validity_period, vec![p2.clone(), p0.clone()], Some(root_vc))?;
+
+        // Duplicated permissions are only granted once
+        let delegatee: OurIssuer<Curve> = OurIssuer::new(delegatee_id, registry.clone())?;
+        let last_id = String::from("https://vc.example/delegators/d3");
+        let mut last_vc = delegatee.issue_delegation_verifiable_credential(context, String::from("http://delegation.example/credentials/1339"), valid_from, last_id.clone(), validity_period, vec![p0.clone(), p2.clone(), p0.clone()], Some(vc))?;
+        if *last_vc.credential().permissions() != vec![p0.clone(), p2.clone()] {
+            return Err(format!("Unexpected permissions {:?}", last_vc.credential().permissions()).into());
+        }
+
+        let verifier: OurVerifier<Curve> = OurVerifier::new(registry.clone())?;
+        let last: OurIssuer<Curve> = OurIssuer::new(last_id.clone(), registry.clone())?;
+        for disclosed_permissions in [vec![p2.clone(), p0.clone(), p2.clone()], vec![p2.clone()], vec![p0.clone()]] {
+            let signed_vp = last.issue_delegation_verifiable_presentation(last_vc.clone(), disclosed_permissions)?;
+            for mode in [VerificationMode::Sequential, VerificationMode::Parallel, VerificationMode::Batched] {
+                verifier.verify_verifiable_presentation(last_id.clone(), signed_vp.clone(), mode)?;
+            }
+        }
+
+        // Witnesses of the remaining permission are updated whatever its position in the chain
+        root.revoke_permissions(&root_credential_id, std::slice::from_ref(&p0))?;
+        let holder: OurHolder<Curve> = OurHolder::new(registry);
+        if holder.update_witnesses(&mut last_vc)? != vec![p0] {
+            return Err(String::from("Expected the revoked permission to be removed").into());
+        }
+        let signed_vp = last.issue_delegation_verifiable_presentation(last_vc, vec![p2])?;
+        verifier.verify_verifiable_presentation(last_id, signed_vp, VerificationMode::Batched)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn verify_vp_with_implied_permissions() -> Result<(), Box<dyn std::error::Error>> {
+        type Curve = Bn254;
+        let registry = InMemoryRegistry::shared();
+
+        let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
+        let valid_from = String::from("2026-01-01T00:00:00Z");
+        let validity_period: Duration = Duration::new(3600, 0);
+        let root_permissions: Vec<Permission> = vec![Permission::from_str("https://vc.example/resources/**:write")?, Permission::from_str("https://vc.example/reports/r1:read")?];
+        let permissions: Vec<Permission> = vec![Permission::from_str("https://vc.example/resources/r1:read")?, Permission::from_str("https://vc.example/resources/r2:write")?];
+
+        let root: OurIssuer<Curve> = OurIssuer::new(String::from("https://vc.example/delegators/d0"), registry.clone())?;
+        let root_credential_id = String::from("http://delegation.example/credentials/1337");
+        let delegator_id = String::from("https://vc.example/delegators/d1");
+        let root_vc = root.issue_delegation_verifiable_credential(context.clone(), root_credential_id.clone(), valid_from.clone(), delegator_id.clone(), validity_period, root_permissions, None)?;
+
+        // Permissions below the granted path are delegated, the broader one being disclosed instead
+        // of the unrelated report
+        let delegator: OurIssuer<Curve> = OurIssuer::new(delegator_id.clone(), registry.clone())?;
+        match delegator.issue_delegation_verifiable_credential(context.clone(), String::from("http://delegation.example/credentials/1338"), valid_from.clone(), String::from("https://vc.example/delegators/d2"), validity_period, vec![Permission::from_str("https://vc.example/reports/r1:write")?], Some(root_vc.clone())) {
+            Err(DelegationError::PermissionNotGranted { .. }) => {}
+            other => { return Err(format!("Expected PermissionNotGranted, got {:?}", other.map(|vc| vc.id().clone())).into()); }
+        }
+        let delegatee_id = String::from("https://vc.example/delegators/d2");
+        let vc = delegator.issue_delegation_verifiable_credential(context.clone(), String::from("http://delegation.example/credentials/1338"), valid_from.clone(), delegatee_id.clone(), validity_period, permissions.clone(), Some(root_vc))?;
+        if vc.credential().hierarchy()[0].permissions() != Some(&vec![Permission::from_str("https://vc.example/resources/**:write")?]) {
+            return Err(format!("Unexpected proven permissions {:?}", vc.credential().hierarchy()[0].permissions()).into());
+        }
+
+        // Exact delegations keep proving the permissions of the credential further down the chain
+        let delegatee: OurIssuer<Curve> = OurIssuer::new(delegatee_id.clone(), registry.clone())?;
+        let last_id = String::from("https://vc.example/delegators/d3");
+        let mut last_vc = delegatee.issue_delegation_verifiable_credential(context, String::from("http://delegation.example/credentials/1339"), valid_from, last_id.clone(), validity_period, permissions[..1].to_vec(), Some(vc.clone()))?;
+        if last_vc.credential().hierarchy()[1].permissions().is_some() {
+            return Err(String::from("Exact delegation should prove the permissions of the credential").into());
+        }
+
+        let verifier: OurVerifier<Curve> = OurVerifier::new(registry.clone())?;
+        let last: OurIssuer<Curve> = OurIssuer::new(last_id.clone(), registry.clone())?;
+        let signed_vp = last.issue_delegation_verifiable_presentation(last_vc.clone(), permissions[..1].to_vec())?;
+        let disclosed_vp = delegatee.issue_delegation_verifiable_presentation(vc, permissions[1..].to_vec())?;
+        for mode in [VerificationMode::Sequential, VerificationMode::Parallel, VerificationMode::Batched] {
+            verifier.verify_verifiable_presentation(last_id.clone(), signed_vp.clone(), mode)?;
+            verifier.verify_verifiable_presentation(delegatee_id.clone(), disclosed_vp.clone(), mode)?;
+        }
+
+        let holder: OurHolder<Curve> = OurHolder::new(registry);
+        if !holder.update_witnesses(&mut last_vc)?.is_empty() {
+            return Err(String::from("Permissions removed although nothing was revoked").into());
+        }
+
+        // A delegator proving a grant that does not imply the delegated permissions is rejected
+        let mut tampered_vc = last_vc.clone();
+        tampered_vc.mut_credential().mut_hierarchy()[0].set_permissions(Some(vec![Permission::from_str("https://vc.example/reports/r1:read")?]));
+        let tampered_vp = last.issue_delegation_verifiable_presentation(tampered_vc, permissions[..1].to_vec())?;
+        match verifier.verify_verifiable_presentation(last_id, tampered_vp, VerificationMode::Sequential) {
+            Err(DelegationError::Delegator { index: 1, source }) if matches!(*source, DelegationError::PermissionNotGranted { .. }) => {}
+            other => { return Err(format!("Expected permission not granted at delegation 1, got {other:?}").into()); }
+        }
+
+        // Revoking the broader permission revokes the permissions it implies
+        root.revoke_permissions(&root_credential_id, &[Permission::from_str("https://vc.example/resources/**:write")?])?;
+        match holder.update_witnesses(&mut last_vc) {
+            Err(DelegationError::AllPermissionsRevoked) => Ok(()),
+            other => Err(format!("Expected every permission to be revoked, got {other:?}").into()),
+        }
+    }
+
+    #[test]
+    fn verify_vp_with_max_depth() -> Result<(), Box<dyn std::error::Error>> {
+        type Curve = Bn254;
+        let registry = InMemoryRegistry::shared();
+
+        let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
+        let valid_from = String::from("2026-01-01T00:00:00Z");
+        let validity_period: Duration = Duration::new(3600, 0);
+        let permissions: Vec<Permission> = vec![Permission::from_str("https://vc.example/resources/r1:p0")?];
+
+        // The delegator may pass the permission on once, so its delegatee may not
+        let root: OurIssuer<Curve> = OurIssuer::new(String::from("https://vc.example/delegators/d0"), registry.clone())?;
+        let delegator_id = String::from("https://vc.example/delegators/d1");
+        let root_vc = root.issue_delegation_verifiable_credential_with_max_depth(context.clone(), String::from("http://delegation.example/credentials/1337"), valid_from.clone(), delegator_id.clone(), validity_period, permissions.clone(), None, Some(1))?;
+
+        let delegator: OurIssuer<Curve> = OurIssuer::new(delegator_id, registry.clone())?;
+        let delegatee_id = String::from("https://vc.example/delegators/d2");
+        let vc = delegator.issue_delegation_verifiable_credential_with_max_depth(context.clone(), String::from("http://delegation.example/credentials/1338"), valid_from.clone(), delegatee_id.clone(), validity_period, permissions.clone(), Some(root_vc), Some(5))?;
+        if vc.credential().max_depth() != Some(0) || vc.credential().may_delegate() {
+            return Err(format!("Expected a credential that cannot be delegated, got maximum depth {:?}", vc.credential().max_depth()).into());
+        }
+
+        let delegatee: OurIssuer<Curve> = OurIssuer::new(delegatee_id.clone(), registry.clone())?;
+        match delegatee.issue_delegation_verifiable_credential(context.clone(), String::from("http://delegation.example/credentials/1339"), valid_from.clone(), String::from("https://vc.example/delegators/d3"), validity_period, permissions.clone(), Some(vc.clone())) {
+            Err(DelegationError::NotRedelegable) => {}
+            other => { return Err(format!("Expected a credential that cannot be delegated, got {:?}", other.map(|vc| vc.id().clone())).into()); }
+        }
+
+        let verifier: OurVerifier<Curve> = OurVerifier::new(registry.clone())?;
+        let signed_vp = delegatee.issue_delegation_verifiable_presentation(vc.clone(), permissions.clone())?;
+        for mode in [VerificationMode::Sequential, VerificationMode::Parallel, VerificationMode::Batched] {
+            verifier.verify_verifiable_presentation(delegatee_id.clone(), signed_vp.clone(), mode)?;
+        }
+
+        // Removing the limit of the credential lets it be delegated, but not verified
+        let last_id = String::from("https://vc.example/delegators/d3");
+        let last: OurIssuer<Curve> = OurIssuer::new(last_id.clone(), registry)?;
+        let mut unlimited_vc = vc;
+        unlimited_vc.mut_credential().set_max_depth(None);
+        let last_vc = delegatee.issue_delegation_verifiable_credential(context, String::from("http://delegation.example/credentials/1339"), valid_from, last_id.clone(), validity_period, permissions.clone(), Some(unlimited_vc))?;
+        let signed_vp = last.issue_delegation_verifiable_presentation(last_vc.clone(), permissions.clone())?;
+        match verifier.verify_verifiable_presentation(last_id.clone(), signed_vp, VerificationMode::Sequential) {
+            Err(DelegationError::Delegator { index: 2, source }) if matches!(*source, DelegationError::DepthExceeded { depth: 2, max_depth: 1 }) => {}
+            other => { return Err(format!("Expected the maximum depth to be exceeded at delegation 2, got {other:?}").into()); }
+        }
+
+        // Limits are part of the keys of the accumulators, so they cannot be removed altogether
+        let mut unlimited_vc = last_vc;
+        unlimited_vc.mut_credential().mut_hierarchy()[0].set_max_depth(None);
+        let signed_vp = last.issue_delegation_verifiable_presentation(unlimited_vc, permissions)?;
+        match verifier.verify_verifiable_presentation(last_id, signed_vp, VerificationMode::Sequential) {
+            Err(DelegationError::Delegator { source, .. }) if matches!(*source, DelegationError::DelegationNotPublished { .. }) => Ok(()),
+            other => Err(format!("Expected an unpublished delegation, got {other:?}").into()),
+        }
+    }
+
+    #[test]
+    fn verify_vp_with_caveats() -> Result<(), Box<dyn std::error::Error>> {
+        type Curve = Bn254;
+        let registry = InMemoryRegistry::shared();
+
+        let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
+        let valid_from = String::from("2026-01-01T00:00:00Z");
+        let validity_period: Duration = Duration::new(24 * 3600, 0);
+        let permissions: Vec<Permission> = vec![Permission::from_str("https://vc.example/resources/r1:p0")?];
+        let audience = String::from("https://vc.example/verifiers/v1");
+
+        // 2026-01-01T10:00:00Z
+        let clock = Arc::new(FixedClock::new(1_767_261_600_000_000_000));
+        let mut root: OurIssuer<Curve> = OurIssuer::new(String::from("https://vc.example/delegators/d0"), registry.clone())?;
+        root.set_clock(clock.clone());
+        let delegator_id = String::from("https://vc.example/delegators/d1");
+        let root_caveats = vec![Caveat::from_str("time=08:00-18:00")?, Caveat::from_str("ip=10.0.0.0/8")?, Caveat::Audience(audience.clone())];
+        let root_vc = root.issue_restricted_delegation_verifiable_credential(context.clone(), String::from("http://delegation.example/credentials/1337"), valid_from.clone(), delegator_id.clone(), validity_period, permissions.clone(), None, None, root_caveats)?;
+
+        // The narrower time window replaces the one of the root, the other caveats are carried over
+        let mut delegator: OurIssuer<Curve> = OurIssuer::new(delegator_id, registry.clone())?;
+        delegator.set_clock(clock.clone());
+        let delegatee_id = String::from("https://vc.example/delegators/d2");
+        let caveats = vec![Caveat::from_str("time=09:00-17:00")?, Caveat::MaxAmount(100)];
+        let vc = delegator.issue_restricted_delegation_verifiable_credential(context, String::from("http://delegation.example/credentials/1338"), valid_from, delegatee_id.clone(), validity_period, permissions.clone(), Some(root_vc), None, caveats)?;
+        let expected_caveats = vec![Caveat::from_str("ip=10.0.0.0/8")?, Caveat::Audience(audience.clone()), Caveat::from_str("time=09:00-17:00")?, Caveat::MaxAmount(100)];
+        if *vc.credential().caveats() != expected_caveats {
+            return Err(format!("Unexpected caveats {:?}", vc.credential().caveats()).into());
+        }
+
+        let delegatee: OurIssuer<Curve> = OurIssuer::new(delegatee_id.clone(), registry.clone())?;
+        let signed_vp = delegatee.issue_delegation_verifiable_presentation(vc.clone(), permissions.clone())?;
+        let mut verifier: OurVerifier<Curve> = OurVerifier::new(registry)?;
+        verifier.set_clock(clock.clone());
+        let request_context = RequestContext::new().with_client_ip("10.1.2.3".parse()?).with_amount(50).with_audience(audience);
+        for mode in [VerificationMode::Sequential, VerificationMode::Parallel, VerificationMode::Batched] {
+            verifier.verify_verifiable_presentation_in_context(delegatee_id.clone(), signed_vp.clone(), &request_context, mode)?;
+        }
+
+        // The first caveat that is not satisfied is reported with the delegation carrying it
+        let failures = [
+            (request_context.clone().with_amount(500), 1, "amount=100"),
+            (request_context.clone().with_client_ip("192.168.0.1".parse()?), 0, "ip=10.0.0.0/8"),
+            (RequestContext::new(), 0, "ip=10.0.0.0/8"),
+        ];
+        for (failing_context, expected_index, expected_caveat) in failures {
+            match verifier.verify_verifiable_presentation_in_context(delegatee_id.clone(), signed_vp.clone(), &failing_context, VerificationMode::Sequential) {
+                Err(DelegationError::Delegator { index, source }) if index == expected_index
+                    && matches!(&*source, DelegationError::CaveatNotSatisfied { caveat } if caveat == expected_caveat) => {}
+                other => { return Err(format!("Expected caveat {expected_caveat} not to be satisfied at delegation {expected_index}, got {other:?}").into()); }
+            }
+        }
+        // 2026-01-01T20:00:00Z
+        clock.advance(Duration::new(10 * 3600, 0))?;
+        match verifier.verify_verifiable_presentation_in_context(delegatee_id.clone(), signed_vp, &request_context, VerificationMode::Sequential) {
+            Err(DelegationError::Delegator { index: 0, source }) if matches!(&*source, DelegationError::CaveatNotSatisfied { caveat } if caveat == "time=08:00-18:00") => {}
+            other => { return Err(format!("Expected the time window not to be satisfied, got {other:?}").into()); }
+        }
+        clock.set(1_767_261_600_000_000_000)?;
+
+        // Dropping a caveat of the parent delegation loosens the chain
+        let mut loosened_vc = vc.clone();
+        loosened_vc.mut_credential().set_caveats(expected_caveats[1..].to_vec());
+        let signed_vp = delegatee.issue_delegation_verifiable_presentation(loosened_vc, permissions.clone())?;
+        match verifier.verify_verifiable_presentation_in_context(delegatee_id.clone(), signed_vp, &request_context, VerificationMode::Sequential) {
+            Err(DelegationError::Delegator { index: 1, source }) if matches!(&*source, DelegationError::CaveatLoosened { caveat } if caveat == "ip=10.0.0.0/8") => {}
+            other => { return Err(format!("Expected the network caveat to be loosened, got {other:?}").into()); }
+        }
+
+        // Caveats are part of the keys of the accumulators, so they cannot be removed either
+        let mut stripped_vc = vc;
+        stripped_vc.mut_credential().set_caveats(expected_caveats[..3].to_vec());
+        let signed_vp = delegatee.issue_delegation_verifiable_presentation(stripped_vc, permissions)?;
+        match verifier.verify_verifiable_presentation_in_context(delegatee_id, signed_vp, &request_context.with_amount(500), VerificationMode::Sequential) {
+            Err(DelegationError::Delegator { index: 1, source }) if matches!(*source, DelegationError::DelegationNotPublished { .. }) => Ok(()),
+            other => Err(format!("Expected an unpublished delegation, got {other:?}").into()),
+        }
+    }
+
+    #[test]
+    fn verify_revoked_vp() -> Result<(), Box<dyn std::error::Error>> {
+        type Curve = Bn254;
+        let registry = InMemoryRegistry::shared();
+
+        let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
+        let valid_from = String::from("2026-01-01T00:00:00Z");
+        let validity_period: Duration = Duration::new(3600, 0);
+        let permissions: Vec<Permission> = vec![Permission::from_str("https://vc.example/resources/r1:p0")?, Permission::from_str("https://vc.example/resources/r1:p1")?];
+
+        let root_id = String::from("https://vc.example/delegators/d0");
+        let root: OurIssuer<Curve> = OurIssuer::new(root_id, registry.clone())?;
+        let root_credential_id = String::from("http://delegation.example/credentials/1337");
+        let delegatee_id = String::from("https://vc.example/delegators/d1");
+        let root_vc = root.issue_delegation_verifiable_credential(context.clone(), root_credential_id.clone(), valid_from.clone(), delegatee_id.clone(), validity_period, permissions.clone(), None)?;
+
+        let delegator: OurIssuer<Curve> = OurIssuer::new(delegatee_id.clone(), registry.clone())?;
+        let revoked_credential_id = String::from("http://delegation.example/credentials/1338");
+        let revoked_vc: VerifiableCredential<OurDelegationCredential> = delegator.issue_delegation_verifiable_credential(context.clone(), revoked_credential_id.clone(), valid_from.clone(), String::from("https://vc.example/delegators/d2"), validity_period, permissions.clone(), Some(root_vc.clone()))?;
+        let vc: VerifiableCredential<OurDelegationCredential> = delegator.issue_delegation_verifiable_credential(context, String::from("http://delegation.example/credentials/1339"), valid_from, String::from("https://vc.example/delegators/d3"), validity_period, permissions.clone(), Some(root_vc))?;
+
+        let verifier: OurVerifier<Curve> = OurVerifier::new(registry.clone())?;
+
+        let revoked_vp = delegator.issue_delegation_verifiable_presentation(revoked_vc, permissions.clone())?;
+        let signed_vp = delegator.issue_delegation_verifiable_presentation(vc, permissions.clone())?;
+        verifier.verify_verifiable_presentation(delegatee_id.clone(), revoked_vp.clone(), VerificationMode::Sequential)?;
+        verifier.verify_verifiable_presentation(delegatee_id.clone(), signed_vp.clone(), VerificationMode::Sequential)?;
+
+        // Revoking the delegatee of a credential only invalidates that credential
+        delegator.revoke_delegatee(&revoked_credential_id)?;
+        let revoked_key = match verifier.verify_verifiable_presentation(delegatee_id.clone(), revoked_vp.clone(), VerificationMode::Sequential) {
+            Err(DelegationError::Delegator { index: 1, source }) => match *source {
+                DelegationError::Revoked { key } => key,
+                other => { return Err(format!("Expected revocation of delegation 1, got {other:?}").into()); }
+            },
+            other => { return Err(format!("Expected revocation of delegation 1, got {other:?}").into()); }
+        };
+
+        // The revoked delegatee cannot put back the accumulator value from before the revocation
+        let entry = match registry.resolve_accumulator_value(&revoked_key)? {
+            Some(entry) => entry,
+            None => { return Err("Accumulator value of the revoked delegation not resolved".into()); }
+        };
+        let restore = AccumulatorUpdate::new(entry.accumulator_value.clone(), entry.updates[0].previous_accumulator_value.clone(), vec![], String::new());
+        match registry.publish_accumulator_update(&revoked_key, restore) {
+            Err(DelegationError::InvalidAccumulatorUpdate { .. }) => {}
+            other => { return Err(format!("Expected InvalidAccumulatorUpdate, got {other:?}").into()); }
+        }
+        match verifier.verify_verifiable_presentation(delegatee_id.clone(), revoked_vp, VerificationMode::Sequential) {
+            Err(DelegationError::Delegator { index: 1, source }) if matches!(*source, DelegationError::Revoked { .. }) => {}
+            other => { return Err(format!("Expected revocation of delegation 1, got {other:?}").into()); }
+        }
+        verifier.verify_verifiable_presentation(delegatee_id.clone(), signed_vp.clone(), VerificationMode::Sequential)?;
+
+        // Revoking a permission that was never granted must fail
+        match root.revoke_permissions(&root_credential_id, &[Permission::from_str("https://vc.example/resources/r1:p9")?]) {
+            Err(DelegationError::PermissionNotGranted { .. }) => {}
+            other => { return Err(format!("Expected PermissionNotGranted, got {other:?}").into()); }
+        }
+
+        // Revoking a permission in the root credential invalidates the whole chain
+        root.revoke_permissions(&root_credential_id, &permissions[1..])?;
+        match verifier.verify_verifiable_presentation(delegatee_id.clone(), signed_vp.clone(), VerificationMode::Sequential) {
+            Err(DelegationError::Delegator { index: 0, source }) if matches!(*source, DelegationError::Revoked { .. }) => {}
+            other => { return Err(format!("Expected revocation of delegation 0, got {other:?}").into()); }
+        }
+
+        // A failing batch reports the offending delegation as well
+        match verifier.verify_verifiable_presentation(delegatee_id, signed_vp, VerificationMode::Batched) {
+            Err(DelegationError::Delegator { index: 0, source }) if matches!(*source, DelegationError::Revoked { .. }) => Ok(()),
+            other => Err(format!("Expected revocation of delegation 0, got {other:?}").into()),
+        }
+    }
 }
\ No newline at end of file
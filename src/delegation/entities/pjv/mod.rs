@@ -1 +1,2 @@
 pub mod pjv_issuer_verifier;
+pub mod pjv_scheme;
\ No newline at end of file
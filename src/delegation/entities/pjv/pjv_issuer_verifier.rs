@@ -1,66 +1,83 @@
+use crate::delegation::clock::clock::Clock;
+use crate::delegation::clock::system_clock::SystemClock;
 use crate::delegation::credentials::pjv::pjv_delegation_credential::PJVDelegationCredential;
 use crate::delegation::credentials::pjv::pjv_delegator::PJVDelegator;
 use crate::delegation::credentials::pjv::pjv_signature::PJVSignature;
+use crate::delegation::credentials::presentation_request::PresentationRequest;
+use crate::delegation::credentials::signature_algorithm::SignatureAlgorithm;
 use crate::delegation::credentials::verifiable_credential::VerifiableCredential;
 use crate::delegation::credentials::verifiable_presentation::VerifiablePresentation;
-use crate::delegation::entities::dtl_sim::DLTSim;
-use crate::delegation::entities::verifier::verify_timings;
+use crate::delegation::entities::verifier::{check_validity_period, consume_nonce, verify_presentation_request, verify_timings, verify_validity_period};
+use crate::delegation::error::DelegationError;
+use crate::delegation::registry::verifiable_data_registry::{KeyPurpose, VerifiableDataRegistry};
+use crate::delegation::replay::in_memory_replay_cache::InMemoryReplayCache;
+use crate::delegation::replay::replay_cache::ReplayCache;
+use crate::delegation::utils::utils::format_date_time;
 use ark_std::rand::prelude::StdRng;
 use ark_std::rand::{RngCore, SeedableRng};
-use ed25519_dalek::{SecretKey, SigningKey};
 use josekit::jwe::{JweHeader, ECDH_ES_A128KW};
 use josekit::jwk::Jwk;
-use josekit::jws::{EdDSA, JwsSigner, JwsVerifier};
 use multibase::Base::Base64Url;
 use serde_json::Value;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::sync::Arc;
+use std::time::Duration;
 use x25519_dalek::{PublicKey as X25519PublicKey, StaticSecret};
 
 pub struct PJVIssuerVerifier {
     id: String,
     decryption_jwk: Jwk,
     signature_jwk: Jwk,
-    encryption_dlt: DLTSim<Jwk>,
-    verification_dlt: DLTSim<Jwk>
+    registry: Arc<dyn VerifiableDataRegistry>,
+    // Nonces of the presentation requests already answered
+    replay_cache: Arc<dyn ReplayCache>,
+    // Algorithms accepted for the signatures of the delegators and of the presentations
+    allowed_algorithms: Vec<SignatureAlgorithm>,
+    clock: Arc<dyn Clock>,
+    // Tolerated skew between the clocks of the issuers and of this verifier
+    leeway: Duration,
 }
 
 impl PJVIssuerVerifier {
-    pub fn new(id: String, encryption_dlt: DLTSim<Jwk>, verification_dlt: DLTSim<Jwk>) -> Result<Self, String> {
+    pub fn new(id: String, registry: Arc<dyn VerifiableDataRegistry>) -> Result<Self, DelegationError> {
+        Self::with_replay_cache(id, registry, InMemoryReplayCache::shared())
+    }
+
+    /// Creates an issuer and verifier sharing the nonces of the answered presentation requests with
+    /// other verifiers.
+    ///
+    /// # Arguments
+    /// * `id` - Id of the entity.
+    /// * `registry` - Registry the public keys are published to and resolved from.
+    /// * `replay_cache` - Cache of the nonces already used.
+    ///
+    /// # Returns
+    /// A result wrapping the entity or the error, if its keys cannot be generated or published.
+    pub fn with_replay_cache(id: String, registry: Arc<dyn VerifiableDataRegistry>, replay_cache: Arc<dyn ReplayCache>) -> Result<Self, DelegationError> {
+        Self::with_signature_algorithm(id, registry, SignatureAlgorithm::EdDSA, replay_cache)
+    }
+
+    /// Creates an issuer and verifier signing its delegators and presentations with the given
+    /// algorithm.
+    ///
+    /// # Arguments
+    /// * `id` - Id of the entity.
+    /// * `registry` - Registry the public keys are published to and resolved from.
+    /// * `signature_algorithm` - Algorithm of the signing key.
+    /// * `replay_cache` - Cache of the nonces already used.
+    ///
+    /// # Returns
+    /// A result wrapping the entity or the error, if its keys cannot be generated or published.
+    pub fn with_signature_algorithm(id: String, registry: Arc<dyn VerifiableDataRegistry>, signature_algorithm: SignatureAlgorithm,
+                                    replay_cache: Arc<dyn ReplayCache>) -> Result<Self, DelegationError> {
         let mut rng: StdRng = StdRng::from_entropy();
 
-        // let signing_algorithm = String::from("EdDSA");
         // let encryption_algorithm = String::from("ECDH-ES+A128KW");
         // let content_encryption_algorithm = String::from("A128GCM");
 
-        // =====================================================
-        // Ed25519 SIGNATURE - Public and Private Key generation
-        // =====================================================
-        let mut sk: SecretKey = [0u8; 32];
-        rng.fill_bytes(&mut sk);
-        let signing_key = SigningKey::from_bytes(&sk);
-        let public_key_bytes = signing_key.verifying_key().to_bytes();
-        let private_key_bytes = signing_key.to_bytes();
-
-        let mut signature_jwk = Jwk::new("OKP");
-        match signature_jwk.set_parameter("crv", Some(Value::String(String::from("Ed25519")))) {
-            Ok(()) => {},
-            Err(e) => { return Err(format!("Failed to set parameter crv for signing key [{}]", e)); }
-        };
-        match signature_jwk.set_parameter("x", Some(Value::String(Base64Url.encode(public_key_bytes)))) {
-            Ok(()) => {},
-            Err(e) => { return Err(format!("Failed to set parameter x for signing key [{}]", e)); }
-        };
-
-        // Take the public key for verification and put it in the DLT
-        let public_signature_jwk = signature_jwk.clone();
-        verification_dlt.borrow_mut().insert(id.clone(), public_signature_jwk);
-
-        // Add the private parameter d to the jwk to enable the signing operation.
-        match signature_jwk.set_parameter("d", Some(Value::String(Base64Url.encode(private_key_bytes)))) {
-            Ok(()) => {},
-            Err(e) => { return Err(format!("Failed to set parameter d for signing key [{}]", e)); }
-        };
+        let (signature_jwk, public_signature_jwk) = signature_algorithm.generate_key(&id)?;
 
+        // Take the public key for verification and put it in the registry
+        registry.publish_key(KeyPurpose::Verification, &id, public_signature_jwk)?;
 
         // =====================================================
         // X25519 SIGNATURE - Public and Private Key generation
@@ -74,45 +91,66 @@ impl PJVIssuerVerifier {
         let mut decryption_jwk = Jwk::new("OKP");
         match decryption_jwk.set_parameter("crv", Some(Value::String(String::from("X25519")))) {
             Ok(_) => {},
-            Err(e) => { return Err(format!("Failed to set parameter crv [{}]", e)); }
+            Err(e) => { return Err(DelegationError::Jose { context: "Failed to set parameter crv for decryption key", source: e }); }
         };
         match decryption_jwk.set_parameter("x", Some(Value::String(Base64Url.encode(x_public.as_bytes())))) {
             Ok(_) => {},
-            Err(e) => { return Err(format!("Failed to set parameter x [{}]", e)); }
+            Err(e) => { return Err(DelegationError::Jose { context: "Failed to set parameter x for decryption key", source: e }); }
         };
 
-        // Take the public key for encryption of data and put it in the DLT
+        // Take the public key for encryption of data and put it in the registry
         let encryption_jwk = decryption_jwk.clone();
-        encryption_dlt.borrow_mut().insert(id.clone(), encryption_jwk);
+        registry.publish_key(KeyPurpose::Encryption, &id, encryption_jwk)?;
 
         // Add the private parameter d to the jwk to enable the decryption operation.
         match decryption_jwk.set_parameter("d", Some(Value::String(Base64Url.encode(encryption_secret.as_bytes())))) {
             Ok(_) => {},
-            Err(e) => { return Err(format!("Failed to set parameter d [{}]", e)); }
+            Err(e) => { return Err(DelegationError::Jose { context: "Failed to set parameter d for decryption key", source: e }); }
         }
 
-        Ok(PJVIssuerVerifier { id, decryption_jwk, signature_jwk, encryption_dlt, verification_dlt})
+        Ok(PJVIssuerVerifier {
+            id, decryption_jwk, signature_jwk, registry, replay_cache,
+            allowed_algorithms: SignatureAlgorithm::ALL.to_vec(), clock: SystemClock::shared(), leeway: Duration::ZERO
+        })
+    }
+
+    pub fn id(&self) -> &String {
+        &self.id
+    }
+
+    /// Restricts the algorithms accepted for the signatures of the delegators and of the
+    /// presentations. Every supported algorithm is accepted by default.
+    pub fn set_allowed_algorithms(&mut self, allowed_algorithms: Vec<SignatureAlgorithm>) {
+        self.allowed_algorithms = allowed_algorithms;
     }
 
-    fn sign_delegator(&self, delegator: &PJVDelegator) -> Result<PJVSignature, String> {
+    /// Sets the clock used to issue and verify credentials. The system clock is used by default.
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// Sets the tolerated skew between the clocks of the issuers and of this verifier, none by
+    /// default.
+    pub fn set_leeway(&mut self, leeway: Duration) {
+        self.leeway = leeway;
+    }
+
+    fn sign_delegator(&self, delegator: &PJVDelegator) -> Result<PJVSignature, DelegationError> {
         let serialized_delegator = match serde_json::to_string(delegator) {
             Ok(serialized_delegator) => serialized_delegator,
-            Err(err) => { return Err(format!("Failed to serialize delegator [{}]", err)); }
+            Err(err) => { return Err(DelegationError::Json { context: "Failed to serialize delegator", source: err }); }
         };
 
         // Convert the serialized delegator to an array of bytes
         let serialized_delegator_bytes = serialized_delegator.as_bytes();
 
         // Create a signer with the issuer's private key
-        let signer = match EdDSA.signer_from_jwk(&self.signature_jwk) {
-            Ok(signer) => signer,
-            Err(e) => { return Err(format!("Failed to set signer for jwk {}", e)); }
-        };
+        let signer = SignatureAlgorithm::for_jwk(&self.signature_jwk)?.signer_from_jwk(&self.signature_jwk)?;
 
         // Sign the delegator's array of bytes
         let vec_signature = match signer.sign(serialized_delegator_bytes) {
             Ok(vec_signature) => vec_signature,
-            Err(e) => { return Err(format!("Failed to sign payload [{}]", e)); }
+            Err(e) => { return Err(DelegationError::Jose { context: "Failed to sign payload", source: e }); }
         };
 
         // Generate a PJVSignature object as specified in the paper
@@ -120,61 +158,92 @@ impl PJVIssuerVerifier {
         Ok(PJVSignature::new(signature))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn issue_delegation_verifiable_credential(&self, context: Vec<String>, credential_id: String,
                                                   valid_from: String, delegatee_id: String,
                                                   validity_period: Duration, owner: String,
                                                   resource_uri: String, operations: Vec<String>,
                                                   optional_issuer_vc: Option<VerifiableCredential<PJVDelegationCredential>>)
-                                                  -> Result<VerifiableCredential<PJVDelegationCredential>, String> {
+                                                  -> Result<VerifiableCredential<PJVDelegationCredential>, DelegationError> {
+        self.issue_delegation_verifiable_credential_with_max_depth(context, credential_id, valid_from, delegatee_id, validity_period,
+                                                                   owner, resource_uri, operations, optional_issuer_vc, None)
+    }
+
+    /// Issues a delegation credential that limits how many times it may be delegated further. The
+    /// limit can only be lowered along the chain: the one of the issued credential is also bounded
+    /// by the one of the credential of the issuer.
+    ///
+    /// # Arguments
+    /// * `max_depth` - Maximum number of delegations that may follow the issued one, `Some(0)` for
+    ///   operations the delegatee may perform but not pass on, none if unlimited.
+    ///
+    /// See `issue_delegation_verifiable_credential` for the other arguments.
+    ///
+    /// # Returns
+    /// A result wrapping the credential or the error, if the credential of the issuer cannot be
+    /// delegated further.
+    #[allow(clippy::too_many_arguments)]
+    pub fn issue_delegation_verifiable_credential_with_max_depth(&self, context: Vec<String>, credential_id: String,
+                                                                 valid_from: String, delegatee_id: String,
+                                                                 validity_period: Duration, owner: String,
+                                                                 resource_uri: String, operations: Vec<String>,
+                                                                 optional_issuer_vc: Option<VerifiableCredential<PJVDelegationCredential>>,
+                                                                 max_depth: Option<usize>)
+                                                                 -> Result<VerifiableCredential<PJVDelegationCredential>, DelegationError> {
 
         let issuer = self.id.clone();
 
         if operations.is_empty() {
-            return Err("Permissions array is empty".to_string());
+            return Err(DelegationError::EmptyPermissions);
         }
 
-        let since_epoch: Duration = match SystemTime::now().duration_since(UNIX_EPOCH) {
-            Ok(duration) => duration,
-            Err(e) => return Err(format!("Failed to get issuance time [{e}]")),
-        };
-
-        let numeric_iat: u128 = since_epoch.as_nanos();
+        let numeric_iat: u128 = self.clock.now()?;
         let numeric_exp: u128 = numeric_iat + validity_period.as_nanos();
         let iat = numeric_iat.to_string();
         let exp = numeric_exp.to_string();
 
-        let hierarchy: String;
+        // The envelope of the credential expires together with the delegation
+        let valid_until = format_date_time(numeric_exp)?;
+        check_validity_period(&valid_from, Some(&valid_until), &iat, &exp)?;
+
+        // The credential of the issuer must allow one more delegation, whose maximum depth is
+        // bounded by the remaining one
+        let mut max_depth = max_depth;
+        if let Some(vc) = &optional_issuer_vc {
+            match vc.credential().delegator().max_depth() {
+                Some(0) => { return Err(DelegationError::NotRedelegable); }
+                Some(issuer_max_depth) => { max_depth = Some(max_depth.map_or(issuer_max_depth - 1, |max_depth| max_depth.min(issuer_max_depth - 1))); }
+                None => {}
+            }
+        }
 
-        match optional_issuer_vc {
+        let hierarchy: String = match optional_issuer_vc {
 
             // If the issued credential is from the root delegator, we simply set the hierarchy to an
             // empty string.
-            None => {
-                hierarchy = String::new();
-            }
+            None => String::new(),
 
             // If not, we have to encrypt the previous credential with the owner's public key
             // so that they're able to decrypt the credential and rebuild the chain of trust backwards
             Some(issuer_vc) => {
 
-                // Retrieve the owner's public key from the dlt
-                let dlt = self.encryption_dlt.borrow();
-                let owner_public_key = match dlt.get(&owner) {
+                // Retrieve the owner's public key from the registry
+                let owner_public_key = match self.registry.resolve_key(KeyPurpose::Encryption, &owner)? {
                     Some(owner_public_key) => owner_public_key,
-                    None => { return Err(format!("Resource owner [{owner}] has not published its public key in the DLT")); }
+                    None => { return Err(DelegationError::OwnerNotFound { id: owner }); }
                 };
 
                 // Serialize the PJVDelegationCredential to a string
                 let issuer_dc = issuer_vc.credential();
                 let serialized_dc = match serde_json::to_string(&issuer_dc) {
                     Ok(serialized_dc) => serialized_dc,
-                    Err(e) => return Err(format!("Failed to serialize issuer delegation credential [{}]", e)),
+                    Err(e) => return Err(DelegationError::Json { context: "Failed to serialize issuer delegation credential", source: e }),
                 };
 
                 // Generate the encrypter from the retrieved owner's public key
-                let encrypter = match ECDH_ES_A128KW.encrypter_from_jwk(owner_public_key) {
+                let encrypter = match ECDH_ES_A128KW.encrypter_from_jwk(&owner_public_key) {
                     Ok(x) => x,
-                    Err(e) => { return Err(format!("Encrypter creation failed: {}", e)); }
+                    Err(e) => { return Err(DelegationError::Jose { context: "Encrypter creation failed", source: e }); }
                 };
 
                 // Since Curve 25519 does not support direct encryption, we have to wrap an ephemeral
@@ -187,31 +256,31 @@ impl PJVIssuerVerifier {
                 // Convert the serialized dc string to an array of bytes
                 let serialized_dc_bytes = serialized_dc.as_bytes();
                 // Encrypt the array of bytes with the encrypter
-                hierarchy = match josekit::jwe::serialize_compact(serialized_dc_bytes, &header, &encrypter) {
+                match josekit::jwe::serialize_compact(serialized_dc_bytes, &header, &encrypter) {
                     Ok(hierarchy) => hierarchy,
-                    Err(e) => { return Err(format!("Serialization failed: {}", e)); }
-                };
+                    Err(e) => { return Err(DelegationError::Jose { context: "Encryption of the hierarchy failed", source: e }); }
+                }
             }
-        }
+        };
 
         // Insert the hierarchy in a new delegator object and serialize it
-        let delegator = PJVDelegator::new(owner, issuer.clone(), delegatee_id, iat, exp,
-                                          resource_uri, operations, hierarchy);
+        let mut delegator = PJVDelegator::new(owner, issuer.clone(), delegatee_id, iat, exp,
+                                              resource_uri, operations, hierarchy);
+        delegator.set_max_depth(max_depth);
 
         let pjv_signature = self.sign_delegator(&delegator)?;
 
         // Create a PJVDelegationCredential and a Verifiable Credential
         let dc = PJVDelegationCredential::new(delegator, pjv_signature)?;
-        let vc = VerifiableCredential::new(context, credential_id, issuer, valid_from, dc);
+        let vc = VerifiableCredential::new(context, credential_id, issuer, valid_from, Some(valid_until), dc);
 
         Ok(vc)
     }
 
 
 
-    pub fn issue_delegation_verifiable_presentation(&self, vc: VerifiableCredential<PJVDelegationCredential>,
-                                                    disclosed_permissions: Vec<String>)
-                                                    -> Result<String, String> {
+    fn presentation(&self, vc: VerifiableCredential<PJVDelegationCredential>, disclosed_permissions: Vec<String>)
+                    -> Result<VerifiablePresentation<PJVDelegationCredential>, DelegationError> {
 
         let mut vp: VerifiablePresentation<PJVDelegationCredential> = VerifiablePresentation::from_verifiable_credential(vc, disclosed_permissions)?;
 
@@ -221,50 +290,76 @@ impl PJVIssuerVerifier {
         let signature = vp.mut_credential().mut_signature();
         signature.signature = pjv_signature.signature;
 
+        Ok(vp)
+    }
+
+    pub fn issue_delegation_verifiable_presentation(&self, vc: VerifiableCredential<PJVDelegationCredential>,
+                                                    disclosed_permissions: Vec<String>)
+                                                    -> Result<String, DelegationError> {
+
+        let vp = self.presentation(vc, disclosed_permissions)?;
+
         // TODO: remove this.
         // println!("{}", serde_json::to_string_pretty(&vp).unwrap());
 
-        vp.to_signed_jwt(&self.signature_jwk)
+        vp.to_signed_jwt(&self.signature_jwk, self.clock.now()?)
+
+    }
 
+    /// Issues a presentation answering the request of a verifier, so that it is accepted only once
+    /// and only by that verifier.
+    ///
+    /// # Arguments
+    /// * `vc` - Delegation credential issued by this entity.
+    /// * `disclosed_permissions` - Operations to be disclosed to the verifier.
+    /// * `request` - Presentation request received from the verifier.
+    ///
+    /// # Returns
+    /// A result wrapping the signed presentation or the error, if it occurs.
+    pub fn issue_bound_delegation_verifiable_presentation(&self, vc: VerifiableCredential<PJVDelegationCredential>,
+                                                          disclosed_permissions: Vec<String>, request: &PresentationRequest)
+                                                          -> Result<String, DelegationError> {
+
+        let mut vp = self.presentation(vc, disclosed_permissions)?;
+        vp.set_request(request);
+
+        vp.to_signed_jwt(&self.signature_jwk, self.clock.now()?)
     }
 
-    fn verify_signature(&self, delegator: &PJVDelegator, signature: &PJVSignature) -> Result<(), String> {
+    fn verify_signature(&self, delegator: &PJVDelegator, signature: &PJVSignature) -> Result<(), DelegationError> {
 
         let issuer = delegator.iss();
-        let verification_dlt = self.verification_dlt.borrow();
-
-        // Retrieve the issuer from the DLT
-        let jwk = match verification_dlt.get(issuer) {
+        // Retrieve the issuer from the registry
+        let jwk = match self.registry.resolve_key(KeyPurpose::Verification, issuer)? {
             Some(issuer_pk) => issuer_pk,
-            None => { return Err(format!("Issuer {issuer} not found in the verification DLT")); }
+            None => { return Err(DelegationError::IssuerNotFound { id: issuer.clone() }); }
         };
 
-        // Generate a verifier with the issuer's public key
-        let verifier = match EdDSA.verifier_from_jwk(jwk) {
-            Ok(verifier) => verifier,
-            Err(err) => { return Err(format!("Failed to set verifier for jwk [{}]", err)); }
-        };
+        // Generate a verifier with the issuer's public key, whose type determines the algorithm
+        let verifier = SignatureAlgorithm::allowed_for_jwk(&jwk, &self.allowed_algorithms)?.verifier_from_jwk(&jwk)?;
 
         // Serialize the delegator into a String
         let serialized_delegator = match serde_json::to_string(&delegator) {
             Ok(serialized_delegator) => serialized_delegator,
-            Err(err) => { return Err(format!("Failed to serialize delegator [{}]", err)); }
+            Err(err) => { return Err(DelegationError::Json { context: "Failed to serialize delegator", source: err }); }
         };
 
         // Decode the signature from base64url
         let decoded_signature = match Base64Url.decode(signature.signature()){
             Ok(decoded_signature) => decoded_signature,
-            Err(err) => { return Err(format!("Decoding of signature failed [{}]", err)); }
+            Err(err) => { return Err(DelegationError::Decoding(err)); }
         };
 
         // Using the arrays of bytes, verify the signature corresponding to the delegator
         match verifier.verify(serialized_delegator.as_bytes(), decoded_signature.as_slice()) {
             Ok(()) => { Ok(()) }
-            Err(err) => { Err(format!("Failed to verify delegator [{}]", err)) }
+            Err(err) => { Err(DelegationError::Jose { context: "Failed to verify delegator", source: err }) }
         }
     }
 
-    fn verify_delegation_credential(&self, delegation_credential: &PJVDelegationCredential, now: u128) -> Result<PJVDelegator, String> {
+    // Verifies a delegation and, recursively, the ones of its hierarchy. Returns its delegator, its
+    // index in the chain and the maximum depth allowed by the chain up to it, if any
+    fn verify_delegation_credential(&self, delegation_credential: &PJVDelegationCredential, now: u128) -> Result<(PJVDelegator, usize, Option<usize>), DelegationError> {
         let delegator = delegation_credential.delegator();
         let signature = delegation_credential.signature();
 
@@ -272,20 +367,20 @@ impl PJVIssuerVerifier {
         let owner = delegator.owner().clone();
         let issuer = delegator.iss().clone();
 
-        // Verify that timings are correct
-        verify_timings(now, delegator.iat(), delegator.exp())?;
-
-        // Verify that the signature on the delegator is correct
-        self.verify_signature(delegator, signature)?;
-
         // Check the hierarchy
         if *delegator.hierarchy() == String::new() {
+            let index = 0;
+
+            // Verify that timings are correct and that the signature on the delegator is correct
+            verify_timings(now, delegator.iat(), delegator.exp(), self.leeway).map_err(|e| e.at_delegator(index))?;
+            self.verify_signature(delegator, signature).map_err(|e| e.at_delegator(index))?;
+
             // If hierarchy is empty, the credential presented must be issued by the verifier, which
             // is also supposed to be the owner
             if self_id != owner || self_id != issuer {
-                Err(format!("Hierarchy is empty but ({self_id} != {owner}) or ({self_id} != {issuer})"))
+                Err(DelegationError::RootMismatch { verifier: self_id, owner, issuer }.at_delegator(index))
             } else {
-                Ok(delegator.clone())
+                Ok((delegator.clone(), index, delegator.max_depth()))
             }
         } else {
             // If hierarchy is not empty, we must decrypt it, create a new PJVDelegationCredential
@@ -294,7 +389,7 @@ impl PJVIssuerVerifier {
             // Create a decrypter object using the issuer_verifier private key
             let decrypter = match ECDH_ES_A128KW.decrypter_from_jwk(&self.decryption_jwk) {
                 Ok(x) => x,
-                Err(e) => { return Err(format!("Decrypter creation failed: {}", e)); }
+                Err(e) => { return Err(DelegationError::Jose { context: "Decrypter creation failed", source: e }); }
             };
 
             let hierarchy = delegator.hierarchy().clone();
@@ -302,82 +397,127 @@ impl PJVIssuerVerifier {
             // Decrypt the string using the decrypter object
             let (payload, _header) = match josekit::jwe::deserialize_compact(hierarchy.as_str(), &decrypter) {
                 Ok((payload, header)) => { (payload, header) },
-                Err(e) => { return Err(format!("Failed to deserialize jws compact payload {}", e)); }
+                Err(e) => { return Err(DelegationError::Jose { context: "Failed to decrypt the hierarchy", source: e }); }
             };
 
             // Convert the byte array into a String
             let dc_string = match String::from_utf8(payload) {
                 Ok(string) => string,
-                Err(e) => { return Err(format!("Failed to convert jws compact string for payload {}", e)); }
+                Err(e) => { return Err(DelegationError::Utf8(e)); }
             };
 
             // Deserialize the String and create a PJVDelegationCredential
             let parsed_delegation_credential = match serde_json::from_str::<PJVDelegationCredential>(&dc_string) {
                 Ok(parsed_delegation_credential) => parsed_delegation_credential,
-                Err(err) => { return Err(format!("Failed to deserialize PJVDelegationCredential {err}")); }
+                Err(err) => { return Err(DelegationError::Json { context: "Failed to deserialize PJVDelegationCredential", source: err }); }
             };
 
             // Recursively call this same function until we get to a point in which hierarchy is empty.
-            let decrypted_delegator = self.verify_delegation_credential(&parsed_delegation_credential, now)?;
-            let decrypted_operations = decrypted_delegator.operations();
+            let (decrypted_delegator, decrypted_index, decrypted_max_depth) = self.verify_delegation_credential(&parsed_delegation_credential, now)?;
+            let index = decrypted_index + 1;
+
+            // The delegation must stay within the maximum depth allowed by the previous ones, and
+            // can only lower it
+            if let Some(max_depth) = decrypted_max_depth && index > max_depth {
+                return Err(DelegationError::DepthExceeded { depth: index, max_depth }.at_delegator(index));
+            }
+            let max_depth = match (decrypted_max_depth, delegator.max_depth().map(|max_depth| index + max_depth)) {
+                (Some(decrypted_max_depth), Some(max_depth)) => Some(decrypted_max_depth.min(max_depth)),
+                (decrypted_max_depth, max_depth) => decrypted_max_depth.or(max_depth),
+            };
 
-            for operation in delegator.operations() {
-                if !decrypted_operations.contains(operation) {
-                    return Err(format!("Operation {operation} not included in the decrypted delegation credential {decrypted_operations:?}"));
+            // Verify that timings are correct and that the signature on the delegator is correct
+            verify_timings(now, delegator.iat(), delegator.exp(), self.leeway).map_err(|e| e.at_delegator(index))?;
+            self.verify_signature(delegator, signature).map_err(|e| e.at_delegator(index))?;
+
+            // Every operation must be implied by one granted to the previous delegator, which may
+            // be broader, e.g. on a resource path containing the one of this delegator
+            let granted_permissions = decrypted_delegator.permissions().map_err(|e| e.at_delegator(index))?;
+            for permission in delegator.permissions().map_err(|e| e.at_delegator(index))? {
+                if !granted_permissions.iter().any(|granted| granted.implies(&permission)) {
+                    return Err(DelegationError::PermissionNotGranted { permission: permission.to_string() }.at_delegator(index));
                 }
             }
 
             if delegator.iss() != decrypted_delegator.sub() {
-                return Err(format!("Mismatch found in delegation credential: decrypted credential's subject is different from the current issuer [{}] [{}]", delegator.iss(), decrypted_delegator.sub()));
+                return Err(DelegationError::DelegatorMismatch { delegatee: decrypted_delegator.sub().clone(), issuer: delegator.iss().clone() }.at_delegator(index));
             }
 
-            Ok(delegator.clone())
+            Ok((delegator.clone(), index, max_depth))
         }
     }
 
-    pub fn verify_verifiable_presentation(&self, presenter_id: String, signed_jwt: String) -> Result<(), String>{
+    fn decode_presentation(&self, presenter_id: String, signed_jwt: String) -> Result<VerifiablePresentation<PJVDelegationCredential>, DelegationError> {
 
-        let ecc_pk = match self.verification_dlt.borrow().get(&presenter_id) {
-            None => { return Err(format!("Could not find presenter {presenter_id} in DLTSim")) }
-            Some(ecc_pk) => { ecc_pk.clone() }
+        let ecc_pk = match self.registry.resolve_key(KeyPurpose::Verification, &presenter_id)? {
+            None => { return Err(DelegationError::PresenterNotFound { id: presenter_id }) }
+            Some(ecc_pk) => { ecc_pk }
         };
 
-        let vp: VerifiablePresentation<PJVDelegationCredential> =
-            VerifiablePresentation::<PJVDelegationCredential>::from_signed_jwt(signed_jwt, &ecc_pk)?;
-        let dc = vp.credential();
+        VerifiablePresentation::<PJVDelegationCredential>::from_signed_jwt(signed_jwt, &ecc_pk, &self.allowed_algorithms)
+    }
 
-        // Get now timestamp and convert it to nanoseconds
-        let now: Duration = match SystemTime::now().duration_since(UNIX_EPOCH) {
-            Ok(duration) => duration,
-            Err(e) => return Err(format!("Error encountered in computing issuance time: {e}")),
-        };
-        let now_ns = now.as_nanos();
+    // Verifies the chain of a presentation and the validity period of its envelope, which belongs to
+    // the last delegation of the chain
+    fn verify_presentation(&self, vp: &VerifiablePresentation<PJVDelegationCredential>, now: u128) -> Result<(), DelegationError> {
+        let (delegator, index, _) = self.verify_delegation_credential(vp.credential(), now)?;
+        verify_validity_period(now, vp.valid_from(), vp.valid_until(), delegator.iat(), delegator.exp(), self.leeway)
+            .map_err(|e| e.at_delegator(index))
+    }
 
-        self.verify_delegation_credential(dc, now_ns)?;
+    pub fn verify_verifiable_presentation(&self, presenter_id: String, signed_jwt: String) -> Result<(), DelegationError>{
 
-        Ok(())
+        let vp = self.decode_presentation(presenter_id, signed_jwt)?;
+        self.verify_presentation(&vp, self.clock.now()?)
+    }
+
+    /// Verifies a presentation answering a request of this verifier. The request must not have
+    /// expired nor been answered yet, so that the presentation cannot be replayed.
+    ///
+    /// # Arguments
+    /// * `presenter_id` - Id of the entity that signed the presentation.
+    /// * `signed_jwt` - Presentation signed by the presenter.
+    /// * `request` - Presentation request previously sent to the presenter.
+    ///
+    /// # Returns
+    /// The result of the verification or the error, if it occurs.
+    pub fn verify_bound_verifiable_presentation(&self, presenter_id: String, signed_jwt: String, request: &PresentationRequest) -> Result<(), DelegationError> {
+
+        let vp = self.decode_presentation(presenter_id, signed_jwt)?;
+
+        let now_ns = self.clock.now()?;
+        verify_presentation_request(now_ns, &vp, request)?;
+        self.verify_presentation(&vp, now_ns)?;
+
+        consume_nonce(now_ns, request, self.replay_cache.as_ref())
     }
 
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::delegation::entities::dtl_sim::{new_dlt_sim, DLTSim};
+    use crate::delegation::clock::fixed_clock::FixedClock;
+    use crate::delegation::credentials::presentation_request::PresentationRequest;
+    use crate::delegation::credentials::signature_algorithm::SignatureAlgorithm;
+    use crate::delegation::entities::verifier::verify_timings;
     use crate::delegation::entities::pjv::pjv_issuer_verifier::PJVIssuerVerifier;
-    use josekit::jwk::Jwk;
+    use crate::delegation::replay::in_memory_replay_cache::InMemoryReplayCache;
+    use crate::delegation::error::DelegationError;
+    use crate::delegation::registry::in_memory_registry::InMemoryRegistry;
+    use std::sync::Arc;
+    use std::thread;
     use std::time::Duration;
 
     #[test]
-    fn test_issuer() -> Result<(), String> {
+    fn test_issuer() -> Result<(), Box<dyn std::error::Error>> {
 
-        let encryption_dlt: DLTSim<Jwk> = new_dlt_sim();
-        let signature_dlt: DLTSim<Jwk> = new_dlt_sim();
+        let registry = InMemoryRegistry::shared();
 
         let owner = String::from("https://vc.example/delegators/d0");
 
         let id = String::from("https://vc.example/delegators/d0");
         let previous_vc = None;
-        let issuer_owner: PJVIssuerVerifier = PJVIssuerVerifier::new(id, encryption_dlt.clone(), signature_dlt.clone())?;
+        let issuer_owner: PJVIssuerVerifier = PJVIssuerVerifier::new(id, registry.clone())?;
         let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
         let credential_id = String::from("http://delegation.example/credentials/1337");
         let valid_from = String::from("2026-01-01T00:00:00Z");
@@ -390,7 +530,7 @@ mod tests {
 
         let id = String::from("https://vc.example/delegators/d1");
         let previous_vc = Some(vc);
-        let issuer: PJVIssuerVerifier = PJVIssuerVerifier::new(id, encryption_dlt.clone(), signature_dlt.clone())?;
+        let issuer: PJVIssuerVerifier = PJVIssuerVerifier::new(id, registry.clone())?;
         let context: Vec<String> = vec![ String::from("https://www.w3.org/ns/credentials/v2") ];
         let credential_id = String::from("http://delegation.example/credentials/1338");
         let valid_from =  String::from("2026-01-01T00:00:00Z");
@@ -403,7 +543,7 @@ mod tests {
 
         let id = String::from("https://vc.example/delegators/d2");
         let previous_vc = Some(vc);
-        let issuer: PJVIssuerVerifier = PJVIssuerVerifier::new(id, encryption_dlt.clone(), signature_dlt.clone())?;
+        let issuer: PJVIssuerVerifier = PJVIssuerVerifier::new(id, registry.clone())?;
         let context: Vec<String> = vec![ String::from("https://www.w3.org/ns/credentials/v2") ];
         let credential_id = String::from("http://delegation.example/credentials/1339");
         let valid_from =  String::from("2026-01-01T00:00:00Z");
@@ -419,8 +559,246 @@ mod tests {
         println!("{vp}");
         println!("{}", vp.len());
 
-        issuer_owner.verify_verifiable_presentation(issuer.id, vp)
+        issuer_owner.verify_verifiable_presentation(issuer.id.clone(), vp.clone())?;
+
+        // Only the owner can decrypt the hierarchy
+        match issuer.verify_verifiable_presentation(issuer.id.clone(), vp) {
+            Err(DelegationError::Jose { .. }) => Ok(()),
+            other => Err(format!("Expected decryption failure, got {other:?}").into()),
+        }
+
+    }
+
+    #[test]
+    fn verify_vp_validity_period() -> Result<(), Box<dyn std::error::Error>> {
+
+        let registry = InMemoryRegistry::shared();
+
+        let owner = String::from("https://vc.example/delegators/d0");
+        let issuer_owner: PJVIssuerVerifier = PJVIssuerVerifier::new(owner.clone(), registry)?;
+        let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
+        let resource_uri: String = String::from("https://vc.example/resources/r1");
+        let permissions: Vec<String> = vec![String::from("p0")];
+
+        // A validFrom following the expiration of the delegation is rejected at issuance
+        match issuer_owner.issue_delegation_verifiable_credential(context.clone(), String::from("http://delegation.example/credentials/1336"), String::from("2999-01-01T00:00:00Z"), String::from("https://vc.example/delegators/d1"), Duration::new(3600, 0), owner.clone(), resource_uri.clone(), permissions.clone(), None) {
+            Err(DelegationError::InconsistentValidityPeriod { .. }) => {}
+            other => { return Err(format!("Expected inconsistent validity period, got {:?}", other.map(|vc| vc.id().clone())).into()); }
+        }
 
+        let validity_period = Duration::new(200 * 365 * 24 * 3600, 0);
+        let vc = issuer_owner.issue_delegation_verifiable_credential(context, String::from("http://delegation.example/credentials/1337"), String::from("2100-01-01T00:00:00Z"), owner.clone(), validity_period, owner.clone(), resource_uri, permissions.clone(), None)?;
+        let vp = issuer_owner.issue_delegation_verifiable_presentation(vc, permissions)?;
+        match issuer_owner.verify_verifiable_presentation(owner, vp) {
+            Err(DelegationError::Delegator { index: 0, source }) if matches!(*source, DelegationError::BeforeValidFrom { .. }) => Ok(()),
+            other => Err(format!("Expected credential not valid yet, got {other:?}").into()),
+        }
     }
 
+    #[test]
+    fn verify_vp_with_clock_skew() -> Result<(), Box<dyn std::error::Error>> {
+
+        let registry = InMemoryRegistry::shared();
+
+        let issuance: u128 = 1_780_000_000_000_000_000;
+        let clock = Arc::new(FixedClock::new(issuance));
+        let owner = String::from("https://vc.example/delegators/d0");
+        let mut issuer_owner: PJVIssuerVerifier = PJVIssuerVerifier::new(owner.clone(), registry)?;
+        issuer_owner.set_clock(clock.clone());
+        let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
+        let resource_uri: String = String::from("https://vc.example/resources/r1");
+        let permissions: Vec<String> = vec![String::from("p0")];
+        let validity_period = Duration::new(3600, 0);
+        let vc = issuer_owner.issue_delegation_verifiable_credential(context, String::from("http://delegation.example/credentials/1337"), String::from("2026-01-01T00:00:00Z"), owner.clone(), validity_period, owner.clone(), resource_uri, permissions.clone(), None)?;
+        let vp = issuer_owner.issue_delegation_verifiable_presentation(vc, permissions)?;
+
+        // Replay the verification a few seconds before the issuance, as a verifier whose clock runs behind
+        clock.set(issuance - 2_000_000_000)?;
+        match issuer_owner.verify_verifiable_presentation(owner.clone(), vp.clone()) {
+            Err(DelegationError::Delegator { index: 0, source }) if matches!(*source, DelegationError::NotYetValid { .. }) => {}
+            other => { return Err(format!("Expected credential not valid yet, got {other:?}").into()); }
+        }
+        issuer_owner.set_leeway(Duration::new(2, 0));
+        issuer_owner.verify_verifiable_presentation(owner.clone(), vp.clone())?;
+
+        // An expiration at the end of time does not overflow with the leeway
+        verify_timings(u128::MAX, &issuance.to_string(), &u128::MAX.to_string(), Duration::new(2, 0))?;
+
+        clock.set(issuance + validity_period.as_nanos() + 2_000_000_001)?;
+        match issuer_owner.verify_verifiable_presentation(owner, vp) {
+            Err(DelegationError::Delegator { index: 0, source }) if matches!(*source, DelegationError::Expired { .. }) => Ok(()),
+            other => Err(format!("Expected expired credential, got {other:?}").into()),
+        }
+    }
+
+    #[test]
+    fn verify_bound_vp() -> Result<(), Box<dyn std::error::Error>> {
+
+        let registry = InMemoryRegistry::shared();
+
+        let owner = String::from("https://vc.example/delegators/d0");
+        let issuer_owner: PJVIssuerVerifier = PJVIssuerVerifier::new(owner.clone(), registry)?;
+        let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
+        let valid_from = String::from("2026-01-01T00:00:00Z");
+        let delegatee_id = String::from("https://vc.example/delegators/d1");
+        let resource_uri: String = String::from("https://vc.example/resources/r1");
+        let permissions: Vec<String> = vec![String::from("p0"), String::from("p1")];
+        let vc = issuer_owner.issue_delegation_verifiable_credential(context, String::from("http://delegation.example/credentials/1337"), valid_from, delegatee_id, Duration::new(3600, 0), owner.clone(), resource_uri, permissions.clone(), None)?;
+
+        let request = PresentationRequest::new(owner.clone(), Duration::new(60, 0))?;
+        let vp = issuer_owner.issue_bound_delegation_verifiable_presentation(vc.clone(), permissions.clone(), &request)?;
+        issuer_owner.verify_bound_verifiable_presentation(owner.clone(), vp.clone(), &request)?;
+
+        match issuer_owner.verify_bound_verifiable_presentation(owner.clone(), vp.clone(), &request) {
+            Err(DelegationError::NonceReused { .. }) => {}
+            other => { return Err(format!("Expected reused nonce, got {other:?}").into()); }
+        }
+
+        let other_request = PresentationRequest::new(owner.clone(), Duration::new(60, 0))?;
+        match issuer_owner.verify_bound_verifiable_presentation(owner, vp, &other_request) {
+            Err(DelegationError::RequestMismatch { .. }) => Ok(()),
+            other => Err(format!("Expected request mismatch, got {other:?}").into()),
+        }
+    }
+
+    #[test]
+    fn verify_vp_with_algorithms() -> Result<(), Box<dyn std::error::Error>> {
+
+        let registry = InMemoryRegistry::shared();
+
+        let owner = String::from("https://vc.example/delegators/d0");
+        let mut issuer_owner: PJVIssuerVerifier = PJVIssuerVerifier::with_signature_algorithm(owner.clone(), registry.clone(), SignatureAlgorithm::ES256, InMemoryReplayCache::shared())?;
+        let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
+        let valid_from = String::from("2026-01-01T00:00:00Z");
+        let resource_uri: String = String::from("https://vc.example/resources/r1");
+        let permissions: Vec<String> = vec![String::from("p0"), String::from("p1")];
+        let vc = issuer_owner.issue_delegation_verifiable_credential(context.clone(), String::from("http://delegation.example/credentials/1337"), valid_from.clone(), String::from("https://vc.example/delegators/d1"), Duration::new(3600, 0), owner.clone(), resource_uri.clone(), permissions.clone(), None)?;
+
+        // Delegators of the same chain can sign with different algorithms
+        let id = String::from("https://vc.example/delegators/d1");
+        let issuer: PJVIssuerVerifier = PJVIssuerVerifier::with_signature_algorithm(id.clone(), registry, SignatureAlgorithm::ES256K, InMemoryReplayCache::shared())?;
+        let vc = issuer.issue_delegation_verifiable_credential(context, String::from("http://delegation.example/credentials/1338"), valid_from, String::from("https://vc.example/delegators/d2"), Duration::new(3600, 0), owner, resource_uri, permissions, Some(vc))?;
+
+        let vp = issuer.issue_delegation_verifiable_presentation(vc, vec![String::from("p1")])?;
+        issuer_owner.verify_verifiable_presentation(id.clone(), vp.clone())?;
+
+        // The signature of the delegator issued by the owner is no longer accepted
+        issuer_owner.set_allowed_algorithms(vec![SignatureAlgorithm::ES256K]);
+        match issuer_owner.verify_verifiable_presentation(id, vp) {
+            Err(DelegationError::Delegator { index: 0, source }) if matches!(*source, DelegationError::AlgorithmNotAllowed { .. }) => Ok(()),
+            other => Err(format!("Expected algorithm of delegation 0 not allowed, got {other:?}").into()),
+        }
+    }
+
+    #[test]
+    fn verify_vp_with_implied_operations() -> Result<(), Box<dyn std::error::Error>> {
+
+        let registry = InMemoryRegistry::shared();
+
+        let owner = String::from("https://vc.example/delegators/d0");
+        let issuer_owner: PJVIssuerVerifier = PJVIssuerVerifier::new(owner.clone(), registry.clone())?;
+        let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
+        let valid_from = String::from("2026-01-01T00:00:00Z");
+        let delegatee_id = String::from("https://vc.example/delegators/d1");
+        let validity_period: Duration = Duration::new(3600, 0);
+        let vc = issuer_owner.issue_delegation_verifiable_credential(context.clone(), String::from("http://delegation.example/credentials/1337"), valid_from.clone(), delegatee_id.clone(), validity_period, owner.clone(), String::from("https://vc.example/resources/**"), vec![String::from("write")], None)?;
+
+        // Reading a resource below the granted path is implied by writing the whole path
+        let issuer = PJVIssuerVerifier::new(delegatee_id, registry.clone())?;
+        let narrowed_vc = issuer.issue_delegation_verifiable_credential(context.clone(), String::from("http://delegation.example/credentials/1338"), valid_from.clone(), String::from("https://vc.example/delegators/d2"), validity_period, owner.clone(), String::from("https://vc.example/resources/r1"), vec![String::from("read")], Some(vc.clone()))?;
+        let vp = issuer.issue_delegation_verifiable_presentation(narrowed_vc, vec![String::from("read")])?;
+        issuer_owner.verify_verifiable_presentation(issuer.id.clone(), vp)?;
+
+        let escalated_vc = issuer.issue_delegation_verifiable_credential(context, String::from("http://delegation.example/credentials/1339"), valid_from, String::from("https://vc.example/delegators/d2"), validity_period, owner, String::from("https://vc.example/resources/r1"), vec![String::from("admin")], Some(vc))?;
+        let vp = issuer.issue_delegation_verifiable_presentation(escalated_vc, vec![String::from("admin")])?;
+        match issuer_owner.verify_verifiable_presentation(issuer.id.clone(), vp) {
+            Err(DelegationError::Delegator { index: 1, source }) if matches!(*source, DelegationError::PermissionNotGranted { .. }) => Ok(()),
+            other => Err(format!("Expected permission not granted at delegation 1, got {other:?}").into()),
+        }
+    }
+
+    #[test]
+    fn verify_vp_with_max_depth() -> Result<(), Box<dyn std::error::Error>> {
+
+        let registry = InMemoryRegistry::shared();
+
+        let owner = String::from("https://vc.example/delegators/d0");
+        let issuer_owner: PJVIssuerVerifier = PJVIssuerVerifier::new(owner.clone(), registry.clone())?;
+        let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
+        let valid_from = String::from("2026-01-01T00:00:00Z");
+        let delegator_id = String::from("https://vc.example/delegators/d1");
+        let validity_period: Duration = Duration::new(3600, 0);
+        let resource_uri: String = String::from("https://vc.example/resources/r1");
+        let permissions: Vec<String> = vec![String::from("p0")];
+        let vc = issuer_owner.issue_delegation_verifiable_credential_with_max_depth(context.clone(), String::from("http://delegation.example/credentials/1337"), valid_from.clone(), delegator_id.clone(), validity_period, owner.clone(), resource_uri.clone(), permissions.clone(), None, Some(1))?;
+
+        // The delegatee of the delegator may use the operation but not pass it on
+        let issuer = PJVIssuerVerifier::new(delegator_id, registry.clone())?;
+        let delegatee_id = String::from("https://vc.example/delegators/d2");
+        let vc = issuer.issue_delegation_verifiable_credential(context.clone(), String::from("http://delegation.example/credentials/1338"), valid_from.clone(), delegatee_id.clone(), validity_period, owner.clone(), resource_uri.clone(), permissions.clone(), Some(vc))?;
+        if vc.credential().delegator().max_depth() != Some(0) {
+            return Err(format!("Expected a credential that cannot be delegated, got maximum depth {:?}", vc.credential().delegator().max_depth()).into());
+        }
+        let delegatee = PJVIssuerVerifier::new(delegatee_id.clone(), registry.clone())?;
+        let last_id = String::from("https://vc.example/delegators/d3");
+        match delegatee.issue_delegation_verifiable_credential(context.clone(), String::from("http://delegation.example/credentials/1339"), valid_from.clone(), last_id.clone(), validity_period, owner.clone(), resource_uri.clone(), permissions.clone(), Some(vc.clone())) {
+            Err(DelegationError::NotRedelegable) => {}
+            other => { return Err(format!("Expected a credential that cannot be delegated, got {:?}", other.map(|vc| vc.id().clone())).into()); }
+        }
+
+        let vp = issuer.issue_delegation_verifiable_presentation(vc.clone(), permissions.clone())?;
+        issuer_owner.verify_verifiable_presentation(issuer.id.clone(), vp)?;
+
+        // The maximum depth is signed together with the delegator, so it cannot be removed to
+        // delegate the credential further
+        let mut value = serde_json::to_value(vc.credential())?;
+        if let Some(claims) = value["claims"].as_object_mut() {
+            claims.remove("md");
+        }
+        let mut unlimited_vc = vc;
+        *unlimited_vc.mut_credential() = serde_json::from_value(value)?;
+        let last_vc = delegatee.issue_delegation_verifiable_credential(context, String::from("http://delegation.example/credentials/1339"), valid_from, last_id, validity_period, owner, resource_uri, permissions.clone(), Some(unlimited_vc))?;
+        let vp = delegatee.issue_delegation_verifiable_presentation(last_vc, permissions)?;
+        match issuer_owner.verify_verifiable_presentation(delegatee_id, vp) {
+            Err(DelegationError::Delegator { index: 1, source }) if matches!(*source, DelegationError::Jose { .. }) => Ok(()),
+            other => Err(format!("Expected a failure of delegation 1, got {other:?}").into()),
+        }
+    }
+
+    #[test]
+    fn verify_vp_concurrently() -> Result<(), Box<dyn std::error::Error>> {
+
+        let registry = InMemoryRegistry::shared();
+
+        let owner = String::from("https://vc.example/delegators/d0");
+        let issuer_owner: Arc<PJVIssuerVerifier> = Arc::new(PJVIssuerVerifier::new(owner.clone(), registry.clone())?);
+        let context: Vec<String> = vec![String::from("https://www.w3.org/ns/credentials/v2")];
+        let valid_from = String::from("2026-01-01T00:00:00Z");
+        let delegatee_id = String::from("https://vc.example/delegators/d1");
+        let validity_period: Duration = Duration::new(3600, 0);
+        let resource_uri: String = String::from("https://vc.example/resources/r1");
+        let permissions: Vec<String> = vec![String::from("p0"), String::from("p1")];
+        let vc = issuer_owner.issue_delegation_verifiable_credential(context.clone(), String::from("http://delegation.example/credentials/1337"), valid_from.clone(), delegatee_id.clone(), validity_period, owner.clone(), resource_uri.clone(), permissions.clone(), None)?;
+
+        let issuer = PJVIssuerVerifier::new(delegatee_id, registry.clone())?;
+        let vc = issuer.issue_delegation_verifiable_credential(context, String::from("http://delegation.example/credentials/1338"), valid_from, String::from("https://vc.example/delegators/d2"), validity_period, owner, resource_uri, permissions, Some(vc))?;
+        let vp = issuer.issue_delegation_verifiable_presentation(vc, vec![String::from("p1")])?;
+
+        // The owner verifies presentations from several threads at once
+        let handles: Vec<_> = (0..4).map(|_| {
+            let issuer_owner = issuer_owner.clone();
+            let presenter_id = issuer.id.clone();
+            let vp = vp.clone();
+            thread::spawn(move || issuer_owner.verify_verifiable_presentation(presenter_id, vp))
+        }).collect();
+
+        for handle in handles {
+            match handle.join() {
+                Ok(result) => result?,
+                Err(_) => { return Err("Verification thread panicked".into()); }
+            }
+        }
+
+        Ok(())
+    }
 }
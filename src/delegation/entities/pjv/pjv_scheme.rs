@@ -0,0 +1,91 @@
+use crate::delegation::credentials::pjv::pjv_delegation_credential::PJVDelegationCredential;
+use crate::delegation::credentials::permission::Permission;
+use crate::delegation::credentials::presentation_request::PresentationRequest;
+use crate::delegation::credentials::verifiable_credential::VerifiableCredential;
+use crate::delegation::entities::pjv::pjv_issuer_verifier::PJVIssuerVerifier;
+use crate::delegation::error::DelegationError;
+use crate::delegation::registry::verifiable_data_registry::VerifiableDataRegistry;
+use crate::delegation::replay::in_memory_replay_cache::InMemoryReplayCache;
+use crate::delegation::replay::replay_cache::ReplayCache;
+use crate::delegation::traits::delegation_scheme::{CredentialParameters, DelegationScheme, SignedPresentation};
+use std::sync::Arc;
+
+/// Delegation scheme based on signature chains, where each credential grants operations on a single
+/// resource of its owner. Permissions on that resource are mapped to their actions, and
+/// presentations are signed by the issuer of the credential.
+pub struct PJVScheme {
+    registry: Arc<dyn VerifiableDataRegistry>,
+    replay_cache: Arc<dyn ReplayCache>,
+    owner: String,
+    resource_uri: String,
+}
+
+impl PJVScheme {
+    pub fn new(registry: Arc<dyn VerifiableDataRegistry>, owner: String, resource_uri: String) -> Self {
+        Self::with_replay_cache(registry, InMemoryReplayCache::shared(), owner, resource_uri)
+    }
+
+    /// Creates the scheme with entities sharing the given cache of the nonces already used.
+    pub fn with_replay_cache(registry: Arc<dyn VerifiableDataRegistry>, replay_cache: Arc<dyn ReplayCache>, owner: String, resource_uri: String) -> Self {
+        PJVScheme { registry, replay_cache, owner, resource_uri }
+    }
+
+    // Maps the permissions on the resource to their actions, the credentials carrying no constraints
+    fn operations(&self, permissions: &[Permission]) -> Result<Vec<String>, DelegationError> {
+        let mut operations: Vec<String> = vec![];
+        for permission in permissions {
+            if *permission.resource() != self.resource_uri {
+                return Err(DelegationError::PermissionNotGranted { permission: permission.to_string() })
+            }
+            if !permission.constraints().is_empty() {
+                return Err(DelegationError::InvalidPermission { permission: permission.to_string(), reason: "PJV credentials cannot carry constraints" })
+            }
+            match permission.action() {
+                Some(action) => operations.push(action.clone()),
+                None => { return Err(DelegationError::InvalidPermission { permission: permission.to_string(), reason: "PJV credentials cannot carry opaque permissions" }) }
+            }
+        }
+        Ok(operations)
+    }
+}
+
+impl DelegationScheme for PJVScheme {
+    type Credential = PJVDelegationCredential;
+    type Issuer = Arc<PJVIssuerVerifier>;
+    type Verifier = Arc<PJVIssuerVerifier>;
+
+    fn name(&self) -> &str {
+        "PJVs"
+    }
+
+    fn issuer(&self, id: String) -> Result<Self::Issuer, DelegationError> {
+        Ok(Arc::new(PJVIssuerVerifier::with_replay_cache(id, self.registry.clone(), self.replay_cache.clone())?))
+    }
+
+    // Only the owner can decrypt the hierarchy of the credentials
+    fn verifier(&self, owner: &Self::Issuer) -> Result<Self::Verifier, DelegationError> {
+        Ok(owner.clone())
+    }
+
+    fn issue_verifiable_credential(&self, issuer: &Self::Issuer, parameters: &CredentialParameters,
+                                   parent: Option<VerifiableCredential<Self::Credential>>)
+                                   -> Result<VerifiableCredential<Self::Credential>, DelegationError> {
+        issuer.issue_delegation_verifiable_credential(
+            parameters.context.clone(), parameters.credential_id.clone(), parameters.valid_from.clone(),
+            parameters.delegatee_id.clone(), parameters.validity_period, self.owner.clone(),
+            self.resource_uri.clone(), self.operations(&parameters.permissions)?, parent
+        )
+    }
+
+    fn issue_verifiable_presentation(&self, issuer: &Self::Issuer, _holder: &Self::Issuer,
+                                     vc: VerifiableCredential<Self::Credential>, disclosed_permissions: Vec<Permission>,
+                                     request: &PresentationRequest) -> Result<SignedPresentation, DelegationError> {
+        let jwt = issuer.issue_bound_delegation_verifiable_presentation(vc, self.operations(&disclosed_permissions)?, request)?;
+        Ok(SignedPresentation { presenter_id: issuer.id().clone(), jwt })
+    }
+
+    fn verify_verifiable_presentation(&self, verifier: &Self::Verifier, presentation: &SignedPresentation,
+                                      request: &PresentationRequest) -> Result<(), DelegationError> {
+        verifier.verify_bound_verifiable_presentation(presentation.presenter_id.clone(), presentation.jwt.clone(), request)
+    }
+}
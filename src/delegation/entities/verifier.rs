@@ -1,23 +1,109 @@
 use std::str::FromStr;
+use std::time::Duration;
+use crate::delegation::credentials::presentation_request::PresentationRequest;
+use crate::delegation::credentials::verifiable_presentation::VerifiablePresentation;
+use crate::delegation::error::DelegationError;
+use crate::delegation::replay::replay_cache::ReplayCache;
+use crate::delegation::traits::credential::Credential;
+use crate::delegation::utils::utils::parse_date_time;
 
-pub fn verify_timings(now: u128, iat: &String, exp: &String) -> Result<(), String> {
+fn parse_timestamp(timestamp: &str) -> Result<u128, DelegationError> {
+    match u128::from_str(timestamp) {
+        Ok(timestamp_ns) => Ok(timestamp_ns),
+        Err(err) => Err(DelegationError::InvalidTimestamp { timestamp: timestamp.to_string(), source: err }),
+    }
+}
+
+/// Checks that a delegation is valid at the given time.
+///
+/// # Arguments
+/// * `now` - Current time of the verifier in nanoseconds.
+/// * `iat` - Issuance time of the delegation in nanoseconds.
+/// * `exp` - Expiration time of the delegation in nanoseconds.
+/// * `leeway` - Tolerated skew between the clocks of the issuer and of the verifier.
+///
+/// # Returns
+/// The result of the check or the error, if the delegation is not valid yet or has expired.
+pub fn verify_timings(now: u128, iat: &str, exp: &str, leeway: Duration) -> Result<(), DelegationError> {
+
+    let iat_ns = parse_timestamp(iat)?;
+    let exp_ns = parse_timestamp(exp)?;
+    let leeway = leeway.as_nanos();
 
-    let iat_ns = match u128::from_str(iat) {
-        Ok(iat) => { iat }
-        Err(err) => { return Err(format!("Could not parse timestamp iat {} [{err}]", iat)); }
+    if now.saturating_add(leeway) < iat_ns {
+        return Err(DelegationError::NotYetValid { now, iat: iat_ns });
+    } else if now > exp_ns.saturating_add(leeway) {
+        return Err(DelegationError::Expired { now, exp: exp_ns });
+    } else if iat_ns > exp_ns {
+        return Err(DelegationError::IssuedAfterExpiration { iat: iat_ns, exp: exp_ns });
+    }
+
+    Ok(())
+}
+
+/// Checks that the RFC 3339 dates of the envelope of a credential are consistent with the
+/// delegation it carries. The envelope can only narrow the validity of the delegation: `validFrom`
+/// must not follow `exp`, while `validUntil`, if any, must lie between `validFrom`, `iat` and `exp`.
+///
+/// # Arguments
+/// * `valid_from` - The `validFrom` date of the envelope.
+/// * `valid_until` - The `validUntil` date of the envelope, if any.
+/// * `iat` - Issuance time of the delegation in nanoseconds.
+/// * `exp` - Expiration time of the delegation in nanoseconds.
+///
+/// # Returns
+/// A result wrapping the dates of the envelope in nanoseconds or the error, if they are not consistent.
+pub fn check_validity_period(valid_from: &str, valid_until: Option<&String>, iat: &str, exp: &str) -> Result<(u128, Option<u128>), DelegationError> {
+    let iat_ns = parse_timestamp(iat)?;
+    let exp_ns = parse_timestamp(exp)?;
+    let valid_from_ns = parse_date_time(valid_from)?;
+    let valid_until_ns = match valid_until {
+        Some(valid_until) => Some(parse_date_time(valid_until)?),
+        None => None,
     };
-    let exp_ns = match u128::from_str(exp) {
-        Ok(iat) => { iat }
-        Err(err) => { return Err(format!("Could not parse timestamp exp {} [{err}]", exp)); }
+
+    let consistent = valid_from_ns <= exp_ns && match valid_until_ns {
+        Some(valid_until_ns) => valid_from_ns <= valid_until_ns && iat_ns <= valid_until_ns && valid_until_ns <= exp_ns,
+        None => true,
     };
+    if !consistent {
+        return Err(DelegationError::InconsistentValidityPeriod {
+            valid_from: valid_from.to_string(), valid_until: valid_until.cloned(), iat: iat_ns, exp: exp_ns
+        });
+    }
 
-    if now < iat_ns {
-        return Err(format!("Timestamp is less than issuance time {iat_ns}"));
-    } else if now > exp_ns {
-        return Err(format!("Timestamp is greater than expiration time {exp_ns}"));
-    } else if iat_ns > exp_ns {
-        return Err(format!("Credential is issued after its expiration date {iat_ns} > {exp_ns}"));
+    Ok((valid_from_ns, valid_until_ns))
+}
+
+/// Checks that the envelope of a credential is consistent with its delegation, see
+/// `check_validity_period`, and valid at the given time, up to the tolerated clock skew.
+pub fn verify_validity_period(now: u128, valid_from: &str, valid_until: Option<&String>, iat: &str, exp: &str, leeway: Duration) -> Result<(), DelegationError> {
+    let (valid_from_ns, valid_until_ns) = check_validity_period(valid_from, valid_until, iat, exp)?;
+    let leeway = leeway.as_nanos();
+
+    if now.saturating_add(leeway) < valid_from_ns {
+        return Err(DelegationError::BeforeValidFrom { now, valid_from: valid_from.to_string() });
+    }
+    if let (Some(valid_until), Some(valid_until_ns)) = (valid_until, valid_until_ns) && now > valid_until_ns.saturating_add(leeway) {
+        return Err(DelegationError::AfterValidUntil { now, valid_until: valid_until.clone() });
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Checks that the presentation answers the request of the verifier and that the request has not
+/// expired yet.
+pub fn verify_presentation_request<C: Credential>(now: u128, vp: &VerifiablePresentation<C>, request: &PresentationRequest) -> Result<(), DelegationError> {
+    if now > request.expires_at() {
+        return Err(DelegationError::RequestExpired { now, expires_at: request.expires_at() });
+    }
+    vp.verify_request(request)
+}
+
+/// Marks the nonce of the request as used, failing if a presentation already answered it.
+pub fn consume_nonce(now: u128, request: &PresentationRequest, replay_cache: &dyn ReplayCache) -> Result<(), DelegationError> {
+    match replay_cache.insert(request.nonce(), request.expires_at(), now)? {
+        true => Ok(()),
+        false => Err(DelegationError::NonceReused { nonce: request.nonce().clone() }),
+    }
+}
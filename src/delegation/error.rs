@@ -0,0 +1,285 @@
+use ark_serialize::SerializationError;
+use josekit::JoseError;
+use rayon::ThreadPoolBuildError;
+use std::error::Error;
+use std::fmt::Display;
+use std::io;
+use std::num::ParseIntError;
+use std::string::FromUtf8Error;
+use std::time::SystemTimeError;
+use vb_accumulator::error::VBAccumulatorError;
+
+/// Errors returned by the issuers, holders and verifiers of delegation credentials.
+#[derive(Debug)]
+pub enum DelegationError {
+    // =============================================================================================
+    // Encoding and cryptographic primitives
+    // =============================================================================================
+    /// An accumulator, witness or scalar could not be (de)serialized.
+    Serialization(SerializationError),
+    /// A multibase encoded element could not be decoded.
+    Decoding(multibase::Error),
+    /// A credential or presentation could not be converted from or to JSON.
+    Json { context: &'static str, source: serde_json::Error },
+    /// A JOSE operation (key handling, signature, encryption) failed.
+    Jose { context: &'static str, source: JoseError },
+    /// A decrypted payload is not valid UTF-8.
+    Utf8(FromUtf8Error),
+    /// The accumulator rejected an addition or a removal.
+    Accumulator(VBAccumulatorError),
+    /// The signature algorithm is not supported or not allowed by the verifier.
+    AlgorithmNotAllowed { alg: String },
+    /// The key cannot be used with any supported signature algorithm.
+    UnsupportedKey { kty: String, crv: Option<String> },
+    /// The key id in the JOSE header differs from the one of the key in the registry.
+    KeyIdMismatch { header: String, key: String },
+    /// The serialized value is not a JSON object.
+    NotAnObject,
+    /// The registry could not be read or written.
+    Io { context: &'static str, source: io::Error },
+    /// A thread panicked while holding a lock on shared state.
+    LockPoisoned,
+
+    // =============================================================================================
+    // Accumulator verification
+    // =============================================================================================
+    /// The number of witnesses differs from the number of elements to be verified.
+    WitnessCountMismatch { witnesses: usize, elements: usize },
+    /// The witness does not prove the membership of the element in the accumulator.
+    MembershipNotVerified { element: String },
+    /// The pool of workers verifying witnesses in parallel could not be built.
+    WorkerPool(ThreadPoolBuildError),
+    /// The randomized batch of membership checks does not hold.
+    BatchNotVerified,
+
+    // =============================================================================================
+    // Timings
+    // =============================================================================================
+    /// The system clock is set before the Unix epoch.
+    Clock(SystemTimeError),
+    /// A timestamp is not a number of nanoseconds.
+    InvalidTimestamp { timestamp: String, source: ParseIntError },
+    /// The credential is not valid yet.
+    NotYetValid { now: u128, iat: u128 },
+    /// The credential has expired.
+    Expired { now: u128, exp: u128 },
+    /// The credential is issued after its expiration.
+    IssuedAfterExpiration { iat: u128, exp: u128 },
+    /// A date of the envelope of a credential is not an RFC 3339 date time.
+    InvalidDateTime { date: String, source: time::error::Parse },
+    /// A timestamp cannot be represented as an RFC 3339 date time.
+    TimestampOutOfRange { timestamp: u128 },
+    /// The `validFrom` date of the envelope has not been reached yet.
+    BeforeValidFrom { now: u128, valid_from: String },
+    /// The `validUntil` date of the envelope has passed.
+    AfterValidUntil { now: u128, valid_until: String },
+    /// The dates of the envelope do not lie within the issuance and expiration time of the
+    /// delegation.
+    InconsistentValidityPeriod { valid_from: String, valid_until: Option<String>, iat: u128, exp: u128 },
+
+    // =============================================================================================
+    // Registry lookups
+    // =============================================================================================
+    /// The issuer has not published its key material.
+    IssuerNotFound { id: String },
+    /// The presenter has not published its verification key.
+    PresenterNotFound { id: String },
+    /// The resource owner has not published its encryption key.
+    OwnerNotFound { id: String },
+    /// The accumulator value of a delegation has not been published.
+    DelegationNotPublished { key: String },
+    /// The accumulator value of a delegation has already been published.
+    DelegationAlreadyPublished { key: String },
+    /// The update does not follow the latest accumulator value of a delegation, or restores a
+    /// value that was already published for it.
+    InvalidAccumulatorUpdate { key: String, reason: &'static str },
+    /// The accumulator value held by the holder was never published by the issuer.
+    AccumulatorValueNotPublished { accumulator_value: String },
+    /// The credential was not issued by this issuer.
+    CredentialNotIssued { credential_id: String },
+    /// The issuer has already issued a credential with this id.
+    CredentialAlreadyIssued { credential_id: String },
+    /// The entity has already published this material in the registry.
+    KeyAlreadyPublished { id: String },
+    /// The entity has not published the key to be rotated.
+    KeyNotPublished { id: String },
+
+    // =============================================================================================
+    // Credential content
+    // =============================================================================================
+    /// No permission is granted, disclosed or revoked.
+    EmptyPermissions,
+    /// The presentation does not disclose any permission.
+    EmptyPresentation,
+    /// The permission is not a resource and an action, optionally followed by constraints.
+    InvalidPermission { permission: String, reason: &'static str },
+    /// The caveat is not a time window, a network, a maximum amount or an audience.
+    InvalidCaveat { caveat: String, reason: &'static str },
+    /// The permission is not included in the parent delegation credential.
+    PermissionNotGranted { permission: String },
+    /// More permissions are granted than those included in the parent delegation credential.
+    TooManyPermissions { requested: usize, granted: usize },
+    /// The credential is not well formed.
+    MalformedCredential { reason: String },
+
+    // =============================================================================================
+    // Delegation chain
+    // =============================================================================================
+    /// The delegatee of a delegation is not the issuer of the following one.
+    DelegatorMismatch { delegatee: String, issuer: String },
+    /// The root of the chain is not issued by the verifier acting as resource owner.
+    RootMismatch { verifier: String, owner: String, issuer: String },
+    /// The delegation credential may not be delegated further.
+    NotRedelegable,
+    /// The delegation is deeper in the chain than a delegator allowed it to be.
+    DepthExceeded { depth: usize, max_depth: usize },
+    /// A caveat of the parent delegation is dropped or relaxed by the following one.
+    CaveatLoosened { caveat: String },
+    /// The request does not satisfy a caveat of the delegation.
+    CaveatNotSatisfied { caveat: String },
+    /// The delegation has been revoked by its issuer.
+    Revoked { key: String },
+    /// The presentation is not signed by the subject of the delegation credential.
+    PresenterMismatch { presenter: String, subject: String },
+    /// The presentation does not answer the request of the verifier.
+    RequestMismatch { nonce: Option<String>, audience: Option<String> },
+    /// The presentation request of the verifier has expired.
+    RequestExpired { now: u128, expires_at: u128 },
+    /// The nonce of the presentation request has already been used.
+    NonceReused { nonce: String },
+    /// Every permission of the credential has been revoked.
+    AllPermissionsRevoked,
+    /// A delegation of the chain failed verification. The index is the position of the delegation
+    /// in the chain, 0 being the one issued by the root delegator.
+    Delegator { index: usize, source: Box<DelegationError> },
+}
+
+impl DelegationError {
+    /// Wraps the error with the index of the offending delegation in the chain.
+    pub fn at_delegator(self, index: usize) -> Self {
+        DelegationError::Delegator { index, source: Box::new(self) }
+    }
+
+    /// Returns the innermost error, skipping the delegation indices.
+    pub fn root_cause(&self) -> &DelegationError {
+        match self {
+            DelegationError::Delegator { source, .. } => source.root_cause(),
+            _ => self,
+        }
+    }
+}
+
+impl Display for DelegationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DelegationError::Serialization(err) => write!(f, "Error in serialization of element: [{err}]"),
+            DelegationError::Decoding(err) => write!(f, "Error in decoding element: [{err}]"),
+            DelegationError::Json { context, source } => write!(f, "{context} [{source}]"),
+            DelegationError::Jose { context, source } => write!(f, "{context} [{source}]"),
+            DelegationError::Utf8(err) => write!(f, "Decrypted payload is not valid UTF-8 [{err}]"),
+            DelegationError::Accumulator(err) => write!(f, "Accumulator operation failed: [{err:?}]"),
+            DelegationError::AlgorithmNotAllowed { alg } => write!(f, "Signature algorithm {alg} is not allowed"),
+            DelegationError::UnsupportedKey { kty, crv } => write!(f, "Key of type {kty} on curve {crv:?} is not supported"),
+            DelegationError::KeyIdMismatch { header, key } => write!(f, "Key id {header} in the header does not match key {key} in the registry"),
+            DelegationError::NotAnObject => write!(f, "Serialized value is not an object"),
+            DelegationError::Io { context, source } => write!(f, "{context} [{source}]"),
+            DelegationError::LockPoisoned => write!(f, "A thread panicked while holding a lock on shared state"),
+            DelegationError::WitnessCountMismatch { witnesses, elements } =>
+                write!(f, "Witnesses length does not match elements [{elements} - {witnesses}]"),
+            DelegationError::MembershipNotVerified { element } => write!(f, "Could not verify membership for element {element}"),
+            DelegationError::WorkerPool(err) => write!(f, "Could not build the pool of workers verifying witnesses [{err}]"),
+            DelegationError::BatchNotVerified => write!(f, "Batch verification of the membership witnesses failed"),
+            DelegationError::Clock(err) => write!(f, "Error encountered in computing current time: {err}"),
+            DelegationError::InvalidTimestamp { timestamp, source } => write!(f, "Could not parse timestamp {timestamp} [{source}]"),
+            DelegationError::NotYetValid { now, iat } => write!(f, "Timestamp {now} is less than issuance time {iat}"),
+            DelegationError::Expired { now, exp } => write!(f, "Timestamp {now} is greater than expiration time {exp}"),
+            DelegationError::IssuedAfterExpiration { iat, exp } => write!(f, "Credential is issued after its expiration date {iat} > {exp}"),
+            DelegationError::InvalidDateTime { date, source } => write!(f, "Could not parse RFC 3339 date time {date} [{source}]"),
+            DelegationError::TimestampOutOfRange { timestamp } => write!(f, "Timestamp {timestamp} cannot be represented as an RFC 3339 date time"),
+            DelegationError::BeforeValidFrom { now, valid_from } => write!(f, "Timestamp {now} is before the validFrom date {valid_from}"),
+            DelegationError::AfterValidUntil { now, valid_until } => write!(f, "Timestamp {now} is after the validUntil date {valid_until}"),
+            DelegationError::InconsistentValidityPeriod { valid_from, valid_until, iat, exp } =>
+                write!(f, "Validity period [{valid_from} - {valid_until:?}] is not consistent with the delegation [{iat} - {exp}]"),
+            DelegationError::IssuerNotFound { id } => write!(f, "Could not find issuer {id} in the registry"),
+            DelegationError::PresenterNotFound { id } => write!(f, "Could not find presenter {id} in the registry"),
+            DelegationError::OwnerNotFound { id } => write!(f, "Resource owner [{id}] has not published its public key in the registry"),
+            DelegationError::DelegationNotPublished { key } => write!(f, "Could not find accumulator value for delegation {key} in the registry"),
+            DelegationError::DelegationAlreadyPublished { key } => write!(f, "Accumulator value for delegation {key} has already been published"),
+            DelegationError::InvalidAccumulatorUpdate { key, reason } => write!(f, "Accumulator update for delegation {key} is not valid: {reason}"),
+            DelegationError::AccumulatorValueNotPublished { accumulator_value } => write!(f, "Accumulator value {accumulator_value} was never published"),
+            DelegationError::CredentialNotIssued { credential_id } => write!(f, "Credential {credential_id} was not issued by this issuer"),
+            DelegationError::CredentialAlreadyIssued { credential_id } => write!(f, "Credential {credential_id} has already been issued by this issuer"),
+            DelegationError::KeyAlreadyPublished { id } => write!(f, "Entity {id} has already published this material in the registry"),
+            DelegationError::KeyNotPublished { id } => write!(f, "Entity {id} has not published the key to be rotated"),
+            DelegationError::EmptyPermissions => write!(f, "Permissions array is empty"),
+            DelegationError::EmptyPresentation => write!(f, "VerifiablePresentation is empty"),
+            DelegationError::InvalidPermission { permission, reason } => write!(f, "Permission {permission} is not valid: {reason}"),
+            DelegationError::InvalidCaveat { caveat, reason } => write!(f, "Caveat {caveat} is not valid: {reason}"),
+            DelegationError::PermissionNotGranted { permission } =>
+                write!(f, "Permission {permission} cannot be granted since it was not included in the previous Delegation Credential"),
+            DelegationError::TooManyPermissions { requested, granted } =>
+                write!(f, "Cannot grant more permissions than those included in the previous Delegation Credential [{requested} > {granted}]"),
+            DelegationError::MalformedCredential { reason } => write!(f, "Delegation Credential is not well formatted: {reason}"),
+            DelegationError::DelegatorMismatch { delegatee, issuer } => write!(f, "Previous delegatee {delegatee} does not match current issuer {issuer}"),
+            DelegationError::RootMismatch { verifier, owner, issuer } =>
+                write!(f, "Hierarchy is empty but ({verifier} != {owner}) or ({verifier} != {issuer})"),
+            DelegationError::NotRedelegable => write!(f, "The delegation credential may not be delegated further"),
+            DelegationError::DepthExceeded { depth, max_depth } =>
+                write!(f, "Delegation at depth {depth} exceeds the maximum depth of the chain [{max_depth}]"),
+            DelegationError::CaveatLoosened { caveat } => write!(f, "Caveat {caveat} of the previous Delegation Credential is not kept or narrowed"),
+            DelegationError::CaveatNotSatisfied { caveat } => write!(f, "The request does not satisfy caveat {caveat}"),
+            DelegationError::Revoked { key } => write!(f, "Delegation {key} has been revoked"),
+            DelegationError::PresenterMismatch { presenter, subject } =>
+                write!(f, "Presentation is signed by {presenter} but the credential was issued to {subject}"),
+            DelegationError::RequestMismatch { nonce, audience } =>
+                write!(f, "Presentation does not answer the request of the verifier [nonce: {nonce:?}, aud: {audience:?}]"),
+            DelegationError::RequestExpired { now, expires_at } => write!(f, "Timestamp {now} is greater than request expiration time {expires_at}"),
+            DelegationError::NonceReused { nonce } => write!(f, "Nonce {nonce} has already been used"),
+            DelegationError::AllPermissionsRevoked => write!(f, "Every permission of the delegation credential has been revoked"),
+            DelegationError::Delegator { index, source } => write!(f, "Delegation {index} of the chain is not valid: {source}"),
+        }
+    }
+}
+
+impl Error for DelegationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DelegationError::Serialization(err) => Some(err),
+            DelegationError::Decoding(err) => Some(err),
+            DelegationError::Json { source, .. } => Some(source),
+            DelegationError::Jose { source, .. } => Some(source),
+            DelegationError::Utf8(err) => Some(err),
+            DelegationError::Io { source, .. } => Some(source),
+            DelegationError::WorkerPool(err) => Some(err),
+            DelegationError::Clock(err) => Some(err),
+            DelegationError::InvalidTimestamp { source, .. } => Some(source),
+            DelegationError::InvalidDateTime { source, .. } => Some(source),
+            DelegationError::Delegator { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<SerializationError> for DelegationError {
+    fn from(err: SerializationError) -> Self {
+        DelegationError::Serialization(err)
+    }
+}
+
+impl From<multibase::Error> for DelegationError {
+    fn from(err: multibase::Error) -> Self {
+        DelegationError::Decoding(err)
+    }
+}
+
+impl From<VBAccumulatorError> for DelegationError {
+    fn from(err: VBAccumulatorError) -> Self {
+        DelegationError::Accumulator(err)
+    }
+}
+
+impl From<SystemTimeError> for DelegationError {
+    fn from(err: SystemTimeError) -> Self {
+        DelegationError::Clock(err)
+    }
+}
@@ -2,4 +2,8 @@ pub mod utils;
 pub mod traits;
 pub mod entities;
 pub mod accumulators;
-pub mod credentials;
\ No newline at end of file
+pub mod credentials;
+pub mod error;
+pub mod clock;
+pub mod registry;
+pub mod replay;
\ No newline at end of file
@@ -0,0 +1,28 @@
+use ark_ec::pairing::Pairing;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use vb_accumulator::prelude::{PublicKey, SetupParams};
+use crate::delegation::accumulators::accumulator_utils::AccumulatorUtils;
+use crate::delegation::error::DelegationError;
+
+/// Accumulator public key and setup parameters published by an issuer in the registry.
+#[derive(Clone,Debug,CanonicalSerialize,CanonicalDeserialize)]
+pub struct AccumulatorParamsEntry<E: Pairing> {
+    pub public_key: PublicKey<E>,
+    pub setup_params: SetupParams<E>
+}
+
+impl <E: Pairing> AccumulatorParamsEntry<E> {
+    pub fn new(public_key: PublicKey<E>, setup_params: SetupParams<E>) -> Self {
+        AccumulatorParamsEntry { public_key, setup_params }
+    }
+
+    /// Encodes the entry so that it can be stored in a registry independently of the curve.
+    pub fn encode(&self) -> Result<String, DelegationError> {
+        AccumulatorUtils::<E>::serialize(self)
+    }
+
+    /// Decodes an entry previously encoded with [`AccumulatorParamsEntry::encode`].
+    pub fn decode(encoded_entry: &String) -> Result<Self, DelegationError> {
+        AccumulatorUtils::<E>::deserialize(encoded_entry)
+    }
+}
@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+use crate::delegation::accumulators::accumulator_update::AccumulatorUpdate;
+use crate::delegation::credentials::caveat::Caveat;
+use crate::delegation::error::DelegationError;
+
+/// Latest accumulator value published by an issuer for a single delegation credential. Every time
+/// the issuer removes elements from the credential's accumulator, the entry is overwritten so that
+/// verifiers can reject presentations carrying a stale (i.e. revoked) accumulator value. The public
+/// information of every removal is appended to `updates` so that holders can refresh their witnesses.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AccumulatorValueEntry {
+    pub accumulator_value: String,
+    pub updates: Vec<AccumulatorUpdate>,
+}
+
+impl AccumulatorValueEntry {
+    pub fn new(accumulator_value: String) -> Self {
+        AccumulatorValueEntry { accumulator_value, updates: vec![] }
+    }
+
+    /// Key identifying a delegation in the registry. It only relies on values that are
+    /// available both in a delegation credential and in every delegator of its hierarchy. The
+    /// maximum depth of the chain, when the delegation limits it, is part of the key so that it
+    /// cannot be removed or raised without losing the accumulator value of the delegation, and so
+    /// are its caveats. Ids may contain `#`, e.g. DID URLs with a fragment, and an audience may
+    /// contain any character, so every field is prefixed with its length and two different
+    /// delegations never give the same key. Delegations without a depth or caveats whose ids do
+    /// not contain `#` keep the key of the older credentials: it holds exactly two `#`, while
+    /// every prefixed key holds at least three, so the two encodings never overlap.
+    pub fn key(issuer: &str, delegatee_id: &str, iat: &str, max_depth: Option<usize>, caveats: &[Caveat]) -> String {
+        let legacy = max_depth.is_none() && caveats.is_empty() && ![issuer, delegatee_id, iat].iter().any(|field| field.contains('#'));
+        if legacy {
+            return format!("{issuer}#{delegatee_id}#{iat}");
+        }
+
+        let mut key = format!("{}:{issuer}#{}:{delegatee_id}#{}:{iat}", issuer.len(), delegatee_id.len(), iat.len());
+        if let Some(max_depth) = max_depth {
+            key.push_str(&format!("#depth={max_depth}"));
+        }
+        if !caveats.is_empty() {
+            key.push_str("#caveats=");
+            for caveat in caveats {
+                let caveat = caveat.to_string();
+                key.push_str(&format!("{}:{caveat}", caveat.len()));
+            }
+        }
+        key
+    }
+
+    /// Checks whether the given accumulator value has ever been the latest one.
+    pub fn has_published(&self, accumulator_value: &str) -> bool {
+        self.accumulator_value == accumulator_value || self.updates.iter()
+            .any(|update| update.previous_accumulator_value == accumulator_value || update.accumulator_value == accumulator_value)
+    }
+
+    /// Checks that an update follows the latest accumulator value and does not restore an earlier
+    /// one, which would undo the revocations published since.
+    ///
+    /// # Arguments
+    /// * `key` - Key of the delegation in the registry.
+    /// * `update` - Update to be published.
+    ///
+    /// # Returns
+    /// The result of the check or the error, if the update cannot be published.
+    pub fn check_update(&self, key: &str, update: &AccumulatorUpdate) -> Result<(), DelegationError> {
+        if update.previous_accumulator_value != self.accumulator_value {
+            return Err(DelegationError::InvalidAccumulatorUpdate { key: key.to_string(), reason: "it does not follow the latest accumulator value" });
+        }
+        if self.has_published(&update.accumulator_value) {
+            return Err(DelegationError::InvalidAccumulatorUpdate { key: key.to_string(), reason: "it restores an accumulator value already published" });
+        }
+        Ok(())
+    }
+
+    /// Records an update and sets the resulting accumulator value as the latest one.
+    pub fn publish_update(&mut self, update: AccumulatorUpdate) {
+        self.accumulator_value = update.accumulator_value.clone();
+        self.updates.push(update);
+    }
+
+    /// Retrieves the updates published after the given accumulator value.
+    ///
+    /// # Arguments
+    /// * `accumulator_value` - Accumulator value currently known by the holder.
+    ///
+    /// # Returns
+    /// A result wrapping the updates to be applied or the error, if it occurs.
+    pub fn updates_since(&self, accumulator_value: &String) -> Result<&[AccumulatorUpdate], DelegationError> {
+        if *accumulator_value == self.accumulator_value {
+            return Ok(&[]);
+        }
+
+        match self.updates.iter().position(|update| update.previous_accumulator_value == *accumulator_value) {
+            Some(position) => Ok(&self.updates[position..]),
+            None => Err(DelegationError::AccumulatorValueNotPublished { accumulator_value: accumulator_value.clone() }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn key_is_injective() -> Result<(), Box<dyn std::error::Error>> {
+        let key = |issuer: &str, delegatee_id: &str, max_depth: Option<usize>, caveats: &[&str]| -> Result<String, DelegationError> {
+            let caveats = caveats.iter().map(|caveat| Caveat::from_str(caveat)).collect::<Result<Vec<Caveat>, DelegationError>>()?;
+            Ok(AccumulatorValueEntry::key(issuer, delegatee_id, "1", max_depth, &caveats))
+        };
+
+        // Delegations without a maximum depth or caveats keep the key of the older credentials
+        assert_eq!(key("d0", "d1", None, &[])?, "d0#d1#1");
+
+        let keys = [
+            key("d0", "d1", None, &[])?,
+            key("d0", "d1", None, &["aud=v1;aud=v2"])?,
+            key("d0", "d1", None, &["aud=v1", "aud=v2"])?,
+            key("d0", "d1", None, &["aud=v1#depth=2"])?,
+            key("d0", "d1", Some(2), &["aud=v1"])?,
+            key("d0", "d1", None, &["aud=v1#caveats=6:aud=v2"])?,
+            key("d0", "d1", None, &["aud=v1", "aud=v2#caveats=6:aud=v3"])?,
+            key("d0", "d1", Some(2), &[])?,
+            key("d0", "d1", None, &["aud=2"])?,
+            // Ids with a fragment
+            key("a#b", "c", None, &[])?,
+            key("a", "b#c", None, &[])?,
+            key("a", "b", None, &[])?,
+            key("a#b", "c", Some(2), &[])?,
+            key("a", "b#c", Some(2), &[])?,
+            key("2:a", "b", None, &[])?,
+            key("d0#1:d1#1:1", "d1", None, &[])?,
+            key("d0", "d1#1", None, &[])?,
+            key("d0", "d1#1:1", None, &[])?,
+        ];
+        for (i, key) in keys.iter().enumerate() {
+            if let Some(j) = keys[..i].iter().position(|other| other == key) {
+                return Err(format!("Keys {j} and {i} collide [{key}]").into());
+            }
+        }
+        Ok(())
+    }
+}
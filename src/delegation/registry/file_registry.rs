@@ -0,0 +1,228 @@
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use crate::delegation::error::DelegationError;
+use crate::delegation::registry::registry_state::{RecordStore, RegistryRecord, RegistryState};
+use crate::delegation::registry::verifiable_data_registry::VerifiableDataRegistry;
+
+/// Registry persisted as an append-only file of JSON lines, one per record. Several processes can
+/// share the same file: each one appends its records and replays those appended by the others
+/// before every operation. Writers hold an exclusive advisory lock on the file while they replay,
+/// check and append a record, so that two processes cannot both publish the same material.
+pub struct FileRegistry {
+    path: PathBuf,
+    // State built from the records read so far and offset of the first unread byte
+    cache: Mutex<(RegistryState, u64)>,
+}
+
+impl FileRegistry {
+    /// Opens the registry stored at the given path, creating the file if it does not exist.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the JSON lines file.
+    ///
+    /// # Returns
+    /// A result wrapping the registry or the error, if it occurs.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, DelegationError> {
+        let registry = FileRegistry { path: path.as_ref().to_path_buf(), cache: Mutex::new((RegistryState::default(), 0)) };
+        let mut file = registry.open_file(false)?;
+        drop(registry.refresh(&mut file)?);
+        Ok(registry)
+    }
+
+    /// Opens the registry stored at the given path so that it can be shared among entities.
+    pub fn shared<P: AsRef<Path>>(path: P) -> Result<Arc<dyn VerifiableDataRegistry>, DelegationError> {
+        Ok(Arc::new(FileRegistry::open(path)?))
+    }
+
+    // Opens the file and locks it, exclusively to write to it. The lock is released when the file
+    // is closed.
+    fn open_file(&self, exclusive: bool) -> Result<File, DelegationError> {
+        let file = match OpenOptions::new().read(true).append(true).create(true).open(&self.path) {
+            Ok(file) => file,
+            Err(err) => { return Err(DelegationError::Io { context: "Failed to open registry file", source: err }) }
+        };
+        let locked = if exclusive { file.lock() } else { file.lock_shared() };
+        match locked {
+            Ok(()) => Ok(file),
+            Err(err) => Err(DelegationError::Io { context: "Failed to lock registry file", source: err }),
+        }
+    }
+
+    // Replays the records appended since the last refresh and returns the up to date cache
+    fn refresh(&self, file: &mut File) -> Result<MutexGuard<'_, (RegistryState, u64)>, DelegationError> {
+        let mut cache = match self.cache.lock() {
+            Ok(cache) => cache,
+            Err(_) => { return Err(DelegationError::LockPoisoned) }
+        };
+        let (state, offset) = &mut *cache;
+
+        if let Err(err) = file.seek(SeekFrom::Start(*offset)) {
+            return Err(DelegationError::Io { context: "Failed to seek registry file", source: err });
+        }
+        let mut content = String::new();
+        if let Err(err) = file.read_to_string(&mut content) {
+            return Err(DelegationError::Io { context: "Failed to read registry file", source: err });
+        }
+
+        // A line without its terminator was left by a writer that did not complete
+        let complete = match content.rfind('\n') {
+            Some(position) => &content[..=position],
+            None => "",
+        };
+        for line in complete.lines().filter(|line| !line.trim().is_empty()) {
+            match serde_json::from_str::<RegistryRecord>(line) {
+                Ok(record) => state.apply(record),
+                Err(err) => { return Err(DelegationError::Json { context: "Failed to parse registry record", source: err }); }
+            }
+        }
+        *offset += complete.len() as u64;
+
+        Ok(cache)
+    }
+}
+
+impl RecordStore for FileRegistry {
+    fn write(&self, record: RegistryRecord) -> Result<(), DelegationError> {
+        // The file stays locked, and so does the cache, until the record is appended, so that
+        // neither other processes nor threads sharing this registry can both pass the check
+        let mut file = self.open_file(true)?;
+        let cache = self.refresh(&mut file)?;
+        cache.0.check(&record)?;
+
+        // The bytes after the last complete record were left by a writer that crashed while
+        // holding the lock. They are dropped, otherwise the record would be appended to them.
+        if let Err(err) = file.set_len(cache.1) {
+            return Err(DelegationError::Io { context: "Failed to truncate registry file", source: err });
+        }
+
+        let mut line = match serde_json::to_string(&record) {
+            Ok(line) => line,
+            Err(err) => { return Err(DelegationError::Json { context: "Failed to serialize registry record", source: err }); }
+        };
+        line.push('\n');
+
+        if let Err(err) = file.write_all(line.as_bytes()) {
+            return Err(DelegationError::Io { context: "Failed to append to registry file", source: err });
+        }
+
+        Ok(())
+    }
+
+    fn read<T, F: FnOnce(&RegistryState) -> T>(&self, f: F) -> Result<T, DelegationError> {
+        let mut file = self.open_file(false)?;
+        let cache = self.refresh(&mut file)?;
+        Ok(f(&cache.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use josekit::jwk::Jwk;
+    use crate::delegation::registry::verifiable_data_registry::KeyPurpose;
+
+    #[test]
+    fn share_between_instances() -> Result<(), Box<dyn std::error::Error>> {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+        let path = std::env::temp_dir().join(format!("delegation-registry-{}-{nanos}.jsonl", std::process::id()));
+
+        // Two instances opened on the same file behave like two processes sharing the registry
+        let issuer_registry = FileRegistry::open(&path)?;
+        let verifier_registry = FileRegistry::open(&path)?;
+        let id = "https://vc.example/delegators/d0";
+
+        let mut jwk = Jwk::new("OKP");
+        jwk.set_key_id("k0");
+        issuer_registry.publish_key(KeyPurpose::Verification, id, jwk)?;
+        issuer_registry.publish_accumulator_params(id, String::from("params"))?;
+        issuer_registry.publish_accumulator_value("d0#d1#1", String::from("av0"))?;
+
+        let resolved_jwk = verifier_registry.resolve_key(KeyPurpose::Verification, id)?;
+        let resolved_params = verifier_registry.resolve_accumulator_params(id)?;
+        let resolved_value = verifier_registry.resolve_accumulator_value("d0#d1#1")?;
+
+        // Records written by one instance are checked by the other as well
+        let duplicate = verifier_registry.publish_key(KeyPurpose::Verification, id, Jwk::new("OKP"));
+
+        // A reopened registry replays the whole file
+        let reopened_registry = FileRegistry::open(&path)?;
+        let reopened_params = reopened_registry.resolve_accumulator_params(id)?;
+        std::fs::remove_file(&path)?;
+
+        if resolved_jwk.and_then(|jwk| jwk.key_id().map(String::from)) != Some(String::from("k0")) {
+            return Err("Key published by another instance not resolved".into());
+        }
+        if resolved_params.as_deref() != Some("params") || reopened_params.as_deref() != Some("params") {
+            return Err("Accumulator parameters not resolved".into());
+        }
+        if resolved_value.map(|entry| entry.accumulator_value) != Some(String::from("av0")) {
+            return Err("Accumulator value not resolved".into());
+        }
+        match duplicate {
+            Err(DelegationError::KeyAlreadyPublished { .. }) => Ok(()),
+            other => Err(format!("Expected KeyAlreadyPublished, got {other:?}").into()),
+        }
+    }
+
+    #[test]
+    fn drop_partial_record() -> Result<(), Box<dyn std::error::Error>> {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+        let path = std::env::temp_dir().join(format!("delegation-registry-{}-{nanos}-partial.jsonl", std::process::id()));
+
+        let registry = FileRegistry::open(&path)?;
+        registry.publish_accumulator_value("d0#d1#1", String::from("av0"))?;
+
+        // A writer crashed in the middle of a record
+        let mut file = OpenOptions::new().append(true).open(&path)?;
+        file.write_all(br#"{"op":"publish_accumulator_value","key":"d0#d2"#)?;
+        drop(file);
+
+        registry.publish_accumulator_value("d0#d3#1", String::from("av1"))?;
+        let reopened_registry = FileRegistry::open(&path)?;
+        let old_value = reopened_registry.resolve_accumulator_value("d0#d1#1")?;
+        let new_value = reopened_registry.resolve_accumulator_value("d0#d3#1")?;
+        std::fs::remove_file(&path)?;
+
+        if old_value.map(|entry| entry.accumulator_value) != Some(String::from("av0")) {
+            return Err("Record written before the partial one not resolved".into());
+        }
+        if new_value.map(|entry| entry.accumulator_value) != Some(String::from("av1")) {
+            return Err("Record written after the partial one not resolved".into());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn publish_concurrently_from_instances() -> Result<(), Box<dyn std::error::Error>> {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+        let path = std::env::temp_dir().join(format!("delegation-registry-{}-{nanos}-concurrent.jsonl", std::process::id()));
+
+        // Each instance stands for a process, only one of them may publish the delegation
+        let handles: Vec<_> = (0..8).map(|i| {
+            let path = path.clone();
+            std::thread::spawn(move || match FileRegistry::open(&path) {
+                Ok(registry) => registry.publish_accumulator_value("d0#d1#1", format!("av{i}")).is_ok(),
+                Err(_) => false,
+            })
+        }).collect();
+
+        let mut published = 0;
+        for handle in handles {
+            match handle.join() {
+                Ok(true) => published += 1,
+                Ok(false) => {}
+                Err(_) => { return Err("Publishing thread panicked".into()); }
+            }
+        }
+        let records = std::fs::read_to_string(&path)?.lines().count();
+        std::fs::remove_file(&path)?;
+
+        match (published, records) {
+            (1, 1) => Ok(()),
+            _ => Err(format!("Accumulator value published {published} times in {records} records").into()),
+        }
+    }
+}
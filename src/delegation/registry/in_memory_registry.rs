@@ -0,0 +1,134 @@
+use std::sync::{Arc, RwLock};
+use crate::delegation::error::DelegationError;
+use crate::delegation::registry::registry_state::{RecordStore, RegistryRecord, RegistryState};
+use crate::delegation::registry::verifiable_data_registry::VerifiableDataRegistry;
+
+/// Registry kept in memory. Issuers, holders and verifiers sharing it must live in the same process,
+/// but can run on different threads.
+#[derive(Default)]
+pub struct InMemoryRegistry {
+    state: RwLock<RegistryState>,
+}
+
+impl InMemoryRegistry {
+    pub fn new() -> Self {
+        InMemoryRegistry { state: RwLock::new(RegistryState::default()) }
+    }
+
+    /// Creates an empty registry ready to be shared among entities.
+    pub fn shared() -> Arc<dyn VerifiableDataRegistry> {
+        Arc::new(InMemoryRegistry::new())
+    }
+}
+
+impl RecordStore for InMemoryRegistry {
+    fn write(&self, record: RegistryRecord) -> Result<(), DelegationError> {
+        let mut state = match self.state.write() {
+            Ok(state) => state,
+            Err(_) => { return Err(DelegationError::LockPoisoned) }
+        };
+        state.check(&record)?;
+        state.apply(record);
+        Ok(())
+    }
+
+    fn read<T, F: FnOnce(&RegistryState) -> T>(&self, f: F) -> Result<T, DelegationError> {
+        match self.state.read() {
+            Ok(state) => Ok(f(&state)),
+            Err(_) => Err(DelegationError::LockPoisoned),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use josekit::jwk::Jwk;
+    use crate::delegation::accumulators::accumulator_update::AccumulatorUpdate;
+    use crate::delegation::registry::verifiable_data_registry::KeyPurpose;
+
+    #[test]
+    fn publish_and_rotate() -> Result<(), Box<dyn std::error::Error>> {
+        let registry = InMemoryRegistry::new();
+        let id = "https://vc.example/delegators/d0";
+
+        let mut jwk = Jwk::new("OKP");
+        jwk.set_key_id("k0");
+        registry.publish_key(KeyPurpose::Verification, id, jwk.clone())?;
+        if registry.publish_key(KeyPurpose::Verification, id, jwk).is_ok() {
+            return Err("Key published twice".into());
+        }
+        if registry.resolve_key(KeyPurpose::Encryption, id)?.is_some() {
+            return Err("Keys with different purposes are not kept apart".into());
+        }
+
+        let mut rotated_jwk = Jwk::new("OKP");
+        rotated_jwk.set_key_id("k1");
+        registry.rotate_key(KeyPurpose::Verification, id, rotated_jwk)?;
+        match registry.resolve_key(KeyPurpose::Verification, id)? {
+            Some(jwk) if jwk.key_id() == Some("k1") => {}
+            other => { return Err(format!("Unexpected key after rotation {other:?}").into()); }
+        }
+        match registry.rotate_key(KeyPurpose::Encryption, id, Jwk::new("OKP")) {
+            Err(DelegationError::KeyNotPublished { .. }) => {}
+            other => { return Err(format!("Expected KeyNotPublished, got {other:?}").into()); }
+        }
+
+        let key = "d0#d1#1";
+        let update = AccumulatorUpdate::new(String::from("av0"), String::from("av1"), vec![], String::new());
+        if registry.publish_accumulator_update(key, update.clone()).is_ok() {
+            return Err("Update published for an unknown delegation".into());
+        }
+        registry.publish_accumulator_value(key, String::from("av0"))?;
+        registry.publish_accumulator_update(key, update)?;
+
+        // Publishing the value again would undo the update
+        match registry.publish_accumulator_value(key, String::from("av0")) {
+            Err(DelegationError::DelegationAlreadyPublished { .. }) => {}
+            other => { return Err(format!("Expected DelegationAlreadyPublished, got {other:?}").into()); }
+        }
+
+        // Neither can an update restoring the value from before the removal, nor one that does not
+        // follow the latest value
+        let restore = AccumulatorUpdate::new(String::from("av1"), String::from("av0"), vec![], String::new());
+        match registry.publish_accumulator_update(key, restore) {
+            Err(DelegationError::InvalidAccumulatorUpdate { .. }) => {}
+            other => { return Err(format!("Expected InvalidAccumulatorUpdate, got {other:?}").into()); }
+        }
+        let stale = AccumulatorUpdate::new(String::from("av0"), String::from("av2"), vec![], String::new());
+        match registry.publish_accumulator_update(key, stale) {
+            Err(DelegationError::InvalidAccumulatorUpdate { .. }) => {}
+            other => { return Err(format!("Expected InvalidAccumulatorUpdate, got {other:?}").into()); }
+        }
+        match registry.resolve_accumulator_value(key)? {
+            Some(entry) if entry.accumulator_value == "av1" && entry.updates.len() == 1 => Ok(()),
+            other => Err(format!("Unexpected accumulator value entry {other:?}").into()),
+        }
+    }
+
+    #[test]
+    fn publish_concurrently() -> Result<(), Box<dyn std::error::Error>> {
+        let registry = InMemoryRegistry::shared();
+        let id = "https://vc.example/delegators/d0";
+
+        // Only one of the threads publishing the same key succeeds
+        let handles: Vec<_> = (0..8).map(|_| {
+            let registry = registry.clone();
+            std::thread::spawn(move || registry.publish_key(KeyPurpose::Verification, id, Jwk::new("OKP")).is_ok())
+        }).collect();
+
+        let mut published = 0;
+        for handle in handles {
+            match handle.join() {
+                Ok(true) => published += 1,
+                Ok(false) => {}
+                Err(_) => { return Err("Publishing thread panicked".into()); }
+            }
+        }
+
+        match published {
+            1 => Ok(()),
+            _ => Err(format!("Key published {published} times").into()),
+        }
+    }
+}
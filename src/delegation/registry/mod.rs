@@ -0,0 +1,6 @@
+pub mod accumulator_params_entry;
+pub mod accumulator_value_entry;
+pub mod file_registry;
+pub mod in_memory_registry;
+pub mod registry_state;
+pub mod verifiable_data_registry;
\ No newline at end of file
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use josekit::jwk::Jwk;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use crate::delegation::accumulators::accumulator_update::AccumulatorUpdate;
+use crate::delegation::error::DelegationError;
+use crate::delegation::registry::accumulator_value_entry::AccumulatorValueEntry;
+use crate::delegation::registry::verifiable_data_registry::{KeyPurpose, VerifiableDataRegistry};
+
+/// A single write operation on a registry. File-backed registries persist them as JSON lines.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum RegistryRecord {
+    PublishKey { purpose: KeyPurpose, id: String, jwk: Map<String, Value> },
+    RotateKey { purpose: KeyPurpose, id: String, jwk: Map<String, Value> },
+    PublishAccumulatorParams { id: String, params: String },
+    PublishAccumulatorValue { key: String, accumulator_value: String },
+    PublishAccumulatorUpdate { key: String, update: AccumulatorUpdate },
+}
+
+/// Content of a registry, built by applying records in order.
+#[derive(Default)]
+pub struct RegistryState {
+    keys: HashMap<(KeyPurpose, String), Map<String, Value>>,
+    accumulator_params: HashMap<String, String>,
+    accumulator_values: HashMap<String, AccumulatorValueEntry>,
+}
+
+impl RegistryState {
+    /// Checks that a record can be applied to the current state.
+    pub fn check(&self, record: &RegistryRecord) -> Result<(), DelegationError> {
+        match record {
+            RegistryRecord::PublishKey { purpose, id, .. } => {
+                if self.keys.contains_key(&(*purpose, id.clone())) {
+                    return Err(DelegationError::KeyAlreadyPublished { id: id.clone() });
+                }
+            }
+            RegistryRecord::RotateKey { purpose, id, .. } => {
+                if !self.keys.contains_key(&(*purpose, id.clone())) {
+                    return Err(DelegationError::KeyNotPublished { id: id.clone() });
+                }
+            }
+            RegistryRecord::PublishAccumulatorParams { id, .. } => {
+                if self.accumulator_params.contains_key(id) {
+                    return Err(DelegationError::KeyAlreadyPublished { id: id.clone() });
+                }
+            }
+            RegistryRecord::PublishAccumulatorValue { key, .. } => {
+                if self.accumulator_values.contains_key(key) {
+                    return Err(DelegationError::DelegationAlreadyPublished { key: key.clone() });
+                }
+            }
+            RegistryRecord::PublishAccumulatorUpdate { key, update } => {
+                match self.accumulator_values.get(key) {
+                    Some(entry) => entry.check_update(key, update)?,
+                    None => { return Err(DelegationError::DelegationNotPublished { key: key.clone() }); }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies a record without checking it, so that a log can always be replayed. Material is
+    /// only published once: publishing it again for an existing key is ignored, so that the first
+    /// write wins and the updates of an accumulator value are never erased. Updates that do not
+    /// pass [`AccumulatorValueEntry::check_update`] are ignored as well, so that a revocation is
+    /// never undone.
+    pub fn apply(&mut self, record: RegistryRecord) {
+        match record {
+            RegistryRecord::PublishKey { purpose, id, jwk } => {
+                self.keys.entry((purpose, id)).or_insert(jwk);
+            }
+            RegistryRecord::RotateKey { purpose, id, jwk } => {
+                self.keys.insert((purpose, id), jwk);
+            }
+            RegistryRecord::PublishAccumulatorParams { id, params } => {
+                self.accumulator_params.entry(id).or_insert(params);
+            }
+            RegistryRecord::PublishAccumulatorValue { key, accumulator_value } => {
+                self.accumulator_values.entry(key).or_insert_with(|| AccumulatorValueEntry::new(accumulator_value));
+            }
+            RegistryRecord::PublishAccumulatorUpdate { key, update } => {
+                if let Some(entry) = self.accumulator_values.get_mut(&key)
+                    && entry.check_update(&key, &update).is_ok() {
+                    entry.publish_update(update);
+                }
+            }
+        }
+    }
+
+    pub fn key(&self, purpose: KeyPurpose, id: &str) -> Result<Option<Jwk>, DelegationError> {
+        match self.keys.get(&(purpose, id.to_string())) {
+            None => Ok(None),
+            Some(map) => match Jwk::from_map(map.clone()) {
+                Ok(jwk) => Ok(Some(jwk)),
+                Err(err) => Err(DelegationError::Jose { context: "Failed to parse registry key", source: err }),
+            },
+        }
+    }
+
+    pub fn accumulator_params(&self, id: &str) -> Option<String> {
+        self.accumulator_params.get(id).cloned()
+    }
+
+    pub fn accumulator_value(&self, key: &str) -> Option<AccumulatorValueEntry> {
+        self.accumulator_values.get(key).cloned()
+    }
+}
+
+/// Storage of registry records. Every store is a [`VerifiableDataRegistry`].
+pub trait RecordStore {
+    /// Checks a record against the current state and stores it.
+    fn write(&self, record: RegistryRecord) -> Result<(), DelegationError>;
+
+    /// Reads the current state of the registry.
+    fn read<T, F: FnOnce(&RegistryState) -> T>(&self, f: F) -> Result<T, DelegationError>;
+}
+
+impl<S: RecordStore + Send + Sync> VerifiableDataRegistry for S {
+    fn publish_key(&self, purpose: KeyPurpose, id: &str, jwk: Jwk) -> Result<(), DelegationError> {
+        self.write(RegistryRecord::PublishKey { purpose, id: id.to_string(), jwk: jwk.into() })
+    }
+
+    fn rotate_key(&self, purpose: KeyPurpose, id: &str, jwk: Jwk) -> Result<(), DelegationError> {
+        self.write(RegistryRecord::RotateKey { purpose, id: id.to_string(), jwk: jwk.into() })
+    }
+
+    fn resolve_key(&self, purpose: KeyPurpose, id: &str) -> Result<Option<Jwk>, DelegationError> {
+        self.read(|state| state.key(purpose, id))?
+    }
+
+    fn publish_accumulator_params(&self, id: &str, params: String) -> Result<(), DelegationError> {
+        self.write(RegistryRecord::PublishAccumulatorParams { id: id.to_string(), params })
+    }
+
+    fn resolve_accumulator_params(&self, id: &str) -> Result<Option<String>, DelegationError> {
+        self.read(|state| state.accumulator_params(id))
+    }
+
+    fn publish_accumulator_value(&self, key: &str, accumulator_value: String) -> Result<(), DelegationError> {
+        self.write(RegistryRecord::PublishAccumulatorValue { key: key.to_string(), accumulator_value })
+    }
+
+    fn publish_accumulator_update(&self, key: &str, update: AccumulatorUpdate) -> Result<(), DelegationError> {
+        self.write(RegistryRecord::PublishAccumulatorUpdate { key: key.to_string(), update })
+    }
+
+    fn resolve_accumulator_value(&self, key: &str) -> Result<Option<AccumulatorValueEntry>, DelegationError> {
+        self.read(|state| state.accumulator_value(key))
+    }
+}
@@ -0,0 +1,62 @@
+use josekit::jwk::Jwk;
+use serde::{Deserialize, Serialize};
+use crate::delegation::accumulators::accumulator_update::AccumulatorUpdate;
+use crate::delegation::error::DelegationError;
+use crate::delegation::registry::accumulator_value_entry::AccumulatorValueEntry;
+
+/// Purpose of a public key published in the registry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyPurpose {
+    /// Key used to verify the signatures of credentials and presentations.
+    Verification,
+    /// Key used to encrypt data for its owner.
+    Encryption,
+}
+
+/// Registry where issuers publish the material that holders and verifiers need: public keys,
+/// accumulator parameters and the latest accumulator value of every issued delegation credential.
+/// Registries are shared among entities running on different threads.
+pub trait VerifiableDataRegistry: Send + Sync {
+    /// Publishes a public key for the first time.
+    ///
+    /// # Arguments
+    /// * `purpose` - Purpose of the key.
+    /// * `id` - Identifier of the owner of the key.
+    /// * `jwk` - Public key.
+    ///
+    /// # Returns
+    /// The result of the operation or the error, if the key was already published.
+    fn publish_key(&self, purpose: KeyPurpose, id: &str, jwk: Jwk) -> Result<(), DelegationError>;
+
+    /// Replaces a previously published public key.
+    ///
+    /// # Arguments
+    /// * `purpose` - Purpose of the key.
+    /// * `id` - Identifier of the owner of the key.
+    /// * `jwk` - New public key.
+    ///
+    /// # Returns
+    /// The result of the operation or the error, if no key was published before.
+    fn rotate_key(&self, purpose: KeyPurpose, id: &str, jwk: Jwk) -> Result<(), DelegationError>;
+
+    /// Resolves the latest public key published by an entity.
+    fn resolve_key(&self, purpose: KeyPurpose, id: &str) -> Result<Option<Jwk>, DelegationError>;
+
+    /// Publishes the encoded accumulator parameters of an issuer.
+    fn publish_accumulator_params(&self, id: &str, params: String) -> Result<(), DelegationError>;
+
+    /// Resolves the encoded accumulator parameters of an issuer.
+    fn resolve_accumulator_params(&self, id: &str) -> Result<Option<String>, DelegationError>;
+
+    /// Publishes the accumulator value of a newly issued delegation credential.
+    fn publish_accumulator_value(&self, key: &str, accumulator_value: String) -> Result<(), DelegationError>;
+
+    /// Publishes an update of the accumulator of a delegation credential after some elements
+    /// have been removed from it.
+    fn publish_accumulator_update(&self, key: &str, update: AccumulatorUpdate) -> Result<(), DelegationError>;
+
+    /// Resolves the latest accumulator value of a delegation credential, together with the
+    /// updates published so far.
+    fn resolve_accumulator_value(&self, key: &str) -> Result<Option<AccumulatorValueEntry>, DelegationError>;
+}
@@ -0,0 +1,24 @@
+use crate::delegation::error::DelegationError;
+use crate::delegation::replay::replay_cache::ReplayCache;
+use std::sync::Arc;
+
+/// Replay cache accepting every nonce, for benchmarks verifying the same presentation several
+/// times. It offers no protection against replays and must never back a real verifier.
+#[derive(Default)]
+pub struct DisabledReplayCache;
+
+impl DisabledReplayCache {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn shared() -> Arc<dyn ReplayCache> {
+        Arc::new(Self::new())
+    }
+}
+
+impl ReplayCache for DisabledReplayCache {
+    fn insert(&self, _nonce: &str, _expires_at: u128, _now: u128) -> Result<bool, DelegationError> {
+        Ok(true)
+    }
+}
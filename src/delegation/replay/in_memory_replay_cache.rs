@@ -0,0 +1,62 @@
+use crate::delegation::error::DelegationError;
+use crate::delegation::replay::replay_cache::ReplayCache;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Replay cache kept in memory, shared by the verifiers of a single process.
+#[derive(Default)]
+pub struct InMemoryReplayCache {
+    // Expiration of every nonce still to be remembered
+    used: Mutex<HashMap<String, u128>>,
+}
+
+impl InMemoryReplayCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn shared() -> Arc<dyn ReplayCache> {
+        Arc::new(Self::new())
+    }
+}
+
+impl ReplayCache for InMemoryReplayCache {
+    fn insert(&self, nonce: &str, expires_at: u128, now: u128) -> Result<bool, DelegationError> {
+        let mut used = match self.used.lock() {
+            Ok(used) => used,
+            Err(_) => { return Err(DelegationError::LockPoisoned) }
+        };
+
+        // Requests that have expired are rejected anyway, so their nonces can be dropped
+        used.retain(|_, used_expires_at| *used_expires_at >= now);
+
+        if used.contains_key(nonce) {
+            return Ok(false);
+        }
+        used.insert(nonce.to_string(), expires_at);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reject_reused_nonces() -> Result<(), Box<dyn std::error::Error>> {
+        let cache = InMemoryReplayCache::new();
+
+        if !cache.insert("n0", 10, 0)? || !cache.insert("n1", 20, 0)? {
+            return Err(String::from("Fresh nonce rejected").into());
+        }
+        if cache.insert("n0", 10, 5)? {
+            return Err(String::from("Reused nonce accepted").into());
+        }
+
+        // Expired nonces are forgotten
+        if !cache.insert("n0", 30, 15)? || cache.insert("n1", 20, 15)? {
+            return Err(String::from("Expired nonces not forgotten").into());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,3 @@
+pub mod disabled_replay_cache;
+pub mod in_memory_replay_cache;
+pub mod replay_cache;
@@ -0,0 +1,17 @@
+use crate::delegation::error::DelegationError;
+
+/// Store of the nonces of the presentation requests already answered. Verifiers share it to reject
+/// presentations replayed within the validity of their request.
+pub trait ReplayCache: Send + Sync {
+
+    /// Records the use of a nonce.
+    ///
+    /// # Arguments
+    /// * `nonce` - Nonce of the presentation request.
+    /// * `expires_at` - Expiration of the request in nanoseconds, after which the nonce can be forgotten.
+    /// * `now` - Current time in nanoseconds.
+    ///
+    /// # Returns
+    /// A result wrapping whether the nonce was never used before, or the error if it occurs.
+    fn insert(&self, nonce: &str, expires_at: u128, now: u128) -> Result<bool, DelegationError>;
+}
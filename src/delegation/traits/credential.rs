@@ -1,19 +1,23 @@
 use std::fmt::Display;
 use serde::Serialize;
 use serde_json::{Map, Value};
+use crate::delegation::error::DelegationError;
 
 pub trait Credential: Clone + Display + Serialize  {
+    /// Claim that presentations of the credential disclose selectively.
+    type Claim: Clone + PartialEq;
+
     fn credential_type(&self) -> &'static str;
 
-    fn from_map(map: Map<String, Value>) -> Result<Self, String> where Self: Sized;
+    fn from_map(map: Map<String, Value>) -> Result<Self, DelegationError> where Self: Sized;
 
-    fn from_string(str: String) -> Result<Self, String> where Self: Sized;
+    fn from_string(str: String) -> Result<Self, DelegationError> where Self: Sized;
 
-    fn to_map(&self) -> Result<Map<String, Value>, String>;
+    fn to_map(&self) -> Result<Map<String, Value>, DelegationError>;
 
-    fn to_string(&self) -> Result<String, String>;
+    fn to_string(&self) -> Result<String, DelegationError>;
 
-    fn retain_only(&mut self, allowed: Vec<String>) -> Result<Vec<usize>, String>;
+    fn retain_only(&mut self, allowed: Vec<Self::Claim>) -> Result<Vec<usize>, DelegationError>;
 
     fn is_empty(&self) -> bool;
 }
\ No newline at end of file
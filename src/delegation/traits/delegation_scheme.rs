@@ -0,0 +1,134 @@
+use crate::delegation::credentials::permission::Permission;
+use crate::delegation::credentials::presentation_request::PresentationRequest;
+use crate::delegation::credentials::verifiable_credential::VerifiableCredential;
+use crate::delegation::error::DelegationError;
+use crate::delegation::traits::credential::Credential;
+use std::time::Duration;
+
+/// Parameters of a delegation credential to be issued, common to every scheme.
+#[derive(Clone, Debug)]
+pub struct CredentialParameters {
+    pub context: Vec<String>,
+    pub credential_id: String,
+    pub valid_from: String,
+    pub delegatee_id: String,
+    pub validity_period: Duration,
+    /// Permissions granted to the delegatee.
+    pub permissions: Vec<Permission>,
+}
+
+/// Presentation signed by its presenter, as sent to the verifier.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignedPresentation {
+    pub presenter_id: String,
+    pub jwt: String,
+}
+
+/// Delegation scheme seen through the operations every scheme supports, so that applications and
+/// benchmarks can be written once for all of them.
+pub trait DelegationScheme {
+    type Credential: Credential;
+    type Issuer;
+    type Verifier;
+
+    /// Name of the scheme, e.g. the column of the scheme in the benchmarks.
+    fn name(&self) -> &str;
+
+    /// Creates an entity issuing delegation credentials and presenting the ones it receives.
+    fn issuer(&self, id: String) -> Result<Self::Issuer, DelegationError>;
+
+    /// Creates the verifier of the presentations of the resource owned by `owner`.
+    fn verifier(&self, owner: &Self::Issuer) -> Result<Self::Verifier, DelegationError>;
+
+    /// Issues a delegation credential.
+    ///
+    /// # Arguments
+    /// * `issuer` - Issuer of the credential.
+    /// * `parameters` - Content of the credential.
+    /// * `parent` - Credential delegated to the issuer, none if the issuer is the resource owner.
+    ///
+    /// # Returns
+    /// A result wrapping the credential or the error, if it occurs.
+    fn issue_verifiable_credential(&self, issuer: &Self::Issuer, parameters: &CredentialParameters,
+                                   parent: Option<VerifiableCredential<Self::Credential>>)
+                                   -> Result<VerifiableCredential<Self::Credential>, DelegationError>;
+
+    /// Issues a presentation of a credential answering a request of the verifier. Depending on the
+    /// scheme, the presentation is signed by the issuer or by the holder of the credential.
+    ///
+    /// # Arguments
+    /// * `issuer` - Issuer of the credential.
+    /// * `holder` - Delegatee of the credential.
+    /// * `vc` - Credential to be presented.
+    /// * `disclosed_permissions` - Permissions to be disclosed to the verifier.
+    /// * `request` - Presentation request received from the verifier.
+    ///
+    /// # Returns
+    /// A result wrapping the signed presentation or the error, if it occurs.
+    fn issue_verifiable_presentation(&self, issuer: &Self::Issuer, holder: &Self::Issuer,
+                                     vc: VerifiableCredential<Self::Credential>, disclosed_permissions: Vec<Permission>,
+                                     request: &PresentationRequest) -> Result<SignedPresentation, DelegationError>;
+
+    /// Verifies a presentation answering a request of the verifier.
+    fn verify_verifiable_presentation(&self, verifier: &Self::Verifier, presentation: &SignedPresentation,
+                                      request: &PresentationRequest) -> Result<(), DelegationError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Bn254;
+    use crate::delegation::accumulators::verification_mode::VerificationMode;
+    use crate::delegation::entities::ours::our_scheme::OurScheme;
+    use crate::delegation::entities::pjv::pjv_scheme::PJVScheme;
+    use crate::delegation::registry::in_memory_registry::InMemoryRegistry;
+
+    // Delegates a permission along a chain of two credentials and presents it to the owner
+    fn delegate<S: DelegationScheme>(scheme: &S, owner_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let ids: Vec<String> = (0..3).map(|i| format!("https://vc.example/delegators/d{i}")).collect();
+        if ids[0] != owner_id {
+            return Err(format!("Unexpected owner {owner_id}").into());
+        }
+        let mut entities: Vec<S::Issuer> = vec![];
+        for id in &ids {
+            entities.push(scheme.issuer(id.clone())?);
+        }
+
+        let resource = String::from("https://vc.example/resources/r1");
+        let permissions: Vec<Permission> = vec![Permission::new(resource.clone(), String::from("p0"))?, Permission::new(resource, String::from("p1"))?];
+        let mut parameters = CredentialParameters {
+            context: vec![String::from("https://www.w3.org/ns/credentials/v2")],
+            credential_id: String::from("http://delegation.example/credentials/1337"),
+            valid_from: String::from("2026-01-01T00:00:00Z"),
+            delegatee_id: ids[1].clone(),
+            validity_period: Duration::new(3600, 0),
+            permissions: permissions.clone(),
+        };
+        let root_vc = scheme.issue_verifiable_credential(&entities[0], &parameters, None)?;
+
+        parameters.credential_id = String::from("http://delegation.example/credentials/1338");
+        parameters.delegatee_id = ids[2].clone();
+        parameters.permissions = permissions[..1].to_vec();
+        let vc = scheme.issue_verifiable_credential(&entities[1], &parameters, Some(root_vc))?;
+
+        let verifier = scheme.verifier(&entities[0])?;
+        let request = PresentationRequest::new(owner_id.to_string(), Duration::new(60, 0))?;
+        let presentation = scheme.issue_verifiable_presentation(&entities[1], &entities[2], vc, permissions[..1].to_vec(), &request)?;
+        scheme.verify_verifiable_presentation(&verifier, &presentation, &request)?;
+
+        match scheme.verify_verifiable_presentation(&verifier, &presentation, &request) {
+            Err(DelegationError::NonceReused { .. }) => Ok(()),
+            other => Err(format!("Expected reused nonce with {}, got {other:?}", scheme.name()).into()),
+        }
+    }
+
+    #[test]
+    fn delegate_with_every_scheme() -> Result<(), Box<dyn std::error::Error>> {
+        let owner = String::from("https://vc.example/delegators/d0");
+
+        for mode in [VerificationMode::Sequential, VerificationMode::Parallel, VerificationMode::Batched] {
+            delegate(&OurScheme::<Bn254>::new(InMemoryRegistry::shared(), mode), &owner)?;
+        }
+        delegate(&PJVScheme::new(InMemoryRegistry::shared(), owner.clone(), String::from("https://vc.example/resources/r1")), &owner)
+    }
+}
@@ -1 +1,2 @@
-pub mod credential;
\ No newline at end of file
+pub mod credential;
+pub mod delegation_scheme;
\ No newline at end of file
@@ -1,15 +1,18 @@
 use serde_json::Value;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+use crate::delegation::error::DelegationError;
 
-pub fn serialize_array(array: &Vec<String>) -> String {
+pub fn serialize_array(array: &[String]) -> String {
     let mut result = String::new();
     let len = array.len();
 
     result.push_str("[ ");
     for (i, element) in array.iter().enumerate() {
 
-        result.push_str("\"");
+        result.push('"');
         result.push_str(element);
-        result.push_str("\"");
+        result.push('"');
         if i < len - 1 {
             result.push_str(", ");
         }
@@ -19,37 +22,73 @@ pub fn serialize_array(array: &Vec<String>) -> String {
     result
 }
 
-pub fn to_value_array(array: &Vec<String>) -> Vec<Value> {
+pub fn to_value_array(array: &[String]) -> Vec<Value> {
     array.iter().map(
         |pw| Value::String(pw.clone())
     ).collect::<Vec<Value>>()
 }
 
-pub fn from_value_array(values: &Vec<Value>, variable: String) -> Result<Vec<String>, String> {
+pub fn from_value_array(values: &[Value], variable: String) -> Result<Vec<String>, DelegationError> {
     let mut result: Vec<String> = Vec::new();
 
     for value in values {
         match value {
             Value::String(string) => { result.push(string.clone()); }
-            _ => return Err(format!("{variable} [{value}] is not a String."))
+            _ => return Err(DelegationError::MalformedCredential { reason: format!("{variable} [{value}] is not a String.") })
         };
     }
 
     Ok(result)
 }
 
+/// Parses an RFC 3339 date time, like the `validFrom` of a credential, into nanoseconds since the
+/// Unix epoch. Dates before the epoch are clamped to it.
+pub fn parse_date_time(date: &str) -> Result<u128, DelegationError> {
+    let date_time = match OffsetDateTime::parse(date, &Rfc3339) {
+        Ok(date_time) => date_time,
+        Err(err) => { return Err(DelegationError::InvalidDateTime { date: date.to_string(), source: err }) }
+    };
+
+    Ok(u128::try_from(date_time.unix_timestamp_nanos()).unwrap_or(0))
+}
+
+/// Formats nanoseconds since the Unix epoch as an RFC 3339 date time in UTC.
+pub fn format_date_time(timestamp: u128) -> Result<String, DelegationError> {
+    let date_time = i128::try_from(timestamp).ok()
+        .and_then(|nanos| OffsetDateTime::from_unix_timestamp_nanos(nanos).ok());
+
+    match date_time.map(|date_time| date_time.format(&Rfc3339)) {
+        Some(Ok(date)) => Ok(date),
+        _ => Err(DelegationError::TimestampOutOfRange { timestamp }),
+    }
+}
 
 
 #[cfg(test)]
 mod tests {
-    use crate::delegation::utils::utils::serialize_array;
+    use crate::delegation::error::DelegationError;
+    use crate::delegation::utils::utils::{format_date_time, parse_date_time, serialize_array};
 
     #[test]
     fn test_serialize_array() {
 
-        let vec = vec![ "w_delegatee_id_c", "w_iat_c", "w_exp_c" ];
+        let vec = [ "w_delegatee_id_c", "w_iat_c", "w_exp_c" ];
         let vec_string = vec.iter().map(|x| x.to_string()).collect::<Vec<String>>();
         println!("{}", serialize_array(&vec_string))
 
     }
+
+    #[test]
+    fn test_date_time() -> Result<(), Box<dyn std::error::Error>> {
+
+        let timestamp = parse_date_time("2026-01-01T01:00:00.5+01:00")?;
+        if timestamp != 1_767_225_600_500_000_000 || format_date_time(timestamp)? != "2026-01-01T00:00:00.5Z" {
+            return Err(format!("Unexpected conversion of the date time [{timestamp}]").into());
+        }
+
+        match parse_date_time("2026-01-01") {
+            Err(DelegationError::InvalidDateTime { .. }) => Ok(()),
+            other => Err(format!("Expected an invalid date time, got {other:?}").into()),
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,642 @@
+use crate::benchmark::{Benchmark, Samples};
+use crate::csv_writer::{CSVWriter, CSV_DIR};
+use crate::delegation::credentials::permission::Permission;
+use crate::delegation::credentials::presentation_request::PresentationRequest;
+use crate::delegation::traits::delegation_scheme::{CredentialParameters, DelegationScheme};
+use crate::metadata::RunMetadata;
+use serde::Serialize;
+use std::cell::Cell;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Time the holders have to answer the presentation requests of an experiment.
+const REQUEST_VALIDITY: Duration = Duration::from_secs(3600);
+/// Resource whose operations are delegated along the chains of an experiment.
+const RESOURCE_URI: &str = "https://vc.example/resources/r1";
+/// Columns of the parameters of the rows, leading every file.
+pub const PARAMETER_COLUMNS: [&str; 3] = ["delegators", "permissions", "disclosed"];
+/// Columns of the files of the distributions of the durations, in microseconds.
+const STATISTICS_COLUMNS: [&str; 10] = ["scheme", "mean", "median", "p95", "p99", "min", "max", "std_dev", "ci95_low", "ci95_high"];
+/// Columns of the files of the samples of the durations, in microseconds.
+const SAMPLES_COLUMNS: [&str; 3] = ["scheme", "iteration", "duration"];
+
+// Keeps the precision of the samples, that can be shorter than a microsecond
+fn micros(duration: &Duration) -> f64 {
+    duration.as_nanos() as f64 / 1000.0
+}
+
+/// Level of a delegation chain: the permissions granted by its credential and the ones disclosed
+/// when the credential is presented. Permissions are always the first ones of the resource, so
+/// that every level grants a subset of the permissions of its parent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct Level {
+    pub granted: usize,
+    pub disclosed: usize,
+}
+
+/// Point of a sweep: the delegation chain to be built and the levels whose measurements are
+/// recorded, one row for each of them.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct SweepPoint {
+    pub levels: Vec<Level>,
+    pub recorded: Vec<usize>,
+}
+
+/// Parameter sweep of an experiment, as the list of chains to be measured.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Sweep {
+    points: Vec<SweepPoint>,
+}
+
+impl Sweep {
+
+    /// Creates a sweep from its points.
+    ///
+    /// # Arguments
+    /// * `points` - Chains to be measured, in the order of the rows.
+    ///
+    /// # Returns
+    /// The sweep or a string containing an error, if a chain is empty, a level grants more
+    /// permissions than its parent, discloses none or more than it grants, or a recorded level
+    /// does not exist.
+    pub fn new(points: Vec<SweepPoint>) -> Result<Self, String> {
+        for point in points.iter() {
+            if point.levels.is_empty() {
+                return Err(String::from("Chains must have at least one level"))
+            }
+
+            let mut parent_granted = usize::MAX;
+            for (i, level) in point.levels.iter().enumerate() {
+                if level.granted > parent_granted {
+                    return Err(format!("Level {i} cannot grant more permissions than its parent [{} > {parent_granted}]", level.granted))
+                } else if level.disclosed > level.granted {
+                    return Err(format!("Cannot disclose more permissions than those included in the credential [{} > {}]", level.disclosed, level.granted))
+                } else if level.disclosed < 1 {
+                    return Err(format!("Permissions to disclose must be at least 1 [{}]", level.disclosed))
+                }
+                parent_granted = level.granted;
+            }
+
+            if let Some(recorded) = point.recorded.iter().find(|recorded| **recorded >= point.levels.len()) {
+                return Err(format!("Recorded level {recorded} is not part of a chain of {} levels", point.levels.len()))
+            }
+        }
+
+        Ok(Sweep { points })
+    }
+
+    /// Sweeps over the length of a chain whose every level grants the same permissions, recording
+    /// the credentials of the given delegators.
+    ///
+    /// # Arguments
+    /// * `delegators` - Delegators whose credential is recorded, from 1 for the root credential.
+    ///   The chain is as long as the largest of them.
+    /// * `permissions` - Permissions granted at each level.
+    /// * `disclosed` - Permissions disclosed at each level.
+    pub fn delegators(delegators: &[usize], permissions: usize, disclosed: usize) -> Result<Self, String> {
+        let length = delegators.iter().max().copied().unwrap_or(0);
+        let mut recorded: Vec<usize> = vec![];
+        for delegator in delegators {
+            match delegator.checked_sub(1) {
+                Some(level) => recorded.push(level),
+                None => { return Err(String::from("Delegators are counted from 1")) }
+            }
+        }
+        Self::new(vec![SweepPoint { levels: vec![Level { granted: permissions, disclosed }; length], recorded }])
+    }
+
+    /// Sweeps over the permissions granted and disclosed along a chain of fixed length, recording
+    /// the last level of each chain.
+    ///
+    /// # Arguments
+    /// * `delegators` - Length of the chains.
+    /// * `permissions` - Permissions granted by each chain.
+    pub fn permissions(delegators: usize, permissions: &[usize]) -> Result<Self, String> {
+        let points = permissions.iter()
+            .map(|permissions| SweepPoint {
+                levels: vec![Level { granted: *permissions, disclosed: *permissions }; delegators],
+                recorded: delegators.checked_sub(1).into_iter().collect(),
+            })
+            .collect();
+        Self::new(points)
+    }
+
+    /// Sweeps over a chain where each delegator retains some of its permissions, disclosing all the
+    /// permissions granted and recording every level.
+    ///
+    /// # Arguments
+    /// * `delegators` - Length of the chain.
+    /// * `permissions` - Permissions granted by the root credential.
+    /// * `retained` - Permissions retained at each level.
+    pub fn retained_permissions(delegators: usize, permissions: usize, retained: usize) -> Result<Self, String> {
+        let mut levels: Vec<Level> = vec![];
+        for i in 0..delegators {
+            let granted = match i.checked_mul(retained).and_then(|retained| permissions.checked_sub(retained)) {
+                Some(granted) => granted,
+                None => { return Err(format!("Cannot retain {retained} permissions at each of the {delegators} levels out of {permissions}")) }
+            };
+            levels.push(Level { granted, disclosed: granted });
+        }
+        Self::new(vec![SweepPoint { levels, recorded: (0..delegators).collect() }])
+    }
+
+    pub fn points(&self) -> &Vec<SweepPoint> {
+        &self.points
+    }
+
+    /// Returns the number of rows of the experiment, one for each recorded level.
+    pub fn rows(&self) -> usize {
+        self.row_parameters().len()
+    }
+
+    /// Returns the parameters of each row of the experiment, in the order of the chains and of
+    /// their levels.
+    pub fn row_parameters(&self) -> Vec<RowParameters> {
+        let mut rows: Vec<RowParameters> = vec![];
+        for point in self.points.iter() {
+            for (i, level) in point.levels.iter().enumerate() {
+                if point.recorded.contains(&i) {
+                    rows.push(RowParameters { delegators: i + 1, permissions: level.granted, disclosed: level.disclosed });
+                }
+            }
+        }
+        rows
+    }
+
+    fn max_levels(&self) -> usize {
+        self.points.iter().map(|point| point.levels.len()).max().unwrap_or(0)
+    }
+
+    fn max_permissions(&self) -> usize {
+        self.points.iter().flat_map(|point| point.levels.iter().map(|level| level.granted)).max().unwrap_or(0)
+    }
+}
+
+/// Parameters of a row: the delegator whose credential is measured, counted from 1 for the root
+/// credential, and the permissions granted and disclosed at its level.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct RowParameters {
+    pub delegators: usize,
+    pub permissions: usize,
+    pub disclosed: usize,
+}
+
+impl RowParameters {
+    fn values(&self) -> [usize; 3] {
+        [self.delegators, self.permissions, self.disclosed]
+    }
+}
+
+/// Measurements taken at a recorded level of a chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Measurements {
+    pub vc_issuance: Samples,
+    pub vp_length: usize,
+    pub vp_issuance: Samples,
+    pub vp_verification: Samples,
+}
+
+/// Metric of an experiment, written to its own csv file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    VcIssuance,
+    VpLength,
+    VpIssuance,
+    VpVerification,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 4] = [Metric::VcIssuance, Metric::VpLength, Metric::VpIssuance, Metric::VpVerification];
+
+    /// Returns the suffix of the csv file of the metric.
+    pub fn file_suffix(&self) -> &'static str {
+        match self {
+            Metric::VcIssuance => "vc_issuance",
+            Metric::VpLength => "vp_jwt_length",
+            Metric::VpIssuance => "vp_issuance",
+            Metric::VpVerification => "vp_verification",
+        }
+    }
+
+    /// Splits the name of a csv file written by an experiment, without extension, into the name
+    /// of the experiment and its metric.
+    pub fn parse_file_name(name: &str) -> Option<(&str, Metric)> {
+        Metric::ALL.into_iter()
+            .find_map(|metric| name.strip_suffix(&format!("_{}", metric.file_suffix())).map(|experiment| (experiment, metric)))
+    }
+
+    pub fn is_duration(&self) -> bool {
+        *self != Metric::VpLength
+    }
+
+    pub fn unit(&self) -> &'static str {
+        if self.is_duration() { "us" } else { "bytes" }
+    }
+
+    /// Returns the samples of the metric, none if it is not a duration.
+    pub fn samples<'a>(&self, measurements: &'a Measurements) -> Option<&'a Samples> {
+        match self {
+            Metric::VcIssuance => Some(&measurements.vc_issuance),
+            Metric::VpLength => None,
+            Metric::VpIssuance => Some(&measurements.vp_issuance),
+            Metric::VpVerification => Some(&measurements.vp_verification),
+        }
+    }
+
+    /// Returns the value of the metric, the mean in microseconds for durations and bytes for lengths.
+    pub fn value(&self, measurements: &Measurements) -> u128 {
+        match self.samples(measurements) {
+            Some(samples) => samples.mean().as_micros(),
+            None => measurements.vp_length as u128,
+        }
+    }
+}
+
+/// Scheme measured by an experiment. It is implemented by every `DelegationScheme`, so that
+/// schemes with different credentials and entities can be compared in the same experiment.
+pub trait MeasuredScheme {
+    /// Name of the scheme, used as its column in the csv files.
+    fn name(&self) -> &str;
+
+    /// Builds the chains of a sweep and measures their recorded levels.
+    ///
+    /// # Arguments
+    /// * `sweep` - Chains to be built.
+    /// * `benchmark` - Settings of the benchmark of each operation.
+    ///
+    /// # Returns
+    /// The measurements of each row of the sweep or a string containing an error.
+    fn measure(&self, sweep: &Sweep, benchmark: &Benchmark) -> Result<Vec<Measurements>, String>;
+}
+
+impl <S: DelegationScheme> MeasuredScheme for S {
+    fn name(&self) -> &str {
+        DelegationScheme::name(self)
+    }
+
+    fn measure(&self, sweep: &Sweep, benchmark: &Benchmark) -> Result<Vec<Measurements>, String> {
+        // The entity i issues the credential of level i, the last one only presents it
+        let mut entities: Vec<S::Issuer> = vec![];
+        for i in 0..=sweep.max_levels() {
+            match self.issuer(format!("https://vc.example/delegators/d{i}")) {
+                Ok(entity) => entities.push(entity),
+                Err(err) => { return Err(format!("Could not create delegator d{i}: [{err}]")) }
+            }
+        }
+        let owner_id = String::from("https://vc.example/delegators/d0");
+        let verifier = match entities.first().map(|owner| self.verifier(owner)) {
+            Some(Ok(verifier)) => verifier,
+            Some(Err(err)) => { return Err(format!("Could not create verifier: [{err}]")) }
+            None => { return Err(String::from("No owner to create the verifier")) }
+        };
+        let request = match PresentationRequest::new(owner_id, REQUEST_VALIDITY) {
+            Ok(request) => request,
+            Err(err) => { return Err(format!("Could not create presentation request: [{err}]")) }
+        };
+        let mut permissions: Vec<Permission> = vec![];
+        for i in 0..sweep.max_permissions() {
+            match Permission::new(String::from(RESOURCE_URI), format!("p{i}")) {
+                Ok(permission) => permissions.push(permission),
+                Err(err) => { return Err(format!("Could not create permission p{i}: [{err}]")) }
+            }
+        }
+
+        // Issuers reject a credential id they have already issued, so every issuance, including
+        // each benchmarked repetition, gets a fresh one
+        let issued_credentials = Cell::new(0usize);
+        let credential_parameters = |i: usize, granted: usize| {
+            let credential_number = issued_credentials.get();
+            issued_credentials.set(credential_number + 1);
+            CredentialParameters {
+                context: vec![String::from("https://www.w3.org/ns/credentials/v2")],
+                credential_id: format!("http://delegation.example/credentials/{credential_number}"),
+                valid_from: String::from("2026-01-01T00:00:00Z"),
+                delegatee_id: format!("https://vc.example/delegators/d{}", i + 1),
+                validity_period: Duration::new(3600, 0),
+                permissions: permissions[..granted].to_vec(),
+            }
+        };
+
+        let mut measurements: Vec<Measurements> = vec![];
+        for point in sweep.points() {
+            let mut vc = None;
+            for (i, level) in point.levels.iter().enumerate() {
+                let (issuer, holder) = (&entities[i], &entities[i + 1]);
+
+                // Levels that are not recorded are only needed to delegate the next ones
+                if !point.recorded.contains(&i) {
+                    match self.issue_verifiable_credential(issuer, &credential_parameters(i, level.granted), vc.take()) {
+                        Ok(result_vc) => vc = Some(result_vc),
+                        Err(err) => { return Err(format!("Could not issue credential at level {i}: [{err}]")) }
+                    }
+                    continue;
+                }
+
+                let (vc_issuance, result_vc) = benchmark.run(
+                    || self.issue_verifiable_credential(issuer, &credential_parameters(i, level.granted), vc.clone())
+                )?;
+
+                let disclosures = permissions[..level.disclosed].to_vec();
+                let (vp_issuance, vp) = benchmark.run(
+                    || self.issue_verifiable_presentation(issuer, holder, result_vc.clone(), disclosures.clone(), &request)
+                )?;
+
+                let (vp_verification, _) = benchmark.run(
+                    || self.verify_verifiable_presentation(&verifier, &vp, &request)
+                )?;
+
+                measurements.push(Measurements { vc_issuance, vp_length: vp.jwt.len(), vp_issuance, vp_verification });
+                vc = Some(result_vc);
+            }
+        }
+
+        Ok(measurements)
+    }
+}
+
+/// File written by an experiment, as described in its metadata.
+#[derive(Serialize)]
+struct FileMetadata {
+    file: String,
+    metric: &'static str,
+    unit: &'static str,
+    /// Either the `mean` of each scheme, their `statistics` or every `sample`.
+    content: &'static str,
+}
+
+/// Metadata of an experiment, written next to its files.
+#[derive(Serialize)]
+struct ExperimentMetadata<'a> {
+    experiment: &'a str,
+    run: Option<&'a RunMetadata>,
+    iterations: u32,
+    warmup: u32,
+    schemes: Vec<&'a str>,
+    files: Vec<FileMetadata>,
+    sweep: &'a Sweep,
+}
+
+/// Experiment comparing schemes over a parameter sweep. Each metric is written to the csv file
+/// `{name}_{metric}`, with the parameters of each row followed by a column for each scheme.
+/// Durations are the mean in microseconds, their distribution being written to
+/// `{name}_{metric}_stats` and every sample to `{name}_{metric}_samples`, with a line for each row
+/// and scheme. The parameters, benchmark settings and run are described in `{name}.meta.json`.
+pub struct Experiment {
+    name: String,
+    sweep: Sweep,
+    metrics: Vec<Metric>,
+    schemes: Vec<Box<dyn MeasuredScheme>>,
+    benchmark: Benchmark,
+    output_dir: PathBuf,
+    json_lines: bool,
+    run_metadata: Option<RunMetadata>,
+}
+
+impl Experiment {
+
+    /// Creates an experiment without schemes.
+    ///
+    /// # Arguments
+    /// * `name` - Prefix of the csv files of the experiment.
+    /// * `sweep` - Chains to be measured.
+    /// * `metrics` - Metrics to be written.
+    /// * `benchmark` - Settings of the benchmark of each operation.
+    pub fn new(name: String, sweep: Sweep, metrics: Vec<Metric>, benchmark: Benchmark) -> Self {
+        Experiment {
+            name, sweep, metrics, schemes: vec![], benchmark, output_dir: PathBuf::from(CSV_DIR), json_lines: false, run_metadata: None
+        }
+    }
+
+    /// Sets the directory where the csv files are written, `CSV_DIR` by default.
+    pub fn set_output_dir(&mut self, output_dir: &Path) {
+        self.output_dir = output_dir.to_path_buf();
+    }
+
+    /// Sets whether every file is also written as JSON lines.
+    pub fn set_json_lines(&mut self, json_lines: bool) {
+        self.json_lines = json_lines;
+    }
+
+    /// Sets the description of the run the experiment is part of, written to its metadata.
+    pub fn set_run_metadata(&mut self, run_metadata: RunMetadata) {
+        self.run_metadata = Some(run_metadata);
+    }
+
+    /// Adds a scheme to the experiment. Schemes verifying the same presentation several times, they
+    /// must be created with a replay cache that does not consume the nonces, like
+    /// `DisabledReplayCache`.
+    pub fn with_scheme<S: MeasuredScheme + 'static>(mut self, scheme: S) -> Self {
+        self.schemes.push(Box::new(scheme));
+        self
+    }
+
+    /// Measures every scheme and writes the csv files of the experiment.
+    ///
+    /// # Returns
+    /// The result of the operation or a string containing an error, also if two schemes have the
+    /// same name, since their columns could not be told apart.
+    pub fn run(&self) -> Result<(), String> {
+        for (i, scheme) in self.schemes.iter().enumerate() {
+            if self.schemes[..i].iter().any(|other| other.name() == scheme.name()) {
+                return Err(format!("Scheme {} is measured more than once", scheme.name()))
+            }
+        }
+
+        let mut columns: Vec<Vec<Measurements>> = vec![];
+        for scheme in self.schemes.iter() {
+            let measurements = scheme.measure(&self.sweep, &self.benchmark)?;
+            if measurements.len() != self.sweep.rows() {
+                return Err(format!("Scheme {} measured {} rows instead of {}", scheme.name(), measurements.len(), self.sweep.rows()))
+            }
+            columns.push(measurements);
+        }
+
+        let rows = self.sweep.row_parameters();
+        let scheme_names: Vec<&str> = self.schemes.iter().map(|scheme| scheme.name()).collect();
+        let mut writer = self.writer(scheme_names.iter().copied())?;
+        let mut statistics_writer = self.writer(STATISTICS_COLUMNS)?;
+        let mut samples_writer = self.writer(SAMPLES_COLUMNS)?;
+        let mut files: Vec<FileMetadata> = vec![];
+
+        for metric in self.metrics.iter() {
+            let filename = format!("{}_{}", self.name, metric.file_suffix());
+            writer.add_file(&filename)?;
+
+            for (row, parameters) in rows.iter().enumerate() {
+                let mut record: Vec<u128> = parameters.values().iter().map(|value| *value as u128).collect();
+                record.extend(columns.iter().map(|column| metric.value(&column[row])));
+                writer.write_record_to_file(&filename, record)?;
+            }
+            let content = if metric.is_duration() { "mean" } else { "value" };
+            files.push(FileMetadata { file: filename.clone(), metric: metric.file_suffix(), unit: metric.unit(), content });
+
+            if !metric.is_duration() {
+                continue;
+            }
+            let statistics_filename = format!("{filename}_stats");
+            statistics_writer.add_file(&statistics_filename)?;
+            let samples_filename = format!("{filename}_samples");
+            samples_writer.add_file(&samples_filename)?;
+
+            for (row, parameters) in rows.iter().enumerate() {
+                let [delegators, permissions, disclosed] = parameters.values();
+                for (scheme, column) in self.schemes.iter().zip(columns.iter()) {
+                    let samples = match metric.samples(&column[row]) {
+                        Some(samples) => samples,
+                        None => { continue }
+                    };
+
+                    let statistics = samples.statistics();
+                    statistics_writer.write_record_to_file(&statistics_filename, (
+                        delegators, permissions, disclosed, scheme.name(),
+                        micros(&statistics.mean), micros(&statistics.median), micros(&statistics.p95),
+                        micros(&statistics.p99), micros(&statistics.min), micros(&statistics.max), micros(&statistics.std_dev),
+                        micros(&statistics.ci95.0), micros(&statistics.ci95.1)
+                    ))?;
+                    for (iteration, duration) in samples.durations().iter().enumerate() {
+                        samples_writer.write_record_to_file(
+                            &samples_filename, (delegators, permissions, disclosed, scheme.name(), iteration, micros(duration))
+                        )?;
+                    }
+                }
+            }
+            files.push(FileMetadata { file: statistics_filename, metric: metric.file_suffix(), unit: metric.unit(), content: "statistics" });
+            files.push(FileMetadata { file: samples_filename, metric: metric.file_suffix(), unit: metric.unit(), content: "samples" });
+        }
+
+        writer.write_metadata(&self.name, &ExperimentMetadata {
+            experiment: &self.name,
+            run: self.run_metadata.as_ref(),
+            iterations: self.benchmark.iterations(),
+            warmup: self.benchmark.warmup(),
+            schemes: scheme_names,
+            files,
+            sweep: &self.sweep,
+        })?;
+
+        Ok(())
+    }
+}
+
+impl Experiment {
+    // Writer of files whose columns are the parameters of the rows followed by the given ones
+    fn writer<'a>(&self, columns: impl IntoIterator<Item = &'a str>) -> Result<CSVWriter, String> {
+        let columns = PARAMETER_COLUMNS.into_iter().chain(columns).map(|column| column.to_string()).collect();
+        let mut writer = CSVWriter::with_dir(columns, &self.output_dir)?;
+        writer.set_json_lines(self.json_lines);
+        Ok(writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Bn254;
+    use crate::delegation::accumulators::verification_mode::VerificationMode;
+    use crate::delegation::entities::ours::our_scheme::OurScheme;
+    use crate::delegation::entities::pjv::pjv_scheme::PJVScheme;
+    use crate::delegation::registry::in_memory_registry::InMemoryRegistry;
+    use crate::delegation::replay::disabled_replay_cache::DisabledReplayCache;
+
+    #[test]
+    fn build_sweeps() -> Result<(), Box<dyn std::error::Error>> {
+        let sweep = Sweep::retained_permissions(3, 6, 2)?;
+        let granted: Vec<usize> = sweep.points()[0].levels.iter().map(|level| level.granted).collect();
+        assert_eq!(granted, vec![6, 4, 2]);
+        assert_eq!(sweep.rows(), 3);
+
+        let sweep = Sweep::permissions(3, &[1, 2, 3, 4])?;
+        assert_eq!(sweep.points().len(), 4);
+        assert_eq!(sweep.rows(), 4);
+        assert_eq!(sweep.points()[3].recorded, vec![2]);
+
+        let sweep = Sweep::delegators(&[2, 4], 2, 1)?;
+        assert_eq!(sweep.points()[0].levels.len(), 4);
+        assert_eq!(sweep.points()[0].recorded, vec![1, 3]);
+
+        assert!(Sweep::delegators(&[1, 2, 3], 2, 3).is_err());
+        assert!(Sweep::delegators(&[1, 2, 3], 2, 0).is_err());
+        assert!(Sweep::delegators(&[0, 1], 2, 1).is_err());
+        assert!(Sweep::delegators(&[], 2, 1).is_err());
+        assert!(Sweep::retained_permissions(4, 6, 2).is_err());
+        assert!(Sweep::new(vec![SweepPoint { levels: vec![Level { granted: 1, disclosed: 1 }, Level { granted: 2, disclosed: 1 }], recorded: vec![] }]).is_err());
+        assert!(Sweep::new(vec![SweepPoint { levels: vec![Level { granted: 1, disclosed: 1 }], recorded: vec![1] }]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn measure_every_scheme() -> Result<(), Box<dyn std::error::Error>> {
+        let sweep = Sweep::new(vec![
+            SweepPoint { levels: vec![Level { granted: 3, disclosed: 2 }, Level { granted: 2, disclosed: 1 }], recorded: vec![0, 1] },
+            SweepPoint { levels: vec![Level { granted: 2, disclosed: 2 }; 3], recorded: vec![2] },
+        ])?;
+
+        let schemes: Vec<Box<dyn MeasuredScheme>> = vec![
+            Box::new(OurScheme::<Bn254>::with_replay_cache(InMemoryRegistry::shared(), DisabledReplayCache::shared(), VerificationMode::Batched)),
+            Box::new(PJVScheme::with_replay_cache(
+                InMemoryRegistry::shared(), DisabledReplayCache::shared(),
+                String::from("https://vc.example/delegators/d0"), String::from(RESOURCE_URI)
+            )),
+        ];
+        for scheme in schemes.iter() {
+            let measurements = scheme.measure(&sweep, &Benchmark::new(2))?;
+            assert_eq!(measurements.len(), sweep.rows());
+            assert!(measurements.iter().all(|measurement| measurement.vp_verification.durations().len() == 2));
+            // Longer chains carry more credentials
+            assert!(measurements[1].vp_length > measurements[0].vp_length, "{}", scheme.name());
+            assert!(measurements[2].vp_length > measurements[1].vp_length, "{}", scheme.name());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn write_self_describing_files() -> Result<(), Box<dyn std::error::Error>> {
+        let output_dir = std::env::temp_dir().join(format!("delegation-experiment-{}", std::process::id()));
+        let sweep = Sweep::delegators(&[1, 2], 2, 1)?;
+        let mut experiment = Experiment::new(String::from("iod"), sweep, vec![Metric::VpLength, Metric::VpVerification], Benchmark::new(2))
+            .with_scheme(PJVScheme::with_replay_cache(
+                InMemoryRegistry::shared(), DisabledReplayCache::shared(),
+                String::from("https://vc.example/delegators/d0"), String::from(RESOURCE_URI)
+            ));
+        experiment.set_output_dir(&output_dir);
+        experiment.set_json_lines(true);
+        experiment.set_run_metadata(RunMetadata::collect("bn254")?);
+        experiment.run()?;
+
+        let lengths = std::fs::read_to_string(output_dir.join("iod_vp_jwt_length.csv"))?;
+        let lines: Vec<&str> = lengths.lines().collect();
+        assert_eq!(lines[0], "delegators,permissions,disclosed,PJVs");
+        assert!(lines[2].starts_with("2,2,1,"));
+
+        let samples = std::fs::read_to_string(output_dir.join("iod_vp_verification_samples.jsonl"))?;
+        let sample: serde_json::Value = serde_json::from_str(samples.lines().next().unwrap_or_default())?;
+        assert_eq!(sample["scheme"], "PJVs");
+        assert_eq!(sample["delegators"], 1);
+
+        let metadata: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(output_dir.join("iod.meta.json"))?)?;
+        assert_eq!(metadata["iterations"], 2);
+        assert_eq!(metadata["run"]["curve"], "bn254");
+        assert_eq!(metadata["files"].as_array().map(Vec::len), Some(4));
+
+        std::fs::remove_dir_all(output_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn reject_schemes_with_the_same_name() -> Result<(), Box<dyn std::error::Error>> {
+        let modes = [VerificationMode::Sequential, VerificationMode::Parallel, VerificationMode::Batched];
+        let names: Vec<String> = modes.iter()
+            .map(|mode| DelegationScheme::name(&OurScheme::<Bn254>::new(InMemoryRegistry::shared(), *mode)).to_string())
+            .collect();
+        assert!(names.iter().enumerate().all(|(i, name)| !names[..i].contains(name)), "{names:?}");
+
+        let sweep = Sweep::delegators(&[1], 1, 1)?;
+        let experiment = Experiment::new(String::from("iod"), sweep, vec![Metric::VpLength], Benchmark::new(1))
+            .with_scheme(OurScheme::<Bn254>::new(InMemoryRegistry::shared(), VerificationMode::Parallel))
+            .with_scheme(OurScheme::<Bn254>::new(InMemoryRegistry::shared(), VerificationMode::Parallel));
+        match experiment.run() {
+            Err(err) if err.contains("more than once") => Ok(()),
+            other => Err(format!("Expected duplicate schemes to be rejected, got {other:?}").into()),
+        }
+    }
+}
@@ -1,3 +1,8 @@
 pub mod delegation;
 pub mod benchmark;
-pub mod csv_writer;
\ No newline at end of file
+pub mod comparison;
+pub mod config;
+pub mod csv_writer;
+pub mod experiment;
+pub mod metadata;
+pub mod report;
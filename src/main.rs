@@ -1,627 +1,173 @@
+use ark_bls12_381::Bls12_381;
 use ark_bn254::Bn254;
 use ark_ec::pairing::Pairing;
-use delegation::delegation::entities::dtl_sim::{new_dlt_sim, DLTSim};
-use delegation::delegation::entities::ours::dlt_acc_entry::DLTSimAccEntry;
-use delegation::delegation::entities::ours::our_issuer::OurIssuer;
-use delegation::delegation::entities::ours::our_verifier::OurVerifier;
-use delegation::delegation::entities::pjv::pjv_issuer_verifier::PJVIssuerVerifier;
-use josekit::jwk::Jwk;
-use std::env;
-use std::str::FromStr;
-use std::time::Duration;
-use delegation::benchmark::Benchmark;
-use delegation::csv_writer::CSVWriter;
-
-fn fetch_usize_env_variable(variable_name: &str) -> Result<usize, String> {
-    let variable = fetch_env_variable(variable_name)?;
-    match usize::from_str(variable.as_str()) {
-        Ok(variable) => Ok(variable),
-        Err(err) => {
-            Err(format!("The environment variable {variable_name} cannot be parsed to i32 [{err}]"))
-        }
-    }
+use clap::{Parser, Subcommand};
+use delegation::comparison::{Comparison, Thresholds};
+use delegation::config::{BenchmarkConfig, CurveName, ExperimentConfig, PLOTS_DIR};
+use delegation::csv_writer::CSV_DIR;
+use delegation::delegation::accumulators::verification_mode::VerificationMode;
+use delegation::delegation::entities::ours::our_scheme::OurScheme;
+use delegation::delegation::entities::pjv::pjv_scheme::PJVScheme;
+use delegation::delegation::registry::in_memory_registry::InMemoryRegistry;
+use delegation::delegation::replay::disabled_replay_cache::DisabledReplayCache;
+use delegation::experiment::{Experiment, Metric, Sweep};
+use delegation::metadata::RunMetadata;
+use delegation::report::render_charts;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+/// Benchmarks our delegation scheme against the one of the PJVs and writes the results to csv
+/// files. Without a command, runs the experiments of the paper.
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    /// Amount of samples taken of each operation.
+    #[arg(long, global = true, default_value_t = 100, value_parser = clap::value_parser!(u32).range(1..))]
+    iterations: u32,
+    /// Amount of times each operation is executed before taking samples.
+    #[arg(long, global = true, default_value_t = 3)]
+    warmup: u32,
+    /// Curve of the accumulators of our scheme.
+    #[arg(long, global = true, value_enum, default_value_t = CurveName::Bn254)]
+    curve: CurveName,
+    /// Directory where the csv files are written.
+    #[arg(long, global = true, default_value = CSV_DIR)]
+    output_dir: PathBuf,
+    /// Also writes every csv file as JSON lines.
+    #[arg(long, global = true)]
+    json_lines: bool,
+    /// Directory where the charts of the csv files are rendered.
+    #[arg(long, global = true, default_value = PLOTS_DIR)]
+    plots_dir: PathBuf,
+    /// Does not render the charts after running the experiments.
+    #[arg(long, global = true)]
+    no_plots: bool,
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-fn fetch_env_variable(variable_name: &str) -> Result<String, String> {
-    match env::var(&variable_name) {
-        Ok(variable) => Ok(variable),
-        Err(err) => {
-            Err(format!("The environment variable {variable_name} is not set [{err}]"))
-        },
-    }
-}
-
-fn get<I>(vector: &Vec<I>, i: usize) -> Result<&I, String> {
-    match vector.get(i) {
-        Some(item) => Ok(item),
-        None => { Err(format!("No item found at index {i}")) },
+impl Cli {
+    // Configuration of the given experiments with the options of the command line
+    fn config(&self, experiments: Vec<ExperimentConfig>) -> BenchmarkConfig {
+        BenchmarkConfig {
+            iterations: self.iterations,
+            warmup: self.warmup,
+            curve: self.curve,
+            output_dir: self.output_dir.clone(),
+            json_lines: self.json_lines,
+            plots: !self.no_plots,
+            plots_dir: self.plots_dir.clone(),
+            experiments,
+        }
     }
 }
 
-fn setup_ours<E: Pairing>(delegators_size: usize) ->
-    Result<(
-        DLTSim<DLTSimAccEntry<E>>,
-        DLTSim<Jwk>,
-        Vec<String>,
-        Vec<String>,
-        Vec<OurIssuer<E>>,
-        Vec<String>,
-        String,
-        Duration
-    ), String>
-{
-    let accumulator_dlt: DLTSim<DLTSimAccEntry<E>> = new_dlt_sim();
-    let verification_dlt: DLTSim<Jwk> = new_dlt_sim();
-
-    let mut delegator_ids: Vec<String> = vec![];
-    let mut credential_ids: Vec<String> = vec![];
-    let mut delegators: Vec<OurIssuer<E>> = vec![];
-
-    for i in 0..delegators_size {
-        let id = format!("https://vc.example/delegators/d{i}");
-        delegator_ids.push(id.clone());
-
-        let credential_id = format!("http://delegation.example/credentials/{i}");
-        credential_ids.push(credential_id.clone());
-
-        let delegator = OurIssuer::new(id, accumulator_dlt.clone(), verification_dlt.clone())?;
-        delegators.push(delegator);
-    }
-
-    let id = format!("https://vc.example/delegators/d{delegators_size}");
-    delegator_ids.push(id);
-
-    let context: Vec<String> = vec![ String::from("https://www.w3.org/ns/credentials/v2") ];
-
-    let valid_from =  String::from("2026-01-01T00:00:00Z");
-    let validity_period: Duration = Duration::new(3600, 0);
-
-    Ok((accumulator_dlt, verification_dlt, delegator_ids, credential_ids, delegators, context, valid_from, validity_period))
-
+#[derive(Subcommand)]
+enum Command {
+    #[command(flatten)]
+    Experiment(ExperimentConfig),
+    /// Runs the benchmark matrix of a TOML or JSON configuration file, which also sets the
+    /// iterations, warm-up, curve and outputs.
+    Config {
+        path: PathBuf,
+    },
+    /// Renders the charts of the csv files of the output directory, without running experiments.
+    Plot,
+    /// Compares the csv files of the output directory with the ones of a baseline run, aligned by
+    /// their parameters, and fails if a metric increased beyond its threshold or if a value of the
+    /// baseline is missing from the new run.
+    Compare {
+        baseline: PathBuf,
+        /// Increase of the durations beyond which they regressed, in percent.
+        #[arg(long, default_value_t = Thresholds::default().duration)]
+        threshold: f64,
+        /// Increase of the VP JWT lengths beyond which they regressed, in percent.
+        #[arg(long, default_value_t = Thresholds::default().length)]
+        length_threshold: f64,
+        /// Only reports the files, schemes and rows of the baseline that the new run did not write.
+        #[arg(long)]
+        allow_missing: bool,
+    },
 }
 
-fn setup_pjvs(delegators_size: usize) ->
-Result<(
-    Vec<String>,
-    Vec<String>,
-    Vec<PJVIssuerVerifier>,
-    Vec<String>,
-    String,
-    Duration,
-    String,
-    String
-), String>
-{
-    let encryption_dlt: DLTSim<Jwk> = new_dlt_sim();
-    let verification_dlt: DLTSim<Jwk> = new_dlt_sim();
-
-    let mut delegator_ids: Vec<String> = vec![];
-    let mut credential_ids: Vec<String> = vec![];
-    let mut delegators: Vec<PJVIssuerVerifier> = vec![];
-
-    for i in 0..delegators_size {
-        let id = format!("https://vc.example/delegators/d{i}");
-        delegator_ids.push(id.clone());
-
-        let credential_id = format!("http://delegation.example/credentials/{i}");
-        credential_ids.push(credential_id.clone());
-
-        let delegator = PJVIssuerVerifier::new(id, encryption_dlt.clone(), verification_dlt.clone())?;
-        delegators.push(delegator);
-    }
-
-    let id = format!("https://vc.example/delegators/d{delegators_size}");
-    delegator_ids.push(id);
-
-    let valid_from =  String::from("2026-01-01T00:00:00Z");
-    let validity_period: Duration = Duration::new(3600, 0);
-
-    let owner = get(&delegator_ids, 0)?.clone();
-    let resource_uri: String = String::from("https://vc.example/resources/r1");
-
-    let context: Vec<String> = vec![ String::from("https://www.w3.org/ns/credentials/v2") ];
-
-    Ok((delegator_ids, credential_ids, delegators, context, valid_from, validity_period, owner, resource_uri))
+/// Adds the compared schemes to an experiment, each one with its own registry. The benchmark
+/// verifies the same presentation several times, so the nonces are never consumed.
+fn with_schemes<E: Pairing>(experiment: Experiment) -> Experiment {
+    let owner = String::from("https://vc.example/delegators/d0");
+    let resource_uri = String::from("https://vc.example/resources/r1");
 
+    experiment
+        .with_scheme(OurScheme::<E>::with_replay_cache(InMemoryRegistry::shared(), DisabledReplayCache::shared(), VerificationMode::Parallel))
+        .with_scheme(OurScheme::<E>::with_replay_cache(InMemoryRegistry::shared(), DisabledReplayCache::shared(), VerificationMode::Batched))
+        .with_scheme(PJVScheme::with_replay_cache(InMemoryRegistry::shared(), DisabledReplayCache::shared(), owner, resource_uri))
 }
 
-fn iterate_over_delegators<E: Pairing>(max_delegators: usize, total_permissions: usize, disclose: usize, iterations: i8) -> Result<(), String> {
-
-    if disclose > total_permissions {
-        return Err(format!("Cannot disclose more permissions than those included in the credential [{disclose} > {total_permissions}]"))
-    } else if disclose < 1 {
-        return Err(format!("Permissions to disclose must be at least 1 [{disclose}]"))
-    }
-
-    const IOD_VC_ISSUANCE: &str = "iod_vc_issuance";
-    const IOD_VP_LENGTH: &str = "iod_vp_jwt_length";
-    const IOD_VP_ISSUANCE: &str = "iod_vp_issuance";
-    const IOD_VP_VERIFICATION: &str = "iod_vp_verification";
-
-    let mut iod_vc_issuance = disclose.to_string();
-    iod_vc_issuance.push('_');
-    iod_vc_issuance.push_str(IOD_VC_ISSUANCE);
-    let mut iod_vp_length = disclose.to_string();
-    iod_vp_length.push('_');
-    iod_vp_length.push_str(IOD_VP_LENGTH);
-    let mut iod_vp_issuance = disclose.to_string();
-    iod_vp_issuance.push('_');
-    iod_vp_issuance.push_str(IOD_VP_ISSUANCE);
-    let mut iod_vp_verification = disclose.to_string();
-    iod_vp_verification.push('_');
-    iod_vp_verification.push_str(IOD_VP_VERIFICATION);
-
-    let mut writer = CSVWriter::new(vec![String::from("Ours"), String::from("PJVs")])?;
-    writer.add_file(&iod_vc_issuance)?;
-    writer.add_file(&iod_vp_length)?;
-    writer.add_file(&iod_vp_issuance)?;
-    writer.add_file(&iod_vp_verification)?;
-
-    // =============================================================================================
-    // ==================================        OURS        =======================================
-    // =============================================================================================
-    let mut our_vps: Vec<String> = vec![];
-    let mut our_vc_issuance_duration: Vec<Duration> = vec![];
-    let mut our_vp_issuance_duration: Vec<Duration> = vec![];
-    let mut our_vp_verification_duration: Vec<Duration> = vec![];
-
-    let (accumulator_dlt, verification_dlt, delegator_ids, credential_ids, delegators, context, valid_from, validity_period) = setup_ours(max_delegators)?;
-
-    let mut permissions: Vec<String> = vec![];
-    for i in 0..total_permissions {
-        permissions.push(format!("https://vc.example/resources/r1:p{i}"));
-    }
-
-    let mut vc = None;
-    for i in 0..max_delegators {
-        let delegator = get(&delegators, i)?;
-        let credential_id = get(&credential_ids, i)?;
-        let delegatee_id = get(&delegator_ids, i + 1)?;
-
-        let (duration, result_vc) = Benchmark::benchmark_function(
-            || delegator.issue_delegation_verifiable_credential(
-                context.clone(), credential_id.clone(), valid_from.clone(), delegatee_id.clone(),
-                validity_period.clone(), permissions.clone(), vc.clone()
-            ),
-            iterations
-        )?;
-        our_vc_issuance_duration.push(duration);
-
-        let disclosures = match permissions.get(0..disclose) {
-            Some(disclosures) => disclosures,
-            None => return Err(String::from("Could not get slice from permissions"))
-        }.to_vec();
-
-        let (duration, result_vp) = Benchmark::benchmark_function(
-            || delegator.issue_delegation_verifiable_presentation(
-                result_vc.clone(), disclosures.clone()
-            ),
-            iterations
-        )?;
-        our_vp_issuance_duration.push(duration);
-
-        our_vps.push(result_vp);
-
-        vc = Some(result_vc);
-    }
-
-    let verifier: OurVerifier<E> = OurVerifier::new(accumulator_dlt, verification_dlt)?;
-
-    for (i, vp) in our_vps.iter().enumerate() {
-        let presenter_id = get(&delegator_ids, i)?;
-        let (duration, _) = Benchmark::benchmark_function(|| verifier.verify_verifiable_presentation(presenter_id.clone(), vp.clone(), true), iterations)?;
-        our_vp_verification_duration.push(duration);
-    }
-
-    // =============================================================================================
-    // ==================================        PJVS        =======================================
-    // =============================================================================================
-    let mut pjv_vps: Vec<String> = vec![];
-    let mut pjv_vc_issuance_duration: Vec<Duration> = vec![];
-    let mut pjv_vp_issuance_duration: Vec<Duration> = vec![];
-    let mut pjv_vp_verification_duration: Vec<Duration> = vec![];
-
-    let (delegator_ids, credential_ids, delegators, context, valid_from, validity_period, owner, resource_uri) = setup_pjvs(max_delegators)?;
-
-    let mut operations: Vec<String> = vec![];
-    for i in 0..total_permissions {
-        operations.push(format!("p{i}"));
-    }
-
-    let mut vc = None;
-    for i in 0..max_delegators {
-        let delegator = get(&delegators, i)?;
-        let credential_id = get(&credential_ids, i)?;
-        let delegatee_id = get(&delegator_ids, i + 1)?;
-
-        let (duration, result_vc) = Benchmark::benchmark_function(
-            || delegator.issue_delegation_verifiable_credential(
-                context.clone(), credential_id.clone(), valid_from.clone(), delegatee_id.clone(),
-                validity_period.clone(), owner.clone(), resource_uri.clone(), operations.clone(), vc.clone()
-            ),
-            iterations
-        )?;
-        pjv_vc_issuance_duration.push(duration);
-
-        let disclosures = match operations.get(0..disclose) {
-            Some(disclosures) => disclosures,
-            None => return Err(String::from("Could not get slice from operations"))
-        }.to_vec();
-
-        let (duration, result_vp) = Benchmark::benchmark_function(
-            || delegator.issue_delegation_verifiable_presentation(
-                result_vc.clone(), disclosures.clone()
-            ),
-            iterations
-        )?;
-        pjv_vp_issuance_duration.push(duration);
-
-        pjv_vps.push(result_vp);
-
-        vc = Some(result_vc);
+fn compare(baseline_dir: &Path, current_dir: &Path, thresholds: &Thresholds, allow_missing: bool) -> Result<(), Box<dyn Error>> {
+    let comparison = Comparison::new(baseline_dir, current_dir)?;
+    for added in comparison.added.iter() {
+        println!("Skipped: {added}");
     }
-
-    let verifier = get(&delegators, 0)?;
-
-    for (i, vp) in pjv_vps.iter().enumerate() {
-        let presenter_id = get(&delegator_ids, i)?;
-        let (duration, _) = Benchmark::benchmark_function(|| verifier.verify_verifiable_presentation(presenter_id.clone(), vp.clone()), iterations)?;
-
-        pjv_vp_verification_duration.push(duration);
+    for missing in comparison.missing.iter() {
+        println!("Missing: {missing}");
     }
-
-    let our_vc_issuance_ms: Vec<u128> = our_vc_issuance_duration.iter().map(Duration::as_micros).collect();
-    let pjv_vc_issuance_ms: Vec<u128> = pjv_vc_issuance_duration.iter().map(Duration::as_micros).collect();
-    for vc_issuance_ms in our_vc_issuance_ms.iter().zip(pjv_vc_issuance_ms.iter()) {
-        writer.write_record_to_file(&iod_vc_issuance, vc_issuance_ms)?;
-    }
-
-    let our_vp_lengths: Vec<usize> = our_vps.iter().map(|v| v.len()).collect();
-    let pjv_vp_lengths: Vec<usize> = pjv_vps.iter().map(|v| v.len()).collect();
-    for vp_length in our_vp_lengths.iter().zip(pjv_vp_lengths.iter()) {
-        writer.write_record_to_file(&iod_vp_length, vp_length)?;
+    let regressions = comparison.regressions(thresholds);
+    for regression in regressions.iter() {
+        println!("Regression: {regression}");
     }
+    println!("Compared {} values, {} regressions", comparison.differences.len(), regressions.len());
 
-    let our_vp_issuance_ms: Vec<u128> = our_vp_issuance_duration.iter().map(Duration::as_micros).collect();
-    let pjv_vp_issuance_ms: Vec<u128> = pjv_vp_issuance_duration.iter().map(Duration::as_micros).collect();
-    for vp_issuance_duration in our_vp_issuance_ms.iter().zip(pjv_vp_issuance_ms.iter()) {
-        writer.write_record_to_file(&iod_vp_issuance, vp_issuance_duration)?;
+    if !regressions.is_empty() {
+        return Err(format!("{} metrics regressed beyond the thresholds", regressions.len()).into())
     }
-
-    let our_vp_verification_ms: Vec<u128> = our_vp_verification_duration.iter().map(Duration::as_micros).collect();
-    let pjv_vp_verification_ms: Vec<u128> = pjv_vp_verification_duration.iter().map(Duration::as_micros).collect();
-    for vp_verification_ms in our_vp_verification_ms.iter().zip(pjv_vp_verification_ms.iter()) {
-        writer.write_record_to_file(&iod_vp_verification, vp_verification_ms)?;
+    if !allow_missing && !comparison.missing.is_empty() {
+        return Err(format!("{} values of the baseline are missing from the new run", comparison.missing.len()).into())
     }
-
     Ok(())
 }
 
-fn iterate_over_permissions<E: Pairing>(total_delegators: usize, max_permissions: usize, iterations: i8) -> Result<(), String> {
-
-    const IOP_VC_ISSUANCE: &str = "iop_vc_issuance";
-    const IOP_VP_LENGTH: &str = "iop_vp_jwt_length";
-    const IOP_VP_ISSUANCE: &str = "iop_vp_issuance";
-    const IOP_VP_VERIFICATION: &str = "iop_vp_verification";
-    let mut writer = CSVWriter::new(vec![String::from("Ours"), String::from("PJVs")])?;
-    writer.add_file(&String::from(IOP_VC_ISSUANCE))?;
-    writer.add_file(&String::from(IOP_VP_LENGTH))?;
-    writer.add_file(&String::from(IOP_VP_ISSUANCE))?;
-    writer.add_file(&String::from(IOP_VP_VERIFICATION))?;
-
-    // =============================================================================================
-    // ==================================        OURS        =======================================
-    // =============================================================================================
-    let mut our_vps: Vec<String> = vec![];
-    let mut our_vc_issuance_duration: Vec<Duration> = vec![];
-    let mut our_vp_issuance_duration: Vec<Duration> = vec![];
-    let mut our_vp_verification_duration: Vec<Duration> = vec![];
-
-    let (accumulator_dlt, verification_dlt, delegator_ids, credential_ids, delegators, context, valid_from, validity_period) = setup_ours(total_delegators)?;
-
-    let mut permissions: Vec<String> = vec![];
-
-    for i in 0..max_permissions {
-        permissions.push(format!("https://vc.example/resources/r1:p{i}"));
-
-        let mut vc = None;
-        for i in 0..total_delegators {
-            let delegator = get(&delegators, i)?;
-            let credential_id = get(&credential_ids, i)?;
-            let delegatee_id = get(&delegator_ids, i + 1)?;
-
-            let (vc_duration, result_vc) = Benchmark::benchmark_function(||
-                delegator.issue_delegation_verifiable_credential(
-                context.clone(), credential_id.clone(), valid_from.clone(), delegatee_id.clone(),
-                validity_period.clone(), permissions.clone(), vc.clone()
-            ), iterations)?;
-
-            let (vp_duration, vp) = Benchmark::benchmark_function(
-                || delegator.issue_delegation_verifiable_presentation(
-                    result_vc.clone(), permissions.clone()
-                ),
-                iterations
-            )?;
-
-            if i == total_delegators - 1 {
-                our_vc_issuance_duration.push(vc_duration);
-                our_vp_issuance_duration.push(vp_duration);
-                our_vps.push(vp);
-            }
-
-            vc = Some(result_vc);
-        }
-    }
-
-    let verifier: OurVerifier<E> = OurVerifier::new(accumulator_dlt, verification_dlt)?;
-
-    for vp in our_vps.iter() {
-        let presenter_id = get(&delegator_ids, total_delegators - 1)?;
-        let (duration, _) = Benchmark::benchmark_function(|| verifier.verify_verifiable_presentation(presenter_id.clone(), vp.clone(), true), iterations)?;
-        our_vp_verification_duration.push(duration);
-    }
-
-    // =============================================================================================
-    // ==================================        PJVS        =======================================
-    // =============================================================================================
-    let mut pjv_vps: Vec<String> = vec![];
-    let mut pjv_vc_issuance_duration: Vec<Duration> = vec![];
-    let mut pjv_vp_issuance_duration: Vec<Duration> = vec![];
-    let mut pjv_vp_verification_duration: Vec<Duration> = vec![];
-
-    let (delegator_ids, credential_ids, delegators, context, valid_from, validity_period, owner, resource_uri) = setup_pjvs(total_delegators)?;
-
-    let mut operations: Vec<String> = vec![];
-
-    for i in 0..max_permissions {
-        operations.push(format!("p{i}"));
-
-        let mut vc = None;
-        for i in 0..total_delegators {
-            let delegator = get(&delegators, i)?;
-            let credential_id = get(&credential_ids, i)?;
-            let delegatee_id = get(&delegator_ids, i + 1)?;
-
-            let (vc_duration, result_vc) = Benchmark::benchmark_function(
-                || delegator.issue_delegation_verifiable_credential(
-                    context.clone(), credential_id.clone(), valid_from.clone(), delegatee_id.clone(),
-                    validity_period.clone(), owner.clone(), resource_uri.clone(), operations.clone(), vc.clone()
-                ),
-                iterations
-            )?;
-
-            let (vp_duration, vp) = Benchmark::benchmark_function(
-                ||delegator.issue_delegation_verifiable_presentation(
-                    result_vc.clone(), operations.clone()
-                ),
-                iterations
-            )?;
-
-            if i == total_delegators - 1 {
-                pjv_vc_issuance_duration.push(vc_duration);
-                pjv_vp_issuance_duration.push(vp_duration);
-                pjv_vps.push(vp);
-            }
-
-            vc = Some(result_vc);
-        }
-    }
-
-    let verifier = get(&delegators, 0)?;
-
-    for vp in pjv_vps.iter() {
-        let presenter_id = get(&delegator_ids, total_delegators - 1)?;
-        let (duration, _) = Benchmark::benchmark_function(|| verifier.verify_verifiable_presentation(presenter_id.clone(), vp.clone()), iterations)?;
-        pjv_vp_verification_duration.push(duration);
+fn run<E: Pairing>(config: &BenchmarkConfig) -> Result<(), Box<dyn Error>> {
+    // Every sweep is checked before the first one runs
+    let mut sweeps: Vec<(String, Sweep)> = vec![];
+    for experiment in config.experiments.iter() {
+        sweeps.extend(experiment.sweeps()?);
     }
 
-    let our_vc_issuance_ms: Vec<u128> = our_vc_issuance_duration.iter().map(Duration::as_micros).collect();
-    let pjv_vc_issuance_ms: Vec<u128> = pjv_vc_issuance_duration.iter().map(Duration::as_micros).collect();
-    for vc_issuance_ms in our_vc_issuance_ms.iter().zip(pjv_vc_issuance_ms.iter()) {
-        writer.write_record_to_file(&String::from(IOP_VC_ISSUANCE), vc_issuance_ms)?;
+    let run_metadata = RunMetadata::collect(config.curve.name())?;
+    for (name, sweep) in sweeps {
+        let mut experiment = with_schemes::<E>(Experiment::new(name, sweep, Metric::ALL.to_vec(), config.benchmark()));
+        experiment.set_output_dir(&config.output_dir);
+        experiment.set_json_lines(config.json_lines);
+        experiment.set_run_metadata(run_metadata.clone());
+        experiment.run()?;
     }
 
-    let our_vp_lengths: Vec<usize> = our_vps.iter().map(|v| v.len()).collect();
-    let pjv_vp_lengths: Vec<usize> = pjv_vps.iter().map(|v| v.len()).collect();
-    for vp_length in our_vp_lengths.iter().zip(pjv_vp_lengths.iter()) {
-        writer.write_record_to_file(&String::from(IOP_VP_LENGTH), vp_length)?;
+    if config.plots {
+        let charts = render_charts(&config.output_dir, &config.plots_dir)?;
+        println!("Rendered {} charts to {}", charts.len(), config.plots_dir.display());
     }
-
-    let our_vp_issuance_ms: Vec<u128> = our_vp_issuance_duration.iter().map(Duration::as_micros).collect();
-    let pjv_vp_issuance_ms: Vec<u128> = pjv_vp_issuance_duration.iter().map(Duration::as_micros).collect();
-    for vp_issuance_duration in our_vp_issuance_ms.iter().zip(pjv_vp_issuance_ms.iter()) {
-        writer.write_record_to_file(&String::from(IOP_VP_ISSUANCE), vp_issuance_duration)?;
-    }
-
-    let our_vp_verification_ms: Vec<u128> = our_vp_verification_duration.iter().map(Duration::as_micros).collect();
-    let pjv_vp_verification_ms: Vec<u128> = pjv_vp_verification_duration.iter().map(Duration::as_micros).collect();
-    for vp_verification_ms in our_vp_verification_ms.iter().zip(pjv_vp_verification_ms.iter()) {
-        writer.write_record_to_file(&String::from(IOP_VP_VERIFICATION), vp_verification_ms)?;
-    }
-
     Ok(())
 }
 
-fn retain_permissions<E: Pairing>(delegators_size: usize, permissions_size: usize, retain_amount: usize, iterations: i8) -> Result<(), String> {
-
-    let retain_check = permissions_size / delegators_size;
-    if retain_check != retain_amount {
-        return Err(format!("Retain amount [{retain_amount}] must be equal to Permissions [{permissions_size}] / Delegators [{delegators_size}]"));
-    }
-
-    const RP_VC_ISSUANCE: &str = "rp_vc_issuance";
-    const RP_VP_LENGTH: &str = "rp_vp_jwt_length";
-    const RP_VP_ISSUANCE: &str = "rp_vp_issuance";
-    const RP_VP_VERIFICATION: &str = "rp_vp_verification";
-    let mut writer = CSVWriter::new(vec![String::from("Ours"), String::from("PJVs")])?;
-    writer.add_file(&String::from(RP_VC_ISSUANCE))?;
-    writer.add_file(&String::from(RP_VP_LENGTH))?;
-    writer.add_file(&String::from(RP_VP_ISSUANCE))?;
-    writer.add_file(&String::from(RP_VP_VERIFICATION))?;
-
-    // =============================================================================================
-    // ==================================        OURS        =======================================
-    // =============================================================================================
-    let mut our_vps: Vec<String> = vec![];
-    let mut our_vc_issuance_duration: Vec<Duration> = vec![];
-    let mut our_vp_issuance_duration: Vec<Duration> = vec![];
-    let mut our_vp_verification_duration: Vec<Duration> = vec![];
-
-    let (accumulator_dlt, verification_dlt, delegator_ids, credential_ids, delegators, context, valid_from, validity_period) = setup_ours(delegators_size)?;
-
-    let mut permissions: Vec<String> = vec![];
-    for i in 0..permissions_size {
-        permissions.push(format!("https://vc.example/resources/r1:p{i}"));
-    }
-
-    let mut vc = None;
-    for i in 0..delegators_size {
-        let delegator = get(&delegators, i)?;
-        let credential_id = get(&credential_ids, i)?;
-        let delegatee_id = get(&delegator_ids, i + 1)?;
-
-        let range = permissions_size - i * retain_amount;
-        let permissions_slice = match permissions.get(0..range) {
-            Some(permissions_slice) => permissions_slice,
-            None => return Err(String::from("Could not get slice from permissions"))
-        }.to_vec();
-
-        let (duration, result_vc) = Benchmark::benchmark_function(
-            || delegator.issue_delegation_verifiable_credential(
-                context.clone(), credential_id.clone(), valid_from.clone(), delegatee_id.clone(),
-                validity_period.clone(), permissions_slice.clone(), vc.clone()
-            ),
-            iterations
-        )?;
-        our_vc_issuance_duration.push(duration);
-
-        let (duration, result_vp) = Benchmark::benchmark_function(
-            || delegator.issue_delegation_verifiable_presentation(
-                result_vc.clone(), permissions_slice.clone()
-            ),
-            iterations
-        )?;
-        our_vp_issuance_duration.push(duration);
-
-        our_vps.push(result_vp);
-
-        vc = Some(result_vc);
-    }
-
-    let verifier: OurVerifier<E> = OurVerifier::new(accumulator_dlt, verification_dlt)?;
-
-    for (i, vp) in our_vps.iter().enumerate() {
-        let presenter_id = get(&delegator_ids, i)?;
-        let (duration, _) = Benchmark::benchmark_function(|| verifier.verify_verifiable_presentation(presenter_id.clone(), vp.clone(), true), iterations)?;
-        our_vp_verification_duration.push(duration);
-    }
-
-    // =============================================================================================
-    // ==================================        PJVS        =======================================
-    // =============================================================================================
-    let mut pjv_vps: Vec<String> = vec![];
-    let mut pjv_vc_issuance_duration: Vec<Duration> = vec![];
-    let mut pjv_vp_issuance_duration: Vec<Duration> = vec![];
-    let mut pjv_vp_verification_duration: Vec<Duration> = vec![];
-
-    let (delegator_ids, credential_ids, delegators, context, valid_from, validity_period, owner, resource_uri) = setup_pjvs(delegators_size)?;
-
-    let mut operations: Vec<String> = vec![];
-    for i in 0..permissions_size {
-        operations.push(format!("p{i}"));
-    }
-
-    let mut vc = None;
-    for i in 0..delegators_size {
-        let delegator = get(&delegators, i)?;
-        let credential_id = get(&credential_ids, i)?;
-        let delegatee_id = get(&delegator_ids, i + 1)?;
-
-        let range = permissions_size - i * retain_amount;
-        let operations_slice = match operations.get(0..range) {
-            Some(operations_slice) => operations_slice,
-            None => return Err(String::from("Could not get slice from operations"))
-        }.to_vec();
-
-        let (duration, result_vc) = Benchmark::benchmark_function(
-            || delegator.issue_delegation_verifiable_credential(
-                context.clone(), credential_id.clone(), valid_from.clone(), delegatee_id.clone(),
-                validity_period.clone(), owner.clone(), resource_uri.clone(), operations_slice.clone(), vc.clone()),
-            iterations
-        )?;
-        pjv_vc_issuance_duration.push(duration);
-
-        let (duration, result_vp) = Benchmark::benchmark_function(
-            || delegator.issue_delegation_verifiable_presentation(
-                result_vc.clone(), operations_slice.clone()
-            ),
-            iterations
-        )?;
-        pjv_vp_issuance_duration.push(duration);
-
-        pjv_vps.push(result_vp);
-
-        vc = Some(result_vc);
-    }
-
-    let verifier = get(&delegators, 0)?;
-
-    for (i, vp) in pjv_vps.iter().enumerate() {
-        let presenter_id = get(&delegator_ids, i)?;
-        let (duration, _) = Benchmark::benchmark_function(|| verifier.verify_verifiable_presentation(presenter_id.clone(), vp.clone()), iterations)?;
-        pjv_vp_verification_duration.push(duration);
-    }
-
-    let our_vc_issuance_ms: Vec<u128> = our_vc_issuance_duration.iter().map(Duration::as_micros).collect();
-    let pjv_vc_issuance_ms: Vec<u128> = pjv_vc_issuance_duration.iter().map(Duration::as_micros).collect();
-    for vc_issuance_ms in our_vc_issuance_ms.iter().zip(pjv_vc_issuance_ms.iter()) {
-        writer.write_record_to_file(&String::from(RP_VC_ISSUANCE), vc_issuance_ms)?;
-    }
-
-    let our_vp_lengths: Vec<usize> = our_vps.iter().map(|v| v.len()).collect();
-    let pjv_vp_lengths: Vec<usize> = pjv_vps.iter().map(|v| v.len()).collect();
-    for vp_length in our_vp_lengths.iter().zip(pjv_vp_lengths.iter()) {
-        writer.write_record_to_file(&String::from(RP_VP_LENGTH), vp_length)?;
-    }
-
-    let our_vp_issuance_ms: Vec<u128> = our_vp_issuance_duration.iter().map(Duration::as_micros).collect();
-    let pjv_vp_issuance_ms: Vec<u128> = pjv_vp_issuance_duration.iter().map(Duration::as_micros).collect();
-    for vp_issuance_duration in our_vp_issuance_ms.iter().zip(pjv_vp_issuance_ms.iter()) {
-        writer.write_record_to_file(&String::from(RP_VP_ISSUANCE), vp_issuance_duration)?;
-    }
+pub fn main() -> Result<(), Box<dyn Error>> {
+
+    let cli = Cli::parse();
+    let config = match &cli.command {
+        None => cli.config(BenchmarkConfig::default().experiments),
+        Some(Command::Experiment(experiment)) => cli.config(vec![experiment.clone()]),
+        Some(Command::Config { path }) => BenchmarkConfig::from_file(path)?,
+        Some(Command::Plot) => {
+            let charts = render_charts(&cli.output_dir, &cli.plots_dir)?;
+            println!("Rendered {} charts to {}", charts.len(), cli.plots_dir.display());
+            return Ok(())
+        }
+        Some(Command::Compare { baseline, threshold, length_threshold, allow_missing }) => {
+            return compare(baseline, &cli.output_dir, &Thresholds { duration: *threshold, length: *length_threshold }, *allow_missing)
+        }
+    };
 
-    let our_vp_verification_ms: Vec<u128> = our_vp_verification_duration.iter().map(Duration::as_micros).collect();
-    let pjv_vp_verification_ms: Vec<u128> = pjv_vp_verification_duration.iter().map(Duration::as_micros).collect();
-    for vp_verification_ms in our_vp_verification_ms.iter().zip(pjv_vp_verification_ms.iter()) {
-        writer.write_record_to_file(&String::from(RP_VP_VERIFICATION), vp_verification_ms)?;
+    match config.curve {
+        CurveName::Bn254 => run::<Bn254>(&config),
+        CurveName::Bls12_381 => run::<Bls12_381>(&config),
     }
 
-    Ok(())
 }
-
-pub fn main() -> Result<(), String> {
-
-    type Curve = Bn254;
-
-    // const DELEGATORS: &str = "DELEGATORS";
-    // const PERMISSIONS: &str = "PERMISSIONS";
-    // const ITERATIONS: &str = "ITERATIONS";
-
-    // let delegators = fetch_usize_env_variable(DELEGATORS)?;
-    // let permissions = fetch_usize_env_variable(PERMISSIONS)?;
-    // let iterations = fetch_usize_env_variable(ITERATIONS)? as i8;
-    
-    let delegators = 10;
-    let permissions = 10;
-    let iterations = 100;
-    let retain_amount = permissions / delegators;
-
-    iterate_over_delegators::<Curve>(delegators, permissions, 1, iterations)?;
-    iterate_over_delegators::<Curve>(delegators, permissions, 4, iterations)?;
-    iterate_over_delegators::<Curve>(delegators, permissions, 7, iterations)?;
-    iterate_over_delegators::<Curve>(delegators, permissions, 10, iterations)?;
-    iterate_over_permissions::<Curve>(delegators, permissions, iterations)?;
-    retain_permissions::<Curve>(delegators, permissions, retain_amount, iterations)?;
-    Ok(())
-
-}
\ No newline at end of file
@@ -0,0 +1,72 @@
+use crate::delegation::clock::clock::Clock;
+use crate::delegation::clock::system_clock::SystemClock;
+use crate::delegation::utils::utils::format_date_time;
+use serde::{Deserialize, Serialize};
+use std::env::consts::{ARCH, OS};
+use std::fs::read_to_string;
+use std::process::Command;
+use std::thread::available_parallelism;
+
+/// Machine running a benchmark.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Machine {
+    pub hostname: Option<String>,
+    pub os: String,
+    pub arch: String,
+    pub cpus: Option<usize>,
+}
+
+/// Description of a benchmark run, written next to its results so that they can be reproduced
+/// and compared.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunMetadata {
+    pub crate_version: String,
+    /// Commit of the repository the benchmark was built from, none outside of a git checkout.
+    pub commit: Option<String>,
+    /// Whether the checkout had uncommitted changes.
+    pub dirty: Option<bool>,
+    /// Curve of the accumulators of our scheme.
+    pub curve: String,
+    pub started_at: String,
+    pub machine: Machine,
+}
+
+impl RunMetadata {
+
+    /// Collects the metadata of a run starting now.
+    ///
+    /// # Arguments
+    /// * `curve` - Name of the curve of the accumulators of our scheme.
+    ///
+    /// # Returns
+    /// The metadata or a string containing an error, if the current time cannot be formatted.
+    pub fn collect(curve: &str) -> Result<Self, String> {
+        let started_at = match SystemClock.now().and_then(format_date_time) {
+            Ok(started_at) => started_at,
+            Err(err) => { return Err(format!("Could not format the start of the run [{err}]")) }
+        };
+
+        Ok(RunMetadata {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            commit: Self::git(&["rev-parse", "HEAD"]),
+            dirty: Self::git(&["status", "--porcelain"]).map(|status| !status.is_empty()),
+            curve: curve.to_string(),
+            started_at,
+            machine: Machine {
+                hostname: read_to_string("/etc/hostname").ok().map(|hostname| hostname.trim().to_string()).filter(|hostname| !hostname.is_empty()),
+                os: OS.to_string(),
+                arch: ARCH.to_string(),
+                cpus: available_parallelism().ok().map(|cpus| cpus.get()),
+            },
+        })
+    }
+
+    // Output of a git command run in the current directory, none if it fails
+    fn git(args: &[&str]) -> Option<String> {
+        let output = Command::new("git").args(args).output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        String::from_utf8(output.stdout).ok().map(|output| output.trim().to_string())
+    }
+}
@@ -0,0 +1,305 @@
+use crate::experiment::{Metric, PARAMETER_COLUMNS};
+use csv::Reader;
+use plotters::prelude::*;
+use std::fs::{create_dir_all, read_dir};
+use std::path::{Path, PathBuf};
+
+/// Permissions granted by the experiments of the paper, whose files have no parameter columns.
+const PAPER_PERMISSIONS: usize = 10;
+/// Colors and markers of the schemes of the paper, then of the other schemes in order.
+const OUR_COLOR: RGBColor = RGBColor(0xF2, 0xBB, 0x66);
+const PJV_COLOR: RGBColor = RGBColor(0x77, 0x9E, 0xCB);
+const OTHER_COLORS: [RGBColor; 3] = [RGBColor(0x8F, 0xBC, 0x8F), RGBColor(0xC3, 0x7B, 0x89), RGBColor(0x9A, 0x8F, 0xBC)];
+/// Size of the rendered charts, in pixels.
+const CHART_SIZE: (u32, u32) = (640, 480);
+
+/// Name of a scheme and its points.
+pub type Series = (String, Vec<(f64, f64)>);
+
+/// Chart of a metric of an experiment, read from the csv file written by the experiment.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chart {
+    /// Name of the csv file, without extension.
+    pub source: String,
+    /// Title of the chart and name of the rendered file, as the figures of the paper.
+    pub title: String,
+    pub x_label: String,
+    pub y_label: String,
+    /// Parameter column of the x-axis, the rows being numbered from 1 in files without parameters.
+    pub x_column: &'static str,
+    /// Permissions disclosed along the chain, when they are the same at every row.
+    pub disclosed: Option<String>,
+    /// Divisor of the values, from microseconds to milliseconds and from bytes to kilobytes.
+    pub scale: f64,
+}
+
+impl Chart {
+
+    /// Describes the chart of a file written by an experiment, named after the figures of the paper
+    /// for the experiments of the paper.
+    ///
+    /// # Arguments
+    /// * `experiment` - Name of the experiment, e.g. `1_iod`, `iop` or `rp`.
+    /// * `metric` - Metric of the file.
+    pub fn new(experiment: &str, metric: Metric) -> Self {
+        let (kind, disclosed, suffix) = Self::parse_name(experiment);
+
+        let (title, x_label, x_column) = match kind {
+            "iod" => {
+                let disclosures = if disclosed == Some("1") { "Disclosure" } else { "Disclosures" };
+                let title = format!("Iterating_Over_Delegators_{}_{disclosures}", disclosed.unwrap_or_default());
+                (title, "Delegation chain length", "delegators")
+            }
+            "iop" => (String::from("Iterating_Over_Permissions"), "Number of permissions", "permissions"),
+            "rp" => (String::from("Retain_Permissions"), "Delegation chain length", "delegators"),
+            _ => (experiment.to_string(), "Delegation chain length", "delegators"),
+        };
+
+        let (metric_title, y_label, scale) = match metric {
+            Metric::VpLength => ("JWT_Length", String::from("VP Size (KB)"), 1000.0),
+            Metric::VcIssuance => ("VC_Issuance", String::from("VC issuance time (ms)"), 1000.0),
+            Metric::VpIssuance => ("VP_Issuance", String::from("VP issuance time (ms)"), 1000.0),
+            Metric::VpVerification => ("VP_Verification", String::from("VP verification time (ms)"), 1000.0),
+        };
+
+        Chart {
+            source: format!("{experiment}_{}", metric.file_suffix()),
+            title: format!("{title}{suffix}_{metric_title}"),
+            x_label: x_label.to_string(),
+            y_label,
+            x_column,
+            disclosed: disclosed.filter(|_| metric == Metric::VpLength).map(str::to_string),
+            scale,
+        }
+    }
+
+    // Splits the name of an experiment into its kind, disclosures and the suffix of its parameters
+    fn parse_name(experiment: &str) -> (&str, Option<&str>, String) {
+        let mut parts: Vec<&str> = experiment.split('_').collect();
+        let disclosed = match parts.first() {
+            Some(part) if part.chars().all(|c| c.is_ascii_digit()) && parts.len() > 1 => Some(parts.remove(0)),
+            _ => None,
+        };
+        let kind = parts.first().copied().unwrap_or_default();
+        let suffix: String = parts.iter().skip(1).map(|part| format!("_{part}")).collect();
+        (kind, disclosed, suffix)
+    }
+
+    /// Describes the charts of the csv files of a directory, that is of the files named after a metric
+    /// of an experiment.
+    ///
+    /// # Arguments
+    /// * `dir` - Directory of the csv files.
+    ///
+    /// # Returns
+    /// The charts sorted by title or a string containing an error.
+    pub fn in_dir(dir: &Path) -> Result<Vec<Chart>, String> {
+        let entries = match read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) => { return Err(format!("Could not read directory {}: [{err}]", dir.display())) }
+        };
+
+        let mut charts: Vec<Chart> = vec![];
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|extension| extension != "csv") {
+                continue;
+            }
+            let Some(source) = path.file_stem().and_then(|stem| stem.to_str()) else { continue };
+
+            if let Some((experiment, metric)) = Metric::parse_file_name(source) {
+                charts.push(Chart::new(experiment, metric));
+            }
+        }
+        charts.sort_by(|a, b| a.title.cmp(&b.title));
+        Ok(charts)
+    }
+
+    /// Reads the series of the chart, one for each scheme.
+    ///
+    /// # Arguments
+    /// * `dir` - Directory of the csv file.
+    ///
+    /// # Returns
+    /// The name and points of each scheme, or a string containing an error.
+    pub fn read_series(&self, dir: &Path) -> Result<Vec<Series>, String> {
+        Ok(self.read(dir)?.0)
+    }
+
+    // Series of the file and the permissions granted at its first row
+    fn read(&self, dir: &Path) -> Result<(Vec<Series>, Option<f64>), String> {
+        let path = dir.join(format!("{}.csv", self.source));
+        let mut reader = match Reader::from_path(&path) {
+            Ok(reader) => reader,
+            Err(err) => { return Err(format!("Could not open {}: [{err}]", path.display())) }
+        };
+        let headers = match reader.headers() {
+            Ok(headers) => headers.clone(),
+            Err(err) => { return Err(format!("Could not read the columns of {}: [{err}]", path.display())) }
+        };
+
+        let x_index = headers.iter().position(|column| column == self.x_column);
+        let permissions_index = headers.iter().position(|column| column == "permissions");
+        let mut permissions = None;
+        let schemes: Vec<(usize, String)> = headers.iter().enumerate()
+            .filter(|(_, column)| !PARAMETER_COLUMNS.contains(column))
+            .map(|(i, column)| (i, column.to_string()))
+            .collect();
+
+        let mut series: Vec<Series> = schemes.iter().map(|(_, scheme)| (scheme.clone(), vec![])).collect();
+        for (row, record) in reader.records().enumerate() {
+            let record = match record {
+                Ok(record) => record,
+                Err(err) => { return Err(format!("Could not read row {row} of {}: [{err}]", path.display())) }
+            };
+            let x = match x_index {
+                Some(i) => Self::parse_value(record.get(i), &path)?,
+                None => (row + 1) as f64,
+            };
+            if let Some(i) = permissions_index && permissions.is_none() {
+                permissions = Some(Self::parse_value(record.get(i), &path)?);
+            }
+            for ((i, _), (_, points)) in schemes.iter().zip(series.iter_mut()) {
+                points.push((x, Self::parse_value(record.get(*i), &path)? / self.scale));
+            }
+        }
+        if permissions_index.is_none() {
+            permissions = Some(PAPER_PERMISSIONS as f64);
+        }
+        Ok((series, permissions))
+    }
+
+    fn parse_value(value: Option<&str>, path: &Path) -> Result<f64, String> {
+        match value.map(str::parse::<f64>) {
+            Some(Ok(value)) => Ok(value),
+            _ => { Err(format!("Invalid value {value:?} in {}", path.display())) }
+        }
+    }
+
+    /// Renders the chart to `{title}.svg`.
+    ///
+    /// # Arguments
+    /// * `input_dir` - Directory of the csv file.
+    /// * `output_dir` - Directory of the rendered chart, created if needed.
+    ///
+    /// # Returns
+    /// The path of the rendered chart or a string containing an error.
+    pub fn render(&self, input_dir: &Path, output_dir: &Path) -> Result<PathBuf, String> {
+        let (series, permissions) = self.read(input_dir)?;
+        let points = series.iter().flat_map(|(_, points)| points.iter());
+        let (x_min, x_max, y_max) = points.fold((f64::MAX, f64::MIN, 0f64), |(x_min, x_max, y_max), (x, y)| {
+            (x_min.min(*x), x_max.max(*x), y_max.max(*y))
+        });
+        if x_min > x_max {
+            return Err(format!("No rows to plot in {}", self.source))
+        }
+
+        if let Err(err) = create_dir_all(output_dir) {
+            return Err(format!("Error in creating plots folder: [{err}]"))
+        }
+        let y_label = match (&self.disclosed, permissions) {
+            (Some(disclosed), Some(permissions)) => format!("{} [{disclosed}/{permissions} Permissions Disclosed]", self.y_label),
+            _ => self.y_label.clone(),
+        };
+        let path = output_dir.join(format!("{}.svg", self.title));
+        let root = SVGBackend::new(&path, CHART_SIZE).into_drawing_area();
+        let result = self.draw(&root, &y_label, &series, (x_min - 0.1)..(x_max + 0.1), 0.0..(y_max * 1.1).max(1.0))
+            .map_err(|err| format!("Error in rendering {}: [{err}]", self.title));
+        drop(root);
+        result?;
+        Ok(path)
+    }
+
+    fn draw<DB: DrawingBackend>(&self, root: &DrawingArea<DB, plotters::coord::Shift>, y_label: &str, series: &[Series],
+                                x_range: std::ops::Range<f64>, y_range: std::ops::Range<f64>)
+                                -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+        root.fill(&WHITE)?;
+        let mut chart = ChartBuilder::on(root)
+            .caption(self.title.replace('_', " "), ("sans-serif", 18))
+            .margin(12)
+            .x_label_area_size(40)
+            .y_label_area_size(56)
+            .build_cartesian_2d(x_range, y_range)?;
+        chart.configure_mesh()
+            .x_desc(self.x_label.as_str())
+            .y_desc(y_label)
+            .light_line_style(WHITE.mix(0.0))
+            .draw()?;
+
+        let mut other_colors = OTHER_COLORS.iter().cycle();
+        for (scheme, points) in series {
+            let (label, color) = match scheme.as_str() {
+                "Ours" => ("Ours", OUR_COLOR),
+                "PJVs" => ("Vrielynck et al.", PJV_COLOR),
+                other => (other, *other_colors.next().unwrap_or(&BLACK)),
+            };
+            chart.draw_series(LineSeries::new(points.iter().copied(), color.stroke_width(2)))?
+                .label(label)
+                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 16, y)], color.stroke_width(2)));
+            chart.draw_series(points.iter().map(|point| Circle::new(*point, 3, color.filled())))?;
+        }
+
+        chart.configure_series_labels()
+            .position(SeriesLabelPosition::UpperLeft)
+            .background_style(WHITE.mix(0.6))
+            .border_style(BLACK.mix(0.4))
+            .draw()?;
+        root.present()
+    }
+}
+
+/// Renders the charts of every csv file of a directory written by the experiments.
+///
+/// # Arguments
+/// * `input_dir` - Directory of the csv files.
+/// * `output_dir` - Directory of the rendered charts.
+///
+/// # Returns
+/// The paths of the rendered charts or a string containing an error.
+pub fn render_charts(input_dir: &Path, output_dir: &Path) -> Result<Vec<PathBuf>, String> {
+    Chart::in_dir(input_dir)?.iter().map(|chart| chart.render(input_dir, output_dir)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name_charts_as_the_paper() {
+        let chart = Chart::new("1_iod", Metric::VpLength);
+        assert_eq!(chart.title, "Iterating_Over_Delegators_1_Disclosure_JWT_Length");
+        assert_eq!(chart.disclosed, Some(String::from("1")));
+        assert_eq!(chart.source, "1_iod_vp_jwt_length");
+
+        assert_eq!(Chart::new("10_iod", Metric::VpLength).title, "Iterating_Over_Delegators_10_Disclosures_JWT_Length");
+        assert_eq!(Chart::new("iop", Metric::VpLength).title, "Iterating_Over_Permissions_JWT_Length");
+        assert_eq!(Chart::new("iop", Metric::VpLength).x_column, "permissions");
+        assert_eq!(Chart::new("rp", Metric::VpVerification).title, "Retain_Permissions_VP_Verification");
+        assert_eq!(Chart::new("rp_d5_p10", Metric::VcIssuance).title, "Retain_Permissions_d5_p10_VC_Issuance");
+    }
+
+    #[test]
+    fn render_every_chart() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = std::env::temp_dir().join(format!("delegation-report-{}", std::process::id()));
+        create_dir_all(&dir)?;
+        // Files of the paper have no parameter columns, the rows being the x-axis
+        std::fs::write(dir.join("iop_vp_jwt_length.csv"), "Ours,PJVs\n1822,2233\n1995,2249\n")?;
+        std::fs::write(dir.join("rp_vp_verification.csv"), "delegators,permissions,disclosed,Ours,Ours (batched),PJVs\n1,2,2,1000,500,10\n2,1,1,2000,900,20\n")?;
+        std::fs::write(dir.join("rp_vp_verification_stats.csv"), "delegators,scheme,mean\n1,Ours,1000\n")?;
+
+        let charts = Chart::in_dir(&dir)?;
+        assert_eq!(charts.len(), 2);
+        let series = charts[1].read_series(&dir)?;
+        assert_eq!(series[1], (String::from("Ours (batched)"), vec![(1.0, 0.5), (2.0, 0.9)]));
+        assert_eq!(charts[0].read_series(&dir)?[1].1, vec![(1.0, 2.233), (2.0, 2.249)]);
+
+        let paths = render_charts(&dir, &dir.join("plots"))?;
+        assert_eq!(paths.len(), 2);
+        let svg = std::fs::read_to_string(&paths[0])?;
+        assert!(svg.contains("Vrielynck et al."));
+        assert!(paths[1].ends_with("Retain_Permissions_VP_Verification.svg"));
+
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}